@@ -0,0 +1,89 @@
+//! Serves an agent defined in a TOML file over MCP.
+//!
+//! ```toml
+//! name = "researcher"
+//! model = "gpt-4o-mini"
+//! preamble = "You are a research assistant."
+//! # The built-in tools of the agent.
+//! tools = ["search"]
+//! # The MCP servers used by the agent, see `servers_config.json`.
+//! mcp_config = "servers_config.json"
+//! # Either `stdio` or `sse`.
+//! transport = "sse"
+//! address = "127.0.0.1:8000"
+//! ```
+//!
+//! Run it with `alith-mcp-server agent.toml`.
+
+use alith::{Agent, MCPServer, SearchTool, Tool, LLM};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+#[derive(Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Transport {
+    #[default]
+    Stdio,
+    Sse,
+}
+
+#[derive(Deserialize)]
+struct ServerConfig {
+    name: String,
+    model: String,
+    #[serde(default)]
+    preamble: String,
+    #[serde(default)]
+    tools: Vec<String>,
+    mcp_config: Option<PathBuf>,
+    /// The name of the tool prompting the agent.
+    prompt_tool: Option<String>,
+    #[serde(default)]
+    transport: Transport,
+    #[serde(default = "default_address")]
+    address: SocketAddr,
+}
+
+fn default_address() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8000))
+}
+
+fn tool(name: &str) -> Result<Box<dyn Tool>> {
+    match name {
+        "search" => Ok(Box::new(SearchTool::default())),
+        _ => bail!("unknown tool {name}"),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let Some(path) = std::env::args().nth(1) else {
+        bail!("usage: alith-mcp-server <config.toml>");
+    };
+    let config: ServerConfig = toml::from_str(
+        &std::fs::read_to_string(&path).with_context(|| format!("failed to read {path}"))?,
+    )?;
+
+    let model = LLM::from_model_name(&config.model)?;
+    let tools = config
+        .tools
+        .iter()
+        .map(|name| tool(name))
+        .collect::<Result<Vec<_>>>()?;
+    let mut agent = Agent::new_with_tools(&config.name, model, tools).preamble(&config.preamble);
+    if let Some(mcp_config) = &config.mcp_config {
+        agent = agent.mcp_config_path(mcp_config).await?;
+    }
+
+    let mut server = MCPServer::new(agent);
+    if let Some(prompt_tool) = &config.prompt_tool {
+        server = server.prompt_tool(prompt_tool);
+    }
+    match config.transport {
+        Transport::Stdio => server.serve_stdio().await?,
+        Transport::Sse => server.serve_sse(config.address).await?,
+    }
+    Ok(())
+}
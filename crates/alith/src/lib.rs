@@ -17,7 +17,9 @@ pub use core::llm::{
 pub use core::{
     agent::Agent,
     chat::{
-        Completion, CompletionError, Prompt, Request, ResponseContent, ResponseToolCalls, ToolCall,
+        BudgetExceeded, CancellationToken, Completion, CompletionError, JsonSchemaFormat, Prompt,
+        ReasoningEffort, Request, ResponseContent, ResponseFormat, ResponseToolCalls,
+        ResponseUsage, ToolCall, UsageRecord,
     },
     chunking::{
         chunk_text, ChunkError, Chunker, ChunkerConfig, ChunkerResult, TextChunker,
@@ -27,7 +29,12 @@ pub use core::{
         normalize_whitespace, reduce_to_single_whitespace, strip_unwanted_chars, TextCleaner,
     },
     concatenator::{TextConcatenator, TextConcatenatorTrait},
-    embeddings::{Embed, EmbedError, Embeddings, EmbeddingsBuilder, EmbeddingsData, TextEmbedder},
+    embeddings::{
+        cohere::{CohereEmbeddings, CohereInputType, CohereTruncate},
+        jina::{JinaEmbeddings, JinaTask},
+        voyage::{VoyageEmbeddings, VoyageInputType},
+        Embed, EmbedError, Embeddings, EmbeddingsBuilder, EmbeddingsData, TextEmbedder,
+    },
     extractor::{ExtractionError, Extractor},
     flow::{
         auto_node, dependencies, Action, Content, DefaultNode, EmptyAction, EnvVar, Graph,
@@ -37,10 +44,15 @@ pub use core::{
         parse_and_check_json_markdown, parse_json_markdown, parse_partial_json, JsonParseError,
     },
     knowledge::{FileKnowledge, Knowledge, KnowledgeError},
-    llm::{EmbeddingsModel, LLM},
+    llm::{
+        EmbeddingsModel, ProviderHealth, RoutedResponse, RouterLLM, RoutingStrategy, UsageLedger,
+        UsageTotals, LLM,
+    },
     mcp::{
         setup_mcp_clients, sse_client, stdio_client, ClientCapabilities, ClientInfo, MCPClient,
-        MCPConfig, MCPError, MCPServerConfig, SseTransport, StdioTransport, Transport,
+        MCPConfig, MCPError, MCPHealth, MCPResourceKnowledge, MCPServer, MCPServerConfig,
+        MCPTransportKind, SseHttpTransport, SseTransport, StdioTransport, StreamableHttpTransport,
+        Transport,
     },
     memory::{Memory, Message, MessageType, RLUCacheMemory, WindowBufferMemory},
     parser::{JsonParser, MarkdownParser, Parser, ParserError, StringParser, TrimParser},
@@ -60,16 +72,28 @@ pub use knowledge::{
     text::TextFileKnowledge,
 };
 pub use store::qdrant::*;
-pub use tools::search::{Search, SearchProvider, SearchResult, SearchResults, SearchTool};
+pub use tools::client::twitter::{TwitterAuth, TwitterClient};
+pub use tools::search::{
+    Search, SearchError, SearchProvider, SearchResult, SearchResults, SearchTool,
+};
+pub use tools::web3::evm::{EvmConfig, EvmError};
+pub use tools::web3::policy::{ChainPolicy, PolicyEngine, SpendLimit, Web3Policy};
+pub use tools::web3::solana::{SolanaConfig, SolanaError};
 
 pub use client::{
+    interface::llms::api::openai::responses::OpenAIBuiltInTool,
     interface::llms::LLMBackend,
     interface::requests::completion::{CompletionFinishReason, GenerationSettings},
     interface::LLMInterface,
     CompletionRequest, CompletionResponse, EmbeddingsRequest, EmbeddingsResponse,
 };
 pub use models::{
-    api_model::ApiLLMModel,
+    api_model::{
+        registry::{
+            ModelEntry, ModelPricing, ModelProvider, ModelRegistry, ResolvedModel, TokenizerChoice,
+        },
+        ApiLLMModel, ApiModelCapabilities,
+    },
     local_model::{
         gguf::{
             preset::{LLMPreset, LLMPresetData, TokenizerConfigPresetData, TokenizerPresetData},
@@ -1,7 +1,9 @@
+use crate::ledger::{UsageLedger, UsageRecord};
+use alith_interface::CancellationToken;
 use alith_interface::{
     llms::LLMBackend,
     requests::{
-        completion::{CompletionRequest, CompletionResponse},
+        completion::{CompletionError, CompletionRequest, CompletionResponse},
         logit_bias::{LogitBias, LogitBiasTrait},
         req_components::{RequestConfig, RequestConfigTrait},
     },
@@ -12,6 +14,7 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub struct BasicCompletion {
     pub base_req: CompletionRequest,
+    pub ledger: Option<UsageLedger>,
 }
 
 impl BasicCompletion {
@@ -19,6 +22,7 @@ impl BasicCompletion {
     pub fn new(backend: Arc<LLMBackend>) -> Self {
         Self {
             base_req: CompletionRequest::new(backend),
+            ledger: None,
         }
     }
 
@@ -27,9 +31,36 @@ impl BasicCompletion {
         &mut self.base_req.prompt
     }
 
+    /// Aborts the request once the token is cancelled.
+    #[inline]
+    pub fn cancellation_token(&mut self, token: CancellationToken) -> &mut Self {
+        self.base_req.cancellation_token = Some(token);
+        self
+    }
+
     #[inline]
     pub async fn run(&mut self) -> crate::Result<CompletionResponse> {
-        Ok(self.base_req.request().await?)
+        if let Some(ledger) = &self.ledger {
+            ledger.check_budget()?;
+        }
+        let response = match self.base_req.request().await {
+            Ok(response) => response,
+            Err(CompletionError::Cancelled { usage }) => {
+                // Discarded retries are billed as well
+                if let Some(ledger) = self.ledger.as_ref().filter(|_| usage.total_tokens > 0) {
+                    ledger.record(UsageRecord::from_usage(
+                        self.base_req.backend.model_id(),
+                        &usage,
+                    ));
+                }
+                return Err(CompletionError::Cancelled { usage }.into());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(ledger) = &self.ledger {
+            ledger.record_response(&response);
+        }
+        Ok(response)
     }
 
     pub fn parse_response(&self, content: &str) -> crate::Result<String> {
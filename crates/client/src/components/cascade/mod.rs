@@ -8,7 +8,7 @@ use alith_interface::requests::{
 use anyhow::{anyhow, Result};
 use core::panic;
 pub use round::CascadeRound;
-use step::InferenceStep;
+use step::{InferenceChoice, InferenceStep};
 
 #[derive(Clone)]
 pub struct CascadeFlow {
@@ -80,6 +80,14 @@ impl CascadeFlow {
             None => panic!("No rounds in cascade"),
         }
     }
+
+    /// Every choice of the last step, when it requested more than one.
+    pub fn primitive_choices(&self) -> Vec<InferenceChoice> {
+        match self.rounds.last() {
+            Some(round) => round.primitive_choices(),
+            None => panic!("No rounds in cascade"),
+        }
+    }
 }
 
 pub(crate) async fn cascade_request(
@@ -87,6 +95,26 @@ pub(crate) async fn cascade_request(
     step: &mut InferenceStep,
 ) -> Result<()> {
     let res = base_req.request().await?;
+    step.choices = if res.choices.len() > 1 {
+        res.choices
+            .iter()
+            .map(|choice| InferenceChoice {
+                llm_content: match choice.finish_reason {
+                    CompletionFinishReason::MatchingStoppingSequence(
+                        StoppingSequence::NoResult(_),
+                    ) => None,
+                    _ => step
+                        .step_config
+                        .grammar
+                        .validate_clean(&choice.content)
+                        .ok(),
+                },
+                confidence: choice.confidence(),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
     if matches!(
         res.finish_reason,
         CompletionFinishReason::MatchingStoppingSequence(StoppingSequence::NoResult(_))
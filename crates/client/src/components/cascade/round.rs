@@ -1,4 +1,4 @@
-use super::step::{CascadeStep, StepConfig};
+use super::step::{CascadeStep, InferenceChoice, StepConfig};
 use alith_interface::requests::completion::CompletionRequest;
 use std::collections::VecDeque;
 
@@ -160,6 +160,14 @@ impl CascadeRound {
         }
     }
 
+    pub fn primitive_choices(&self) -> Vec<InferenceChoice> {
+        if let Some(step) = self.resolved_steps.back() {
+            step.primitive_choices()
+        } else {
+            Vec::new()
+        }
+    }
+
     pub fn open_round(&mut self, base_req: &mut CompletionRequest) -> crate::Result<()> {
         base_req.prompt.add_user_message()?.set_content(&self.task);
         Ok(())
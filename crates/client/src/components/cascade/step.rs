@@ -13,6 +13,7 @@ impl CascadeStep {
     pub fn new_inference_step(step_config: StepConfig, step_counter: usize) -> Self {
         CascadeStep::Inference(InferenceStep {
             llm_content: None,
+            choices: Vec::new(),
             dynamic_suffix: None,
             outcome: RefCell::new(None),
             step_config,
@@ -86,11 +87,28 @@ impl CascadeStep {
             Self::Guidance(_) => panic!("GuidanceStep does not have primitive_result."),
         }
     }
+
+    pub fn primitive_choices(&self) -> Vec<InferenceChoice> {
+        match self {
+            Self::Inference(step) => step.choices.clone(),
+            Self::Guidance(_) => panic!("GuidanceStep does not have primitive_choices."),
+        }
+    }
+}
+
+/// One choice of an inference step configured with [`StepConfig::n_choices`] above `1`.
+#[derive(Clone, Debug)]
+pub struct InferenceChoice {
+    /// The cleaned result of the choice, `None` if the result is none or invalid.
+    pub llm_content: Option<String>,
+    /// The mean probability of the generated tokens.
+    pub confidence: Option<f32>,
 }
 
 #[derive(Clone)]
 pub struct InferenceStep {
     pub llm_content: Option<String>, // raw, unformatted result from llm.
+    pub choices: Vec<InferenceChoice>, // every choice when more than one is requested.
     pub dynamic_suffix: Option<String>, // suffix to be added to the result.
     pub outcome: RefCell<Option<String>>,
     pub step_config: StepConfig,
@@ -139,6 +157,9 @@ impl InferenceStep {
         base_req.logit_bias = Some(self.step_config.logit_bias.clone());
 
         base_req.config.cache_prompt = self.step_config.cache_prompt;
+        // Request choices
+        base_req.config.n_choices = self.step_config.n_choices;
+        base_req.config.logprobs = self.step_config.n_choices > 1;
         cascade_request(base_req, self).await
     }
 
@@ -195,6 +216,9 @@ pub struct StepConfig {
     pub cache_prompt: bool,
     pub grammar: Grammar,
     pub logit_bias: LogitBias,
+    /// The number of choices to request. The first one continues the cascade, the others are
+    /// kept in [`InferenceStep::choices`] for voting.
+    pub n_choices: u8,
 }
 
 impl Default for StepConfig {
@@ -207,6 +231,7 @@ impl Default for StepConfig {
             cache_prompt: true,
             grammar: Grammar::default(),
             logit_bias: LogitBias::default(),
+            n_choices: 1,
         }
     }
 }
@@ -242,6 +267,11 @@ impl StepConfig {
         self
     }
 
+    pub fn n_choices(&mut self, n_choices: u8) -> &mut Self {
+        self.n_choices = n_choices.max(1);
+        self
+    }
+
     fn display_prefix(&self, step_counter: usize) -> Option<String> {
         match (self.use_counter, &self.step_prefix) {
             (true, Some(step_prefix)) => Some(format!("{} {}", step_counter, step_prefix)),
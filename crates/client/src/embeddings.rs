@@ -1,3 +1,4 @@
+use alith_interface::CancellationToken;
 use alith_interface::{
     llms::LLMBackend,
     requests::embeddings::{EmbeddingsRequest, EmbeddingsResponse},
@@ -31,4 +32,10 @@ impl Embeddings {
     pub fn set_model(&mut self, model: String) {
         self.req.model = model;
     }
+
+    /// Aborts the request once the token is cancelled.
+    #[inline]
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.req.cancellation_token = Some(token);
+    }
 }
@@ -0,0 +1,276 @@
+use alith_interface::requests::completion::{CompletionResponse, TokenUsage};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// The usage of a single completion request.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageRecord {
+    /// The id of the completion response.
+    pub id: String,
+    pub model: String,
+    /// The name of the agent that sent the request, if any.
+    pub agent: Option<String>,
+    /// The index of the request in the tool loop of an agent run.
+    pub step: Option<usize>,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub tokens_cached: u32,
+    /// `None` when the model pricing is unknown.
+    pub dollar_cost: Option<f32>,
+}
+
+impl UsageRecord {
+    pub fn from_response(response: &CompletionResponse) -> Self {
+        Self {
+            id: response.id.clone(),
+            model: response.generation_settings.model.clone(),
+            agent: None,
+            step: None,
+            prompt_tokens: response.token_usage.prompt_tokens,
+            completion_tokens: response.token_usage.completion_tokens,
+            tokens_cached: response.token_usage.tokens_cached.unwrap_or_default(),
+            dollar_cost: response.token_usage.dollar_cost,
+        }
+    }
+
+    /// The usage of a request without a response, e.g. the discarded retries of a cancelled one.
+    pub fn from_usage<S: Into<String>>(model: S, usage: &TokenUsage) -> Self {
+        Self {
+            model: model.into(),
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            tokens_cached: usage.tokens_cached.unwrap_or_default(),
+            dollar_cost: usage.dollar_cost,
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    pub fn agent<S: Into<String>>(mut self, agent: S) -> Self {
+        self.agent = Some(agent.into());
+        self
+    }
+
+    #[inline]
+    pub fn step(mut self, step: usize) -> Self {
+        self.step = Some(step);
+        self
+    }
+}
+
+/// Aggregated usage of a set of requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cached_tokens: u64,
+    pub total_tokens: u64,
+    /// The cost of the requests with a known model pricing.
+    pub dollar_cost: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, record: &UsageRecord) {
+        self.requests += 1;
+        self.prompt_tokens += record.prompt_tokens as u64;
+        self.completion_tokens += record.completion_tokens as u64;
+        self.cached_tokens += record.tokens_cached as u64;
+        self.total_tokens += (record.prompt_tokens + record.completion_tokens) as u64;
+        self.dollar_cost += record.dollar_cost.unwrap_or_default() as f64;
+    }
+}
+
+/// Returned when a request is attempted after the ledger budget was spent.
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+#[error("Usage budget exceeded: spent ${spent:.4} of ${budget:.4}")]
+pub struct BudgetExceeded {
+    pub budget: f64,
+    pub spent: f64,
+}
+
+#[derive(Debug, Default)]
+struct LedgerState {
+    records: Vec<UsageRecord>,
+    ids: HashSet<String>,
+    budget: Option<f64>,
+}
+
+/// A shareable ledger of token usage and dollar cost.
+///
+/// Clones share the same records, so one ledger can be attached to several clients and agents
+/// to track a whole run. Responses are recorded once by id, so attaching the same ledger to an
+/// agent and to its model doesn't count a request twice.
+///
+/// # Example
+///
+/// ```no_run
+/// use alith_client::ledger::UsageLedger;
+///
+/// let ledger = UsageLedger::new().with_budget(0.50);
+/// // Attach `ledger.clone()` to clients and agents, then:
+/// println!("{}", ledger.to_json().unwrap());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UsageLedger {
+    state: Arc<Mutex<LedgerState>>,
+}
+
+impl UsageLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a cap in dollars. Requests fail with [`BudgetExceeded`] once it is spent.
+    pub fn with_budget(self, dollars: f64) -> Self {
+        self.set_budget(Some(dollars));
+        self
+    }
+
+    pub fn set_budget(&self, dollars: Option<f64>) {
+        self.lock().budget = dollars;
+    }
+
+    pub fn budget(&self) -> Option<f64> {
+        self.lock().budget
+    }
+
+    /// Records the usage of a request. A record with an already recorded id only fills in the
+    /// agent and step labels of the existing one.
+    pub fn record(&self, record: UsageRecord) {
+        let mut state = self.lock();
+        if !record.id.is_empty() && !state.ids.insert(record.id.clone()) {
+            if let Some(existing) = state.records.iter_mut().find(|r| r.id == record.id) {
+                existing.agent = existing.agent.take().or(record.agent);
+                existing.step = existing.step.or(record.step);
+            }
+            return;
+        }
+        state.records.push(record);
+    }
+
+    #[inline]
+    pub fn record_response(&self, response: &CompletionResponse) {
+        self.record(UsageRecord::from_response(response));
+    }
+
+    /// Fails when the budget is already spent.
+    pub fn check_budget(&self) -> Result<(), BudgetExceeded> {
+        let state = self.lock();
+        if let Some(budget) = state.budget {
+            let spent = state
+                .records
+                .iter()
+                .map(|r| r.dollar_cost.unwrap_or_default() as f64)
+                .sum();
+            if spent >= budget {
+                return Err(BudgetExceeded { budget, spent });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn records(&self) -> Vec<UsageRecord> {
+        self.lock().records.clone()
+    }
+
+    pub fn totals(&self) -> UsageTotals {
+        let mut totals = UsageTotals::default();
+        self.lock().records.iter().for_each(|r| totals.add(r));
+        totals
+    }
+
+    pub fn by_model(&self) -> BTreeMap<String, UsageTotals> {
+        self.group_by(|r| Some(r.model.clone()))
+    }
+
+    /// Totals of the requests sent by agents, keyed by agent name.
+    pub fn by_agent(&self) -> BTreeMap<String, UsageTotals> {
+        self.group_by(|r| r.agent.clone())
+    }
+
+    /// Totals of the requests sent by agents, keyed by tool loop step.
+    pub fn by_step(&self) -> BTreeMap<usize, UsageTotals> {
+        self.group_by(|r| r.step)
+    }
+
+    /// The totals and breakdowns of the ledger as a JSON string.
+    pub fn to_json(&self) -> crate::Result<String> {
+        Ok(serde_json::to_string_pretty(&serde_json::json!({
+            "budget": self.budget(),
+            "totals": self.totals(),
+            "by_model": self.by_model(),
+            "by_agent": self.by_agent(),
+            "by_step": self.by_step(),
+            "records": self.records(),
+        }))?)
+    }
+
+    pub fn export_json<P: AsRef<Path>>(&self, path: P) -> crate::Result<()> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    fn group_by<K: Ord>(
+        &self,
+        key: impl Fn(&UsageRecord) -> Option<K>,
+    ) -> BTreeMap<K, UsageTotals> {
+        let mut groups = BTreeMap::<K, UsageTotals>::new();
+        for record in self.lock().records.iter() {
+            if let Some(key) = key(record) {
+                groups.entry(key).or_default().add(record);
+            }
+        }
+        groups
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LedgerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, model: &str, dollar_cost: f32) -> UsageRecord {
+        UsageRecord {
+            id: id.to_string(),
+            model: model.to_string(),
+            prompt_tokens: 100,
+            completion_tokens: 50,
+            dollar_cost: Some(dollar_cost),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ledger_breakdown_and_budget() {
+        let ledger = UsageLedger::new().with_budget(0.5);
+        ledger.record(record("a", "gpt-4o", 0.25));
+        // The same response seen by the agent only adds its labels.
+        ledger
+            .clone()
+            .record(record("a", "gpt-4o", 0.25).agent("writer").step(0));
+        ledger.record(
+            record("b", "claude-3-5-sonnet", 0.1)
+                .agent("writer")
+                .step(1),
+        );
+        assert!(ledger.check_budget().is_ok());
+
+        let totals = ledger.totals();
+        assert_eq!(totals.requests, 2);
+        assert_eq!(totals.total_tokens, 300);
+        assert_eq!(ledger.by_model().len(), 2);
+        assert_eq!(ledger.by_agent()["writer"].requests, 2);
+        assert_eq!(ledger.by_step()[&1].requests, 1);
+
+        ledger.record(record("c", "gpt-4o", 0.2));
+        let err = ledger.check_budget().unwrap_err();
+        assert_eq!(err.budget, 0.5);
+        assert!(ledger.to_json().unwrap().contains("\"by_model\""));
+    }
+}
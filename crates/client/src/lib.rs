@@ -2,6 +2,7 @@ pub mod backend_builders;
 pub mod basic_completion;
 pub mod components;
 pub mod embeddings;
+pub mod ledger;
 pub mod prelude;
 pub mod primitives;
 pub mod workflows;
@@ -22,6 +23,7 @@ use backend_builders::openai::OpenAIBackendBuilder;
 use backend_builders::perplexity::PerplexityBackendBuilder;
 use basic_completion::BasicCompletion;
 use embeddings::Embeddings;
+use ledger::UsageLedger;
 use std::sync::Arc;
 use workflows::basic_primitive::BasicPrimitiveWorkflowBuilder;
 use workflows::nlp::Nlp;
@@ -30,12 +32,24 @@ use workflows::reason::ReasonWorkflowBuilder;
 #[derive(Clone)]
 pub struct LLMClient {
     pub backend: Arc<LLMBackend>,
+    /// Records the usage of the basic completions sent through the client.
+    pub ledger: Option<UsageLedger>,
 }
 
 impl LLMClient {
     #[inline]
     pub fn new(backend: Arc<LLMBackend>) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            ledger: None,
+        }
+    }
+
+    /// Records usage in the ledger and enforces its budget. Clones of the client share it.
+    #[inline]
+    pub fn with_ledger(mut self, ledger: UsageLedger) -> Self {
+        self.ledger = Some(ledger);
+        self
     }
 
     /// Creates a new instance of the [`OpenAIBackendBuilder`]. This builder that allows you to specify the model and other parameters. It is converted to an `LLMClient` instance using the `init` method.
@@ -63,7 +77,9 @@ impl LLMClient {
 
     #[inline]
     pub fn basic_completion(&self) -> BasicCompletion {
-        BasicCompletion::new(self.backend.clone())
+        let mut completion = BasicCompletion::new(self.backend.clone());
+        completion.ledger = self.ledger.clone();
+        completion
     }
 
     #[inline]
@@ -91,6 +107,12 @@ impl LLMClient {
         CompletionRequest::new(self.backend.clone())
     }
 
+    /// A job sending many completion requests to the batch API of the backend.
+    #[inline]
+    pub fn batch_job(&self) -> BatchJob {
+        BatchJob::new(self.backend.clone())
+    }
+
     #[inline]
     pub fn embeddings_request(&self) -> EmbeddingsRequest {
         EmbeddingsRequest::new(self.backend.clone())
@@ -13,6 +13,7 @@ pub use alith_devices::devices::MetalConfig;
 pub use alith_interface::{
     llms::local::LLMLocalTrait,
     requests::{
+        batch::{BatchJob, BatchStatus},
         completion::{CompletionRequest, CompletionResponse},
         embeddings::{EmbeddingsRequest, EmbeddingsResponse},
         logit_bias::LogitBiasTrait,
@@ -18,6 +18,7 @@ pub struct Decision<D: DecisionTrait> {
     pub dynamic_temperature: bool,
     pub reason: D,
     pub result_can_be_none: bool,
+    pub single_request: bool,
 }
 
 impl<D: DecisionTrait> Decision<D> {
@@ -68,6 +69,20 @@ impl<D: DecisionTrait> Decision<D> {
     }
 
     async fn run_decision(&mut self) -> crate::Result<DecisionResult> {
+        if self.single_request
+            && self.best_of_n_votes > 1
+            && self.base_req.backend.supports_n_choices()
+        {
+            if let Some(decision_result) = self.run_single_request_decision().await? {
+                return Ok(decision_result);
+            }
+        }
+        self.run_votes_decision().await
+    }
+
+    /// Votes over the solutions of one request per vote.
+    async fn run_votes_decision(&mut self) -> crate::Result<DecisionResult> {
+        self.reason.set_solution_choices(1);
         let start = std::time::Instant::now();
         let mut decision_result = DecisionResult::new();
         let mut failed_attempts = 0;
@@ -154,6 +169,93 @@ impl<D: DecisionTrait> Decision<D> {
         ))
     }
 
+    /// Votes over the solutions of a single request, weighted by their token confidence.
+    ///
+    /// Returns `None` when the backend ignored the number of choices, so the votes are
+    /// requested one by one instead.
+    async fn run_single_request_decision(&mut self) -> crate::Result<Option<DecisionResult>> {
+        let start = std::time::Instant::now();
+        let mut failed_attempts = 0;
+
+        while failed_attempts < self.base_req.config.retry_after_fail_n_times {
+            *self.reason.base_req_mut() = self.base_req.clone();
+            self.reason.set_solution_choices(self.best_of_n_votes);
+            let reason_result = match self
+                .reason
+                .return_reason_result(self.result_can_be_none)
+                .await
+            {
+                Ok(reason_result) => reason_result,
+                Err(_) => {
+                    failed_attempts += 1;
+                    continue;
+                }
+            };
+            if reason_result.choices.len() != self.best_of_n_votes as usize {
+                tracing::warn!(
+                    "Requested {} choices but received {}, voting with one request per vote",
+                    self.best_of_n_votes,
+                    reason_result.choices.len()
+                );
+                return Ok(None);
+            }
+
+            let mut decision_result = DecisionResult::new();
+            let mut none_count = 0;
+            let mut weights: HashMap<Option<u32>, f32> = HashMap::new();
+            for choice in &reason_result.choices {
+                if choice.result_index.is_none() && !self.result_can_be_none {
+                    continue;
+                }
+                decision_result.total_votes += 1;
+                *weights.entry(choice.result_index).or_insert(0.0) +=
+                    choice.confidence.unwrap_or(1.0);
+                match choice.result_index {
+                    Some(result_index) => {
+                        *decision_result.votes.entry(result_index).or_insert(0) += 1
+                    }
+                    None => none_count += 1,
+                }
+            }
+            let Some((winner_index, winner_weight)) = weights
+                .iter()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(index, weight)| (*index, *weight))
+            else {
+                failed_attempts += 1;
+                continue;
+            };
+
+            let total_weight: f32 = weights.values().sum();
+            decision_result.confidence = if total_weight > 0.0 {
+                winner_weight / total_weight
+            } else {
+                0.0
+            };
+            decision_result.winner_index = winner_index;
+            decision_result.winner_votes = match winner_index {
+                Some(index) => decision_result.votes[&index],
+                None => none_count,
+            };
+            decision_result.winner_primitive_result = Some(
+                reason_result
+                    .choices
+                    .iter()
+                    .find(|choice| choice.result_index == winner_index)
+                    .and_then(|choice| choice.primitive_result.clone())
+                    .unwrap_or_else(|| "none".to_string()),
+            );
+            decision_result.duration = start.elapsed();
+            decision_result.reason_results.push(reason_result);
+            tracing::info!("{}", decision_result.to_string());
+            return Ok(Some(decision_result));
+        }
+        Err(anyhow::format_err!(
+            "BaseDecider: failed to get a valid response after {}",
+            failed_attempts
+        ))
+    }
+
     fn set_dynamic_temperature_on_initial(
         &mut self,
         dynamic_temperature: bool,
@@ -203,6 +305,13 @@ impl<D: DecisionTrait> Decision<D> {
         self
     }
 
+    /// Requests every vote as a choice of a single request, and weights the votes by the token
+    /// confidence of each choice. Backends without multiple choices send one request per vote.
+    pub fn single_request(&mut self, single_request: bool) -> &mut Self {
+        self.single_request = single_request;
+        self
+    }
+
     /// Dynamically scales temperature during the voting process. Starts at a low temperature and increases towards max temperature as the number of votes increases.
     pub fn dynamic_temperature(&mut self, dynamic_temperature: bool) -> &mut Self {
         self.dynamic_temperature = dynamic_temperature;
@@ -219,6 +328,9 @@ pub trait DecisionTrait: Sized + InstructPromptTrait {
 
     fn primitive(&self) -> &Self::ReasonPrimitive;
 
+    /// Sets the number of solutions the reason workflow requests in its final step.
+    fn set_solution_choices(&mut self, n_choices: u8);
+
     async fn return_reason_result(
         &mut self,
         result_can_be_none: bool,
@@ -231,6 +343,7 @@ pub trait DecisionTrait: Sized + InstructPromptTrait {
             dynamic_temperature: true,
             reason: self,
             result_can_be_none: false,
+            single_request: false,
         }
     }
 }
@@ -340,3 +453,140 @@ impl std::fmt::Display for DecisionResult {
         writeln!(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{components::cascade::CascadeFlow, workflows::reason::ReasonChoice, LLMClient};
+    use std::collections::VecDeque;
+
+    /// A reason workflow answering with scripted results.
+    struct ScriptedReason {
+        base_req: CompletionRequest,
+        primitive: BooleanPrimitive,
+        instruct_prompt: InstructPrompt,
+        results: VecDeque<ReasonResult>,
+        solution_choices: Vec<u8>,
+    }
+
+    impl ScriptedReason {
+        fn new(results: Vec<ReasonResult>) -> Self {
+            let mut builder = LLMClient::openai();
+            builder.config.api_config.api_key = Some("test-key".to_string().into());
+            builder.config.logging_config.logging_enabled = false;
+            Self {
+                base_req: CompletionRequest::new(builder.init().unwrap().backend),
+                primitive: BooleanPrimitive::default(),
+                instruct_prompt: InstructPrompt::default(),
+                results: results.into(),
+                solution_choices: Vec::new(),
+            }
+        }
+    }
+
+    impl InstructPromptTrait for ScriptedReason {
+        fn instruct_prompt_mut(&mut self) -> &mut InstructPrompt {
+            &mut self.instruct_prompt
+        }
+    }
+
+    impl DecisionTrait for ScriptedReason {
+        type ReasonPrimitive = BooleanPrimitive;
+
+        fn base_req(&self) -> &CompletionRequest {
+            &self.base_req
+        }
+
+        fn base_req_mut(&mut self) -> &mut CompletionRequest {
+            &mut self.base_req
+        }
+
+        fn primitive(&self) -> &Self::ReasonPrimitive {
+            &self.primitive
+        }
+
+        fn set_solution_choices(&mut self, n_choices: u8) {
+            self.solution_choices.push(n_choices);
+        }
+
+        async fn return_reason_result(&mut self, _: bool) -> crate::Result<ReasonResult> {
+            self.results
+                .pop_front()
+                .ok_or_else(|| anyhow::format_err!("No more results"))
+        }
+    }
+
+    fn reason_result(result: bool, choices: &[(bool, f32)]) -> ReasonResult {
+        ReasonResult {
+            primitive_result: Some(result.to_string()),
+            duration: std::time::Duration::ZERO,
+            workflow: CascadeFlow::new("Scripted"),
+            result_index: Some(result as u32),
+            temperature: 0.0,
+            choices: choices
+                .iter()
+                .map(|(result, confidence)| ReasonChoice {
+                    primitive_result: Some(result.to_string()),
+                    result_index: Some(*result as u32),
+                    confidence: Some(*confidence),
+                })
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_single_request_decision() {
+        let reason = ScriptedReason::new(vec![reason_result(
+            true,
+            &[(true, 0.9), (false, 0.8), (true, 0.3)],
+        )]);
+        let mut decision = reason.decision();
+        decision.best_of_n_votes(3).single_request(true);
+        let result = decision.return_result().await.unwrap();
+
+        assert_eq!(decision.reason.solution_choices, [3]);
+        assert_eq!(result.total_votes, 3);
+        assert_eq!(result.votes[&1], 2);
+        assert_eq!(result.votes[&0], 1);
+        assert_eq!(result.winner_index, Some(1));
+        assert_eq!(result.winner_votes, 2);
+        assert_eq!(result.winner_primitive_result.as_deref(), Some("true"));
+        assert!((result.confidence - 0.6).abs() < 1e-6);
+        assert_eq!(decision.parse_decision_result(&result).unwrap(), Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_single_request_decision_weights_confidence() {
+        // A confident choice outweighs two unsure ones.
+        let reason = ScriptedReason::new(vec![reason_result(
+            false,
+            &[(false, 0.9), (true, 0.2), (true, 0.2)],
+        )]);
+        let mut decision = reason.decision();
+        decision.best_of_n_votes(3).single_request(true);
+        let result = decision.return_result().await.unwrap();
+
+        assert_eq!(result.winner_index, Some(0));
+        assert_eq!(result.winner_votes, 1);
+        assert_eq!(result.winner_primitive_result.as_deref(), Some("false"));
+    }
+
+    #[tokio::test]
+    async fn test_single_request_decision_ignored_n() {
+        // The backend returned a single choice, the votes are requested one by one.
+        let reason = ScriptedReason::new(vec![
+            reason_result(true, &[]),
+            reason_result(true, &[]),
+            reason_result(true, &[]),
+        ]);
+        let mut decision = reason.decision();
+        decision.best_of_n_votes(3).single_request(true);
+        let result = decision.return_result().await.unwrap();
+
+        assert_eq!(decision.reason.solution_choices, [3, 1]);
+        assert_eq!(result.total_votes, 2);
+        assert_eq!(result.winner_votes, 2);
+        assert_eq!(result.winner_index, Some(1));
+        assert!(decision.reason.results.is_empty());
+    }
+}
@@ -47,6 +47,7 @@ impl ReasonWorkflowBuilder {
             reasoning_sentences: 3,
             conclusion_sentences: 2,
             result_can_be_none: false,
+            solution_choices: 1,
             instruct_prompt: InstructPrompt::default(),
         }
     }
@@ -77,6 +78,17 @@ pub struct ReasonResult {
     pub workflow: CascadeFlow,
     pub result_index: Option<u32>,
     pub temperature: f32,
+    /// Every solution when more than one was requested in the solution step.
+    pub choices: Vec<ReasonChoice>,
+}
+
+/// One of the solutions of a reason workflow that requested several in one request.
+#[derive(Clone)]
+pub struct ReasonChoice {
+    pub primitive_result: Option<String>,
+    pub result_index: Option<u32>,
+    /// The mean probability of the tokens of the solution.
+    pub confidence: Option<f32>,
 }
 
 impl ReasonResult {
@@ -89,12 +101,25 @@ impl ReasonResult {
         let result_index = primitive_result
             .as_ref()
             .map(|primitive_result| primitive.primitive_to_result_index(primitive_result));
+        let choices = flow
+            .primitive_choices()
+            .into_iter()
+            .map(|choice| ReasonChoice {
+                result_index: choice
+                    .llm_content
+                    .as_ref()
+                    .map(|content| primitive.primitive_to_result_index(content)),
+                primitive_result: choice.llm_content,
+                confidence: choice.confidence,
+            })
+            .collect();
         Ok(ReasonResult {
             primitive_result,
             duration: flow.duration,
             workflow: flow,
             result_index,
             temperature: base_req.config.temperature,
+            choices,
         })
     }
 }
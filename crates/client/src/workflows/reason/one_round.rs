@@ -15,6 +15,8 @@ pub struct ReasonOneRound<P> {
     pub reasoning_sentences: u8,
     pub conclusion_sentences: u8,
     pub result_can_be_none: bool,
+    /// The number of solutions requested in the final step, see [`DecisionTrait::set_solution_choices`].
+    pub solution_choices: u8,
     pub primitive: P,
     pub base_req: CompletionRequest,
     pub instruct_prompt: InstructPrompt,
@@ -142,6 +144,7 @@ impl<P: PrimitiveTrait + ReasonTrait> ReasonOneRound<P> {
                 .primitive
                 .stop_word_result_is_none(self.result_can_be_none),
             grammar: self.primitive.grammar(),
+            n_choices: self.solution_choices,
             ..StepConfig::default()
         };
         flow.last_round()?.add_inference_step(&step_config);
@@ -203,6 +206,10 @@ impl<P: PrimitiveTrait + ReasonTrait> DecisionTrait for ReasonOneRound<P> {
         &self.primitive
     }
 
+    fn set_solution_choices(&mut self, n_choices: u8) {
+        self.solution_choices = n_choices.max(1);
+    }
+
     async fn return_reason_result(
         &mut self,
         result_can_be_none: bool,
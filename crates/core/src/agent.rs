@@ -1,12 +1,19 @@
-use crate::chat::{Completion, Document, Message, Request};
+use crate::chat::{
+    CancellationToken, Completion, CompletionError, Document, Message, ReasoningEffort, Request,
+    ResponseFormat,
+};
 use crate::executor::Executor;
 use crate::knowledge::Knowledge;
-use crate::mcp::{setup_mcp_clients, sse_client, stdio_client, MCPClient, MCPError};
+use crate::mcp::{
+    refresh_changed_tools, setup_mcp_clients, sse_client, stdio_client, supervise, MCPClient,
+    MCPError, MCPHealth, MCPServerConfig,
+};
 use crate::memory::Memory;
 use crate::store::{Storage, VectorStoreError};
 use crate::task::TaskError;
-use crate::tool::Tool;
+use crate::tool::{Tool, ToolChoice};
 use crate::{make_ref, Ref};
+use alith_client::ledger::UsageLedger;
 use futures::{stream, StreamExt, TryStreamExt};
 use std::collections::HashMap;
 use std::path::Path;
@@ -41,6 +48,20 @@ pub struct Agent<M: Completion> {
     pub respect_context_window: bool,
     /// Whether code execution is allowed.
     pub allow_code_execution: bool,
+    /// The structured format of the agent responses.
+    pub response_format: Option<ResponseFormat>,
+    /// Whether the provider should cache the preamble, tools and documents between requests.
+    pub cache_prompt: bool,
+    /// The number of tokens the model may spend thinking before it responds.
+    pub thinking_budget_tokens: Option<u64>,
+    /// How much effort a reasoning model spends thinking before it responds.
+    pub reasoning_effort: Option<ReasoningEffort>,
+    /// How the model may use the agent tools.
+    pub tool_choice: ToolChoice,
+    /// Whether the model may call several tools in one response, `None` for the provider default.
+    pub parallel_tool_calls: Option<bool>,
+    /// The ledger recording the usage of the agent, also enforcing its budget.
+    pub ledger: Option<UsageLedger>,
     /// The MCP client used to communicate with the MCP server
     mcp_clients: Ref<Vec<MCPClient>>,
 }
@@ -66,6 +87,13 @@ where
             mcp_clients: make_ref(vec![]),
             respect_context_window: false,
             allow_code_execution: false,
+            response_format: None,
+            cache_prompt: false,
+            thinking_budget_tokens: None,
+            reasoning_effort: None,
+            tool_choice: ToolChoice::default(),
+            parallel_tool_calls: None,
+            ledger: None,
         }
     }
 
@@ -89,10 +117,18 @@ where
             mcp_clients: make_ref(vec![]),
             respect_context_window: false,
             allow_code_execution: false,
+            response_format: None,
+            cache_prompt: false,
+            thinking_budget_tokens: None,
+            reasoning_effort: None,
+            tool_choice: ToolChoice::default(),
+            parallel_tool_calls: None,
+            ledger: None,
         }
     }
 
-    /// Add a tool into the agent
+    /// Add a tool into the agent. A tool named like one of the `server__tool` names of the MCP
+    /// clients fails the prompts with [`MCPError::ToolCollision`].
     pub async fn tool(self, tool: impl Tool + 'static) -> Self {
         let mut self_tools = self.tools.write().await;
         self_tools.push(Box::new(tool));
@@ -100,7 +136,7 @@ where
         self
     }
 
-    /// Add some tools into the agent
+    /// Add some tools into the agent, see [`Agent::tool`] for the names of the MCP tools.
     pub async fn tools<I>(self, tools: I) -> Self
     where
         I: IntoIterator<Item = Box<dyn Tool>>,
@@ -131,25 +167,137 @@ where
         self
     }
 
-    /// Set the MCP client.
-    pub async fn mcp_client(self, mcp_client: MCPClient) -> Self {
+    /// Constrains the agent responses to the given format.
+    pub fn response_format(mut self, response_format: ResponseFormat) -> Self {
+        self.response_format = Some(response_format);
+        self
+    }
+
+    /// Caches the preamble, tools and documents on the provider side between requests.
+    pub fn cache_prompt(mut self, cache_prompt: bool) -> Self {
+        self.cache_prompt = cache_prompt;
+        self
+    }
+
+    /// Lets the model think with the given token budget before it responds.
+    pub fn thinking_budget_tokens(mut self, thinking_budget_tokens: u64) -> Self {
+        self.thinking_budget_tokens = Some(thinking_budget_tokens);
+        self
+    }
+
+    /// Sets the reasoning effort of reasoning models.
+    pub fn reasoning_effort(mut self, reasoning_effort: ReasoningEffort) -> Self {
+        self.reasoning_effort = Some(reasoning_effort);
+        self
+    }
+
+    /// Sets how the model may use the tools, e.g. `ToolChoice::tool("extractor")` to force a call.
+    pub fn tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = tool_choice;
+        self
+    }
+
+    /// Allows or forbids several tool calls in one response.
+    pub fn parallel_tool_calls(mut self, parallel_tool_calls: bool) -> Self {
+        self.parallel_tool_calls = Some(parallel_tool_calls);
+        self
+    }
+
+    /// Records the usage of the agent in the ledger, by agent name and tool loop step.
+    pub fn ledger(mut self, ledger: UsageLedger) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// Set the MCP client. Its tools are exposed to the model as `server__tool`, failing with
+    /// [`MCPError::ToolCollision`] when another server has the same name or a local tool is
+    /// named like one of its `server__tool` names.
+    pub async fn mcp_client(self, mcp_client: MCPClient) -> anyhow::Result<Self, MCPError> {
+        let tools = self.tools.read().await;
         let mut mcp_clients = self.mcp_clients.write().await;
+        let prefix = mcp_client.tool_prefix();
+        if mcp_clients
+            .iter()
+            .any(|client| client.tool_prefix() == prefix)
+        {
+            return Err(MCPError::ToolCollision(format!(
+                "an MCP server named {} is already registered",
+                mcp_client.name
+            )));
+        }
+        for definition in mcp_client.tool_definitions() {
+            if tools
+                .iter()
+                .any(|tool| tool.name().eq_ignore_ascii_case(&definition.name))
+            {
+                return Err(MCPError::ToolCollision(format!(
+                    "the tool {} is already registered",
+                    definition.name
+                )));
+            }
+        }
         mcp_clients.push(mcp_client);
         drop(mcp_clients);
-        self
+        drop(tools);
+        Ok(self)
     }
 
     /// Set the MCP server config path.
-    pub async fn mcp_config_path<P: AsRef<Path>>(self, path: P) -> anyhow::Result<Self, MCPError> {
-        let clients = setup_mcp_clients(path).await?;
-        let mut mcp_clients = self.mcp_clients.write().await;
-        for (_, client) in clients {
-            mcp_clients.push(client);
+    pub async fn mcp_config_path<P: AsRef<Path>>(
+        mut self,
+        path: P,
+    ) -> anyhow::Result<Self, MCPError> {
+        for (_, client) in setup_mcp_clients(path).await? {
+            self = self.mcp_client(client).await?;
         }
-        drop(mcp_clients);
         Ok(self)
     }
 
+    /// Connects to an MCP server, see [`MCPServerConfig`] for the stdio and remote servers.
+    pub async fn mcp_server(self, config: &MCPServerConfig) -> anyhow::Result<Self, MCPError> {
+        self.mcp_client(config.connect().await?).await
+    }
+
+    /// Checks the MCP servers at every interval, refreshing their tools and reconnecting the
+    /// ones not answering, until the agent is dropped. Must be called within a Tokio runtime.
+    pub fn mcp_health_check(self, interval: std::time::Duration) -> Self {
+        tokio::spawn(supervise(Arc::downgrade(&self.mcp_clients), interval));
+        self
+    }
+
+    /// The health of the MCP servers, by server name.
+    pub async fn mcp_health(&self) -> HashMap<String, MCPHealth> {
+        self.mcp_clients
+            .read()
+            .await
+            .iter()
+            .map(|client| (client.name.clone(), client.health.clone()))
+            .collect()
+    }
+
+    /// Adds the resources of an MCP server as knowledge sources, read once. Fails with
+    /// [`MCPError::SharedKnowledges`] when the knowledges are shared with another agent.
+    pub async fn mcp_resources(mut self, client: &MCPClient) -> anyhow::Result<Self, MCPError> {
+        let resources = client.resource_knowledges().await?;
+        // The knowledges are only shared with the executor while the agent is prompted.
+        let knowledges = Arc::get_mut(&mut self.knowledges).ok_or(MCPError::SharedKnowledges)?;
+        for resource in resources {
+            knowledges.push(Box::new(resource));
+        }
+        Ok(self)
+    }
+
+    /// Uses an MCP prompt, rendered with the given arguments, as the preamble.
+    pub async fn mcp_preamble(
+        self,
+        client: &MCPClient,
+        name: &str,
+        arguments: HashMap<String, String>,
+    ) -> anyhow::Result<Self, MCPError> {
+        let preamble = client.prompt_text(name, arguments).await?;
+        Ok(self.preamble(preamble))
+    }
+
     /// Set the MCP sse client.
     #[inline]
     pub async fn mcp_sse_client<S: AsRef<str> + 'static>(
@@ -157,7 +305,7 @@ where
         sse_url: S,
         env: HashMap<String, String>,
     ) -> anyhow::Result<Self> {
-        Ok(self.mcp_client(sse_client(sse_url, env).await?).await)
+        Ok(self.mcp_client(sse_client(sse_url, env).await?).await?)
     }
 
     /// Set the MCP sse client.
@@ -170,11 +318,46 @@ where
     ) -> anyhow::Result<Self> {
         Ok(self
             .mcp_client(stdio_client(command, args, env).await?)
-            .await)
+            .await?)
     }
 
     /// Processes a prompt using the agent.
     pub async fn prompt(&self, prompt: &str) -> Result<String, TaskError> {
+        self.run_prompt(prompt, None).await
+    }
+
+    /// Processes a prompt using the agent, aborted with [`TaskError::Cancelled`] once the token
+    /// is cancelled, e.g. when the user leaves the chat.
+    pub async fn prompt_with_cancellation(
+        &self,
+        prompt: &str,
+        cancellation_token: CancellationToken,
+    ) -> Result<String, TaskError> {
+        self.run_prompt(prompt, Some(cancellation_token)).await
+    }
+
+    /// Processes a prompt using the agent.
+    pub async fn chat(&self, prompt: &str, history: Vec<Message>) -> Result<String, TaskError> {
+        self.run_chat(prompt, history, None).await
+    }
+
+    /// Processes a prompt using the agent, aborted with [`TaskError::Cancelled`] once the token
+    /// is cancelled.
+    pub async fn chat_with_cancellation(
+        &self,
+        prompt: &str,
+        history: Vec<Message>,
+        cancellation_token: CancellationToken,
+    ) -> Result<String, TaskError> {
+        self.run_chat(prompt, history, Some(cancellation_token))
+            .await
+    }
+
+    async fn run_prompt(
+        &self,
+        prompt: &str,
+        cancellation_token: Option<CancellationToken>,
+    ) -> Result<String, TaskError> {
         // Add chat conversion history.
         let history = if let Some(memory) = &self.memory {
             let memory = memory.read().await;
@@ -184,16 +367,21 @@ where
                 .map(|m| Message {
                     role: m.message_type.type_string(),
                     content: m.content.clone(),
+                    thinking_blocks: m.thinking_blocks.clone(),
                 })
                 .collect()
         } else {
             vec![]
         };
-        self.chat(prompt, history).await
+        self.run_chat(prompt, history, cancellation_token).await
     }
 
-    /// Processes a prompt using the agent.
-    pub async fn chat(&self, prompt: &str, history: Vec<Message>) -> Result<String, TaskError> {
+    async fn run_chat(
+        &self,
+        prompt: &str,
+        history: Vec<Message>,
+        cancellation_token: Option<CancellationToken>,
+    ) -> Result<String, TaskError> {
         let mut executor = Executor::new(
             self.model.clone(),
             self.knowledges.clone(),
@@ -201,19 +389,45 @@ where
             self.memory.clone(),
             self.mcp_clients.clone(),
         );
+        if let Some(ledger) = &self.ledger {
+            ledger.check_budget()?;
+            executor = executor.ledger(ledger.clone(), &self.name);
+        }
         let mut req = Request::new(prompt.to_string(), self.preamble.clone());
         req.history = history;
         req.max_tokens = self.max_tokens;
         req.temperature = self.temperature;
+        req.response_format = self.response_format.clone();
+        req.cache_prompt = self.cache_prompt;
+        req.thinking_budget_tokens = self.thinking_budget_tokens;
+        req.reasoning_effort = self.reasoning_effort;
+        req.tool_choice = self.tool_choice.clone();
+        req.parallel_tool_calls = self.parallel_tool_calls;
+        req.cancellation_token = cancellation_token;
         let tools = self.tools.read().await;
         req.tools = tools
             .iter()
             .map(|tool| tool.definition())
             .collect::<Vec<_>>();
+        if let Err(e) = refresh_changed_tools(&self.mcp_clients).await {
+            tracing::warn!("failed to refresh the MCP tools: {e}");
+        }
         let mcp_clients = self.mcp_clients.read().await;
         for client in mcp_clients.iter() {
-            for tool in client.tools.values() {
-                req.tools.push(tool.clone());
+            for definition in client.tool_definitions() {
+                // The local tools may be added after the MCP clients, and the MCP tools change.
+                if req
+                    .tools
+                    .iter()
+                    .any(|tool| tool.name.eq_ignore_ascii_case(&definition.name))
+                {
+                    return Err(MCPError::ToolCollision(format!(
+                        "the tool {} is already registered",
+                        definition.name
+                    ))
+                    .into());
+                }
+                req.tools.push(definition);
             }
         }
         req.documents = stream::iter(self.store_indices.iter())
@@ -238,11 +452,136 @@ where
             .await
             .map_err(|err| TaskError::ExecutionError(err.to_string()))?;
 
-        let response = executor
-            .invoke(req)
-            .await
-            .map_err(|err| TaskError::ExecutionError(err.to_string()))?;
+        let response =
+            executor
+                .invoke(req)
+                .await
+                .map_err(|err| match err.downcast::<CompletionError>() {
+                    Ok(CompletionError::BudgetExceeded(err)) => TaskError::BudgetExceeded(err),
+                    Ok(CompletionError::Cancelled { .. }) => TaskError::Cancelled,
+                    Ok(err) => TaskError::ExecutionError(err.to_string()),
+                    Err(err) => TaskError::ExecutionError(err.to_string()),
+                })?;
 
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::UsageRecord;
+    use crate::llm::client::{Client, LLMClient};
+    use crate::mcp::tests::{docs_server, scripted_client, ScriptedHandle};
+    use crate::tool::tests::DummpyInput;
+    use crate::tool::{StructureTool, ToolError};
+
+    fn agent() -> Agent<Client> {
+        let mut builder = LLMClient::openai();
+        builder.config.api_config.api_key = Some("test-key".to_string().into());
+        builder.config.logging_config.logging_enabled = false;
+        Agent::new(
+            "test",
+            Client {
+                client: builder.init().unwrap(),
+            },
+        )
+    }
+
+    /// A model whose request is cancelled after a retry discarded a response.
+    struct CancelledModel;
+
+    impl Completion for CancelledModel {
+        type Response = crate::llm::client::CompletionResponse;
+
+        async fn completion(
+            &mut self,
+            _request: Request,
+        ) -> Result<Self::Response, CompletionError> {
+            Err(CompletionError::Cancelled {
+                usage: Some(UsageRecord {
+                    model: "gpt-4o".to_string(),
+                    prompt_tokens: 10,
+                    completion_tokens: 5,
+                    ..Default::default()
+                }),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_usage_recorded() {
+        let ledger = UsageLedger::new();
+        let agent = Agent::new("test", CancelledModel).ledger(ledger.clone());
+        assert!(matches!(
+            agent.prompt("Hello").await,
+            Err(TaskError::Cancelled)
+        ));
+        let records = ledger.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].agent.as_deref(), Some("test"));
+        assert_eq!(records[0].step, Some(0));
+        assert_eq!(records[0].prompt_tokens, 10);
+    }
+
+    /// A local tool named like the `search` tool of the docs MCP server.
+    struct DocsSearch;
+
+    #[async_trait::async_trait]
+    impl StructureTool for DocsSearch {
+        type Input = DummpyInput;
+        type Output = String;
+
+        fn name(&self) -> &str {
+            "docs__search"
+        }
+
+        async fn run_with_args(&self, _input: Self::Input) -> Result<Self::Output, ToolError> {
+            Ok(String::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_collision() {
+        let client = scripted_client(ScriptedHandle::new(docs_server)).await;
+        let agent = Agent::new("test", CancelledModel)
+            .mcp_client(client)
+            .await
+            .unwrap()
+            .tool(DocsSearch)
+            .await;
+        assert!(matches!(
+            agent.prompt("Hello").await,
+            Err(TaskError::MCPError(MCPError::ToolCollision(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mcp_resources_and_preamble() {
+        let client = scripted_client(ScriptedHandle::new(docs_server)).await;
+        let agent = agent()
+            .mcp_resources(&client)
+            .await
+            .unwrap()
+            .mcp_preamble(
+                &client,
+                "writer",
+                HashMap::from([("topic".to_string(), "Rust".to_string())]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(agent.knowledges.len(), 2);
+        assert_eq!(
+            agent.knowledges[0].load().unwrap(),
+            "The content of docs://readme"
+        );
+        assert_eq!(agent.preamble, "You write about Rust.\n\nBe brief.");
+
+        // The knowledges shared with another agent can't be extended
+        let _shared = Arc::clone(&agent.knowledges);
+        assert!(matches!(
+            agent.mcp_resources(&client).await,
+            Err(MCPError::SharedKnowledges)
+        ));
+    }
+}
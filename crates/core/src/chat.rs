@@ -1,4 +1,8 @@
-use alith_interface::requests::completion::ToolDefinition;
+pub use alith_client::ledger::{BudgetExceeded, UsageRecord};
+pub use alith_interface::requests::completion::{JsonSchemaFormat, ResponseFormat, ThinkingBlock};
+use alith_interface::requests::completion::{ToolChoice, ToolDefinition};
+pub use alith_interface::requests::req_components::ReasoningEffort;
+pub use alith_interface::CancellationToken;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
@@ -40,6 +44,9 @@ pub struct Message {
     /// "system", "user", or "assistant"
     pub role: String,
     pub content: String,
+    /// The signed thinking blocks of an assistant message, sent back to the model.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thinking_blocks: Vec<ThinkingBlock>,
 }
 
 /// Represents a document with an ID, text, and additional properties.
@@ -127,11 +134,39 @@ pub struct Request {
     /// specific tasks or enhance its responses.
     pub tools: Vec<ToolDefinition>,
 
+    /// How the model may use the tools, e.g. forcing a call of a named tool.
+    pub tool_choice: ToolChoice,
+
+    /// Optional: Whether the model may call several tools in one response.
+    pub parallel_tool_calls: Option<bool>,
+
     /// A collection of documents that provide context or background information for the model.
     ///
     /// These documents can be used by the model to generate more accurate and informed responses.
     /// Examples include research papers, policy documents, or reference materials.
     pub documents: Vec<Document>,
+
+    /// Optional: The structured format the model's output must follow.
+    ///
+    /// When `None`, the model responds with free form text.
+    pub response_format: Option<ResponseFormat>,
+
+    /// Whether the provider should cache the prompt prefix, e.g. a long preamble or documents,
+    /// so the following requests sharing it are cheaper and faster.
+    pub cache_prompt: bool,
+
+    /// Optional: The number of tokens the model may spend thinking before it responds.
+    pub thinking_budget_tokens: Option<u64>,
+
+    /// Optional: How much effort a reasoning model spends thinking before it responds.
+    pub reasoning_effort: Option<ReasoningEffort>,
+
+    /// Optional: Aborts the request with [`CompletionError::Cancelled`] once cancelled.
+    pub cancellation_token: Option<CancellationToken>,
+
+    /// Optional: Continues the conversation of a previous response on stateful APIs, e.g. the
+    /// OpenAI Responses API, which then only receive the messages after the last answer.
+    pub previous_response_id: Option<String>,
 }
 
 impl Request {
@@ -152,7 +187,15 @@ impl Request {
             max_tokens: None,
             temperature: None,
             tools: Vec::new(),
+            tool_choice: ToolChoice::default(),
+            parallel_tool_calls: None,
             documents: Vec::new(),
+            response_format: None,
+            cache_prompt: false,
+            thinking_budget_tokens: None,
+            reasoning_effort: None,
+            cancellation_token: None,
+            previous_response_id: None,
         }
     }
 
@@ -184,6 +227,22 @@ pub trait ResponseContent {
     /// # Returns
     /// A string containing the text content of the response.
     fn content(&self) -> String;
+
+    /// Retrieves the reasoning of the model before the content, when the model returns it.
+    fn reasoning(&self) -> Option<String> {
+        None
+    }
+
+    /// Retrieves the signed thinking blocks the model expects back in the following turns.
+    fn thinking_blocks(&self) -> Vec<ThinkingBlock> {
+        Vec::new()
+    }
+
+    /// Retrieves the id continuing the conversation on stateful APIs, see
+    /// [`Request::previous_response_id`].
+    fn response_id(&self) -> Option<String> {
+        None
+    }
 }
 
 /// A trait for extracting tool-based calls from a language model's response.
@@ -195,6 +254,14 @@ pub trait ResponseToolCalls {
     fn toolcalls(&self) -> Vec<ToolCall>;
 }
 
+/// A trait for extracting the token usage and cost from a language model's response.
+pub trait ResponseUsage {
+    /// Retrieves the usage of the request, if the response reports it.
+    fn usage(&self) -> Option<UsageRecord> {
+        None
+    }
+}
+
 /// Represents a call to a specific tool in a response.
 pub struct ToolCall {
     /// The unique identifier for the tool call.
@@ -222,7 +289,7 @@ pub struct CallFunction {
 /// - `Response`: The specific type of the response generated by the completion engine.
 pub trait Completion {
     /// The type of response returned by the `completion` method.
-    type Response: Send + Sync + ResponseContent + ResponseToolCalls;
+    type Response: Send + Sync + ResponseContent + ResponseToolCalls + ResponseUsage;
 
     /// Processes a `Request` and returns the generated response asynchronously.
     ///
@@ -237,6 +304,13 @@ pub trait Completion {
         &mut self,
         request: Request,
     ) -> impl std::future::Future<Output = Result<Self::Response, CompletionError>>;
+
+    /// Whether the completion engine natively supports the given response format.
+    ///
+    /// Engines without native support are expected to ignore [Request::response_format].
+    fn supports_response_format(&self, _response_format: &ResponseFormat) -> bool {
+        false
+    }
 }
 
 /// An enumeration of possible errors that may occur during completion operations.
@@ -250,4 +324,36 @@ pub enum CompletionError {
     Normal(String),
     #[error("An inference error occurred: {0}")]
     Inference(String),
+    /// An error returned by the model provider.
+    #[error("A provider error occurred: {0}")]
+    Provider(#[from] alith_interface::requests::completion::CompletionError),
+    /// The usage budget of the ledger was spent before the request.
+    #[error("{0}")]
+    BudgetExceeded(#[from] BudgetExceeded),
+    /// The request was cancelled with its cancellation token.
+    #[error("The request was cancelled")]
+    Cancelled {
+        /// The usage of the responses discarded by retries before the cancellation, if any.
+        usage: Option<UsageRecord>,
+    },
+}
+
+impl CompletionError {
+    /// Whether the error is caused by the provider, e.g. a rate limit, a server error or a
+    /// timeout, so the request may succeed later or with another provider.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            CompletionError::Provider(err) => err.is_transient(),
+            _ => false,
+        }
+    }
+
+    /// Whether the quota of the account at the provider is spent. The provider will keep
+    /// rejecting requests, but another provider may succeed.
+    pub fn is_quota_exhausted(&self) -> bool {
+        match self {
+            CompletionError::Provider(err) => err.is_quota_exhausted(),
+            _ => false,
+        }
+    }
 }
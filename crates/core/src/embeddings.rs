@@ -1,3 +1,7 @@
+pub mod cohere;
+pub mod jina;
+pub mod voyage;
+
 use async_trait::async_trait;
 use futures::stream;
 use futures::stream::StreamExt;
@@ -23,6 +27,30 @@ pub trait Embeddings: Clone + Send + Sync {
         -> Result<Vec<EmbeddingsData>, EmbeddingsError>;
 }
 
+/// Pair the input texts with the vectors returned by an embeddings API, restoring the input order.
+/// Fails when the API did not return one vector per text.
+pub(crate) fn zip_embeddings(
+    input: Vec<String>,
+    mut data: Vec<alith_interface::requests::embeddings::EmbeddingsData>,
+) -> Result<Vec<EmbeddingsData>, EmbeddingsError> {
+    if data.len() != input.len() {
+        return Err(EmbeddingsError::ResponseError(format!(
+            "expected {} embeddings, got {}",
+            input.len(),
+            data.len()
+        )));
+    }
+    data.sort_by_key(|d| d.index);
+    Ok(data
+        .into_iter()
+        .zip(input)
+        .map(|(data, document)| EmbeddingsData {
+            document,
+            vec: data.embedding,
+        })
+        .collect())
+}
+
 // Trait that defines the embedding process for a document
 pub trait Embed {
     fn embed(&self, embedder: &mut TextEmbedder) -> Result<(), EmbedError>;
@@ -157,3 +185,32 @@ impl<M: Embeddings, T: Embed + Send> EmbeddingsBuilder<M, T> {
             .collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alith_interface::requests::embeddings::EmbeddingsData as ApiEmbeddingsData;
+
+    fn data(index: usize, value: f64) -> ApiEmbeddingsData {
+        ApiEmbeddingsData {
+            object: "embedding".to_string(),
+            embedding: vec![value],
+            index,
+        }
+    }
+
+    #[test]
+    fn test_zip_embeddings() {
+        let input = vec!["a".to_string(), "b".to_string()];
+        let embeddings = zip_embeddings(input.clone(), vec![data(1, 2.0), data(0, 1.0)]).unwrap();
+        assert_eq!(embeddings[0].document, "a");
+        assert_eq!(embeddings[0].vec, [1.0]);
+        assert_eq!(embeddings[1].document, "b");
+        assert_eq!(embeddings[1].vec, [2.0]);
+
+        assert!(matches!(
+            zip_embeddings(input, vec![data(0, 1.0)]),
+            Err(EmbeddingsError::ResponseError(_))
+        ));
+    }
+}
@@ -0,0 +1,73 @@
+use super::{Embeddings, EmbeddingsData, EmbeddingsError};
+use alith_interface::llms::api::cohere::{
+    CohereBackend, CohereConfig, CohereEmbeddingsRequest, COHERE_MAX_DOCUMENTS,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub use alith_interface::llms::api::cohere::{CohereInputType, CohereTruncate};
+
+pub const EMBED_ENGLISH_V3: &str = "embed-english-v3.0";
+pub const EMBED_ENGLISH_LIGHT_V3: &str = "embed-english-light-v3.0";
+pub const EMBED_MULTILINGUAL_V3: &str = "embed-multilingual-v3.0";
+pub const EMBED_MULTILINGUAL_LIGHT_V3: &str = "embed-multilingual-light-v3.0";
+
+/// Embeddings model backed by the Cohere v2 embed API.
+#[derive(Clone)]
+pub struct CohereEmbeddings {
+    backend: Arc<CohereBackend>,
+    pub model: String,
+    pub input_type: CohereInputType,
+    pub truncate: Option<CohereTruncate>,
+}
+
+impl CohereEmbeddings {
+    /// Create a Cohere embeddings model, the API key is loaded from `COHERE_API_KEY`.
+    pub fn new(model: &str) -> anyhow::Result<Self> {
+        Self::from_config(CohereConfig::default(), model)
+    }
+
+    pub fn from_config(config: CohereConfig, model: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            backend: Arc::new(CohereBackend::new(config)?),
+            model: model.to_string(),
+            input_type: CohereInputType::default(),
+            truncate: None,
+        })
+    }
+
+    /// Set the input type, defaults to [CohereInputType::SearchDocument].
+    pub fn with_input_type(mut self, input_type: CohereInputType) -> Self {
+        self.input_type = input_type;
+        self
+    }
+
+    pub fn with_truncate(mut self, truncate: CohereTruncate) -> Self {
+        self.truncate = Some(truncate);
+        self
+    }
+}
+
+#[async_trait]
+impl Embeddings for CohereEmbeddings {
+    const MAX_DOCUMENTS: usize = COHERE_MAX_DOCUMENTS;
+
+    async fn embed_texts(
+        &self,
+        input: Vec<String>,
+    ) -> Result<Vec<EmbeddingsData>, EmbeddingsError> {
+        let request = CohereEmbeddingsRequest {
+            model: self.model.clone(),
+            texts: input.clone(),
+            input_type: self.input_type,
+            embedding_types: vec!["float".to_string()],
+            truncate: self.truncate,
+        };
+        let response = self
+            .backend
+            .embeddings_request(&request)
+            .await
+            .map_err(|err| EmbeddingsError::ProviderError(err.to_string()))?;
+        super::zip_embeddings(input, response.data)
+    }
+}
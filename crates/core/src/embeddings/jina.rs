@@ -0,0 +1,79 @@
+use super::{Embeddings, EmbeddingsData, EmbeddingsError};
+use alith_interface::llms::api::jina::{
+    JinaBackend, JinaConfig, JinaEmbeddingsRequest, JINA_MAX_DOCUMENTS,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub use alith_interface::llms::api::jina::JinaTask;
+
+pub const JINA_EMBEDDINGS_V3: &str = "jina-embeddings-v3";
+pub const JINA_CLIP_V2: &str = "jina-clip-v2";
+
+/// Embeddings model backed by the Jina AI embeddings API.
+#[derive(Clone)]
+pub struct JinaEmbeddings {
+    backend: Arc<JinaBackend>,
+    pub model: String,
+    pub task: Option<JinaTask>,
+    pub truncate: Option<bool>,
+    pub dimensions: Option<u32>,
+}
+
+impl JinaEmbeddings {
+    /// Create a Jina embeddings model, the API key is loaded from `JINA_API_KEY`.
+    pub fn new(model: &str) -> anyhow::Result<Self> {
+        Self::from_config(JinaConfig::default(), model)
+    }
+
+    pub fn from_config(config: JinaConfig, model: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            backend: Arc::new(JinaBackend::new(config)?),
+            model: model.to_string(),
+            task: None,
+            truncate: None,
+            dimensions: None,
+        })
+    }
+
+    /// Set the downstream task, e.g. [JinaTask::RetrievalPassage] when indexing and
+    /// [JinaTask::RetrievalQuery] when searching.
+    pub fn with_task(mut self, task: JinaTask) -> Self {
+        self.task = Some(task);
+        self
+    }
+
+    pub fn with_truncate(mut self, truncate: bool) -> Self {
+        self.truncate = Some(truncate);
+        self
+    }
+
+    pub fn with_dimensions(mut self, dimensions: u32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+}
+
+#[async_trait]
+impl Embeddings for JinaEmbeddings {
+    const MAX_DOCUMENTS: usize = JINA_MAX_DOCUMENTS;
+
+    async fn embed_texts(
+        &self,
+        input: Vec<String>,
+    ) -> Result<Vec<EmbeddingsData>, EmbeddingsError> {
+        let request = JinaEmbeddingsRequest {
+            model: self.model.clone(),
+            input: input.clone(),
+            task: self.task,
+            truncate: self.truncate,
+            dimensions: self.dimensions,
+        };
+        let response = self
+            .backend
+            .embeddings_request(&request)
+            .await
+            .map_err(|err| EmbeddingsError::ProviderError(err.to_string()))?;
+        super::zip_embeddings(input, response.data)
+    }
+}
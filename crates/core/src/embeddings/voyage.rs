@@ -0,0 +1,81 @@
+use super::{Embeddings, EmbeddingsData, EmbeddingsError};
+use alith_interface::llms::api::voyage::{
+    VoyageBackend, VoyageConfig, VoyageEmbeddingsRequest, VOYAGE_MAX_DOCUMENTS,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub use alith_interface::llms::api::voyage::VoyageInputType;
+
+pub const VOYAGE_3: &str = "voyage-3";
+pub const VOYAGE_3_LITE: &str = "voyage-3-lite";
+pub const VOYAGE_3_LARGE: &str = "voyage-3-large";
+pub const VOYAGE_CODE_3: &str = "voyage-code-3";
+
+/// Embeddings model backed by the Voyage AI embeddings API.
+#[derive(Clone)]
+pub struct VoyageEmbeddings {
+    backend: Arc<VoyageBackend>,
+    pub model: String,
+    pub input_type: Option<VoyageInputType>,
+    pub truncation: Option<bool>,
+    pub output_dimension: Option<u32>,
+}
+
+impl VoyageEmbeddings {
+    /// Create a Voyage embeddings model, the API key is loaded from `VOYAGE_API_KEY`.
+    pub fn new(model: &str) -> anyhow::Result<Self> {
+        Self::from_config(VoyageConfig::default(), model)
+    }
+
+    pub fn from_config(config: VoyageConfig, model: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            backend: Arc::new(VoyageBackend::new(config)?),
+            model: model.to_string(),
+            input_type: None,
+            truncation: None,
+            output_dimension: None,
+        })
+    }
+
+    /// Set the input type, use [VoyageInputType::Document] when indexing and
+    /// [VoyageInputType::Query] when searching.
+    pub fn with_input_type(mut self, input_type: VoyageInputType) -> Self {
+        self.input_type = Some(input_type);
+        self
+    }
+
+    pub fn with_truncation(mut self, truncation: bool) -> Self {
+        self.truncation = Some(truncation);
+        self
+    }
+
+    pub fn with_output_dimension(mut self, output_dimension: u32) -> Self {
+        self.output_dimension = Some(output_dimension);
+        self
+    }
+}
+
+#[async_trait]
+impl Embeddings for VoyageEmbeddings {
+    const MAX_DOCUMENTS: usize = VOYAGE_MAX_DOCUMENTS;
+
+    async fn embed_texts(
+        &self,
+        input: Vec<String>,
+    ) -> Result<Vec<EmbeddingsData>, EmbeddingsError> {
+        let request = VoyageEmbeddingsRequest {
+            input: input.clone(),
+            model: self.model.clone(),
+            input_type: self.input_type,
+            truncation: self.truncation,
+            output_dimension: self.output_dimension,
+        };
+        let response = self
+            .backend
+            .embeddings_request(&request)
+            .await
+            .map_err(|err| EmbeddingsError::ProviderError(err.to_string()))?;
+        super::zip_embeddings(input, response.data)
+    }
+}
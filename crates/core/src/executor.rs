@@ -1,9 +1,13 @@
-use crate::chat::{Completion, Request, ResponseContent, ResponseToolCalls, ToolCall};
+use crate::chat::{
+    CancellationToken, Completion, CompletionError, Request, ResponseContent, ResponseToolCalls,
+    ResponseUsage, ToolCall, UsageRecord,
+};
 use crate::knowledge::Knowledge;
-use crate::mcp::MCPClient;
+use crate::mcp::{reconnect_client, MCPClient, MCPError};
 use crate::memory::{Memory, Message};
-use crate::tool::Tool;
+use crate::tool::{Tool, ToolError};
 use crate::Ref;
+use alith_client::ledger::UsageLedger;
 use std::sync::Arc;
 
 /// Manages the execution of tasks using an LLM, tools, and (optionally) memory components.
@@ -14,6 +18,10 @@ pub struct Executor<M: Completion> {
     memory: Option<Ref<dyn Memory>>,
     /// The MCP client used to communicate with the MCP server
     mcp_clients: Ref<Vec<MCPClient>>,
+    /// The ledger and agent name the usage of each completion is recorded with.
+    ledger: Option<(UsageLedger, String)>,
+    /// The number of completions sent by the executor.
+    steps: usize,
 }
 
 impl<M: Completion> Executor<M> {
@@ -31,9 +39,17 @@ impl<M: Completion> Executor<M> {
             tools,
             memory,
             mcp_clients,
+            ledger: None,
+            steps: 0,
         }
     }
 
+    /// Records the usage of every completion in the ledger, labeled with the agent name and step.
+    pub fn ledger(mut self, ledger: UsageLedger, agent: impl ToString) -> Self {
+        self.ledger = Some((ledger, agent.to_string()));
+        self
+    }
+
     /// Executes the task by managing interactions between the LLM and tools.
     pub async fn invoke(&mut self, mut request: Request) -> anyhow::Result<String> {
         request.knowledges = {
@@ -44,18 +60,48 @@ impl<M: Completion> Executor<M> {
             }
             enriched_knowledges
         };
+        if is_cancelled(request.cancellation_token.as_ref()) {
+            return Err(CompletionError::Cancelled { usage: None }.into());
+        }
+        // Continue the conversation of the last response on stateful APIs
+        if request.previous_response_id.is_none() {
+            request.previous_response_id = self.previous_response_id().await;
+        }
         // Add user memory
         self.add_user_message(&request.prompt).await;
         // Interact with the LLM to get a response.
-        let mut model = self.model.write().await;
-        let response = model.completion(request.clone()).await?;
+        if let Some((ledger, _)) = &self.ledger {
+            ledger.check_budget().map_err(CompletionError::from)?;
+        }
+        let response = {
+            let mut model = self.model.write().await;
+            model.completion(request.clone()).await
+        };
+        let response = match response {
+            Ok(response) => response,
+            Err(CompletionError::Cancelled { usage }) => {
+                // The retries discarded before the cancellation are billed as well
+                self.record_usage(usage.clone());
+                return Err(CompletionError::Cancelled { usage }.into());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        self.record_usage(response.usage());
 
         let mut responses = vec![response.content()];
-        self.add_ai_message(&responses[0]).await;
+        self.add_ai_message(&responses[0], &response).await;
+
+        // The tool results are not sent back to the model, so a response calling tools can't
+        // be continued and the next request resends the conversation.
+        let tool_calls = response.toolcalls();
+        let response_id = response.response_id().filter(|_| tool_calls.is_empty());
+        self.set_previous_response_id(response_id).await;
 
         // Attempt to parse and execute a tool action.
-        for call in response.toolcalls() {
-            let tool_call = self.execute_tool(call).await?;
+        for call in tool_calls {
+            let tool_call = self
+                .execute_tool(call, request.cancellation_token.as_ref())
+                .await?;
             self.add_ai_message_with_tool_call(&tool_call).await?;
             responses.push(tool_call);
         }
@@ -63,6 +109,31 @@ impl<M: Completion> Executor<M> {
         Ok(responses.join("\n"))
     }
 
+    /// Record the usage of a completion into the ledger if the ledger has been set.
+    fn record_usage(&mut self, usage: Option<UsageRecord>) {
+        let step = self.steps;
+        self.steps += 1;
+        if let (Some((ledger, agent)), Some(usage)) = (&self.ledger, usage) {
+            ledger.record(usage.agent(agent.as_str()).step(step));
+        }
+    }
+
+    /// The id of the last response stored in the memory, if the memory has been set.
+    async fn previous_response_id(&self) -> Option<String> {
+        match &self.memory {
+            Some(memory) => memory.read().await.previous_response_id(),
+            None => None,
+        }
+    }
+
+    /// Store the id of the last response into the memory if the memory has been set.
+    async fn set_previous_response_id(&self, response_id: Option<String>) {
+        if let Some(memory) = &self.memory {
+            let mut memory = memory.write().await;
+            memory.set_previous_response_id(response_id);
+        }
+    }
+
     /// Add a user message into the memory if the memory has been set.
     async fn add_user_message(&self, message: &dyn std::fmt::Display) {
         if let Some(memory) = &self.memory {
@@ -71,11 +142,21 @@ impl<M: Completion> Executor<M> {
         }
     }
 
-    /// Add an AI message into the memory if the memory has been set.
-    async fn add_ai_message(&self, message: &dyn std::fmt::Display) {
+    /// Add an AI message with the reasoning and thinking blocks of its response into the memory
+    /// if the memory has been set.
+    async fn add_ai_message(
+        &self,
+        message: &dyn std::fmt::Display,
+        response: &impl ResponseContent,
+    ) {
         if let Some(memory) = &self.memory {
             let mut memory = memory.write().await;
-            memory.add_ai_message(message);
+            let mut message =
+                Message::new_ai_message(message).with_thinking_blocks(response.thinking_blocks());
+            if let Some(reasoning) = response.reasoning() {
+                message = message.with_reasoning(reasoning);
+            }
+            memory.add_message(message);
         }
     }
 
@@ -92,30 +173,91 @@ impl<M: Completion> Executor<M> {
         Ok(())
     }
 
-    /// Executes a tool action and returns the result.
-    async fn execute_tool(&self, call: ToolCall) -> anyhow::Result<String> {
-        let tools = self.tools.read().await;
-        if let Some(tool) = tools
-            .iter()
-            .find(|t| t.name().eq_ignore_ascii_case(&call.function.name))
+    /// Executes a tool action and returns the result, aborted once the token is cancelled.
+    async fn execute_tool(
+        &self,
+        call: ToolCall,
+        cancellation_token: Option<&CancellationToken>,
+    ) -> anyhow::Result<String> {
         {
-            Ok(tool.run(&call.function.arguments).await?)
-        } else {
-            let mcp_clients = self.mcp_clients.read().await;
-            if !mcp_clients.is_empty() {
-                for mcp_client in mcp_clients.iter() {
-                    if mcp_client.tools.contains_key(&call.function.name) {
-                        let arguments = serde_json::from_str(&call.function.arguments)?;
-                        let response = mcp_client.call_tool(&call.function.name, arguments).await?;
-                        if let Some(text) = response.content[0].as_text() {
-                            return Ok(text.to_string());
-                        } else {
-                            return Ok("".to_string());
-                        }
-                    }
-                }
+            let tools = self.tools.read().await;
+            if let Some(tool) = tools
+                .iter()
+                .find(|t| t.name().eq_ignore_ascii_case(&call.function.name))
+            {
+                return match cancellable(tool.run(&call.function.arguments), cancellation_token)
+                    .await?
+                {
+                    Ok(output) => Ok(output),
+                    // A refused action is an observation the model may explain to the user.
+                    Err(err @ ToolError::PolicyViolation(_)) => Ok(format!("Error: {err}")),
+                    Err(err) => Err(err.into()),
+                };
             }
-            Err(anyhow::anyhow!("Tool not found: {}", call.function.name))
         }
+        let Some(index) = self
+            .mcp_clients
+            .read()
+            .await
+            .iter()
+            .position(|client| client.resolve_tool(&call.function.name).is_some())
+        else {
+            return Err(anyhow::anyhow!("Tool not found: {}", call.function.name));
+        };
+        let arguments: serde_json::Value = serde_json::from_str(&call.function.arguments)?;
+        let mut output = self
+            .call_mcp_tool(
+                index,
+                &call.function.name,
+                arguments.clone(),
+                cancellation_token,
+            )
+            .await?;
+        if output.as_ref().is_err_and(MCPError::is_transport_error) {
+            // The server is gone, e.g. a crashed stdio server: respawn it and retry once.
+            reconnect_client(&self.mcp_clients, index).await?;
+            output = self
+                .call_mcp_tool(index, &call.function.name, arguments, cancellation_token)
+                .await?;
+        }
+        match output {
+            Ok(output) => Ok(output),
+            // The failure is an observation the model may recover from.
+            Err(MCPError::ToolError(error)) => Ok(format!("Error: {error}")),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Calls a tool of the MCP client at the given index, aborted once the token is cancelled.
+    async fn call_mcp_tool(
+        &self,
+        index: usize,
+        name: &str,
+        arguments: serde_json::Value,
+        cancellation_token: Option<&CancellationToken>,
+    ) -> Result<Result<String, MCPError>, CompletionError> {
+        let mcp_clients = self.mcp_clients.read().await;
+        let client = &mcp_clients[index];
+        let tool = client.resolve_tool(name).unwrap_or(name);
+        cancellable(client.call_tool_text(tool, arguments), cancellation_token).await
+    }
+}
+
+fn is_cancelled(cancellation_token: Option<&CancellationToken>) -> bool {
+    cancellation_token.is_some_and(|token| token.is_cancelled())
+}
+
+/// Runs the future until it completes or the token is cancelled.
+async fn cancellable<F: std::future::Future>(
+    future: F,
+    cancellation_token: Option<&CancellationToken>,
+) -> Result<F::Output, CompletionError> {
+    match cancellation_token {
+        Some(token) => tokio::select! {
+            biased;
+            _ = token.cancelled() => Err(CompletionError::Cancelled { usage: None }),
+            output = future => Ok(output),
+        },
+        None => Ok(future.await),
     }
 }
@@ -2,12 +2,12 @@ use std::marker::PhantomData;
 
 use crate::{
     agent::Agent,
-    chat::Completion,
+    chat::{Completion, ResponseFormat},
     task::TaskError,
-    tool::{StructureTool, ToolError},
+    tool::{StructureTool, ToolChoice, ToolError},
 };
 use async_trait::async_trait;
-use schemars::JsonSchema;
+use schemars::{schema_for, JsonSchema};
 use serde::{Deserialize, Serialize};
 
 pub struct Extractor<M>
@@ -22,11 +22,24 @@ where
     M: Completion,
 {
     /// Constructor for Extractor that initializes the agent with the given model.
+    ///
+    /// Models with native JSON schema structured outputs are constrained to the schema of `T`,
+    /// other models fall back to extracting the data structure through a tool call.
     #[inline]
     pub async fn new<T>(model: M) -> Self
     where
         T: Serialize + for<'a> Deserialize<'a> + JsonSchema + Send + Sync + 'static,
     {
+        // Schemas generated by schemars don't satisfy the strict mode requirements.
+        let response_format =
+            ResponseFormat::json_schema("extractor", serde_json::json!(schema_for!(T)), false);
+        if model.supports_response_format(&response_format) {
+            return Self {
+                agent: Agent::new("extract-agent", model)
+                    .preamble("Extract the data structure from the input string.")
+                    .response_format(response_format),
+            };
+        }
         Self {
             agent: Agent::new("extract-agent", model)
                 .preamble(
@@ -35,6 +48,8 @@ Note you MUST use the tool named `extractor` to extract the input string to the
 data structure.
 "#,
                 )
+                .tool_choice(ToolChoice::tool("extractor"))
+                .parallel_tool_calls(false)
                 .tool(ExtractTool::<T> { _data: PhantomData })
                 .await,
         }
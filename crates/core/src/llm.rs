@@ -1,10 +1,13 @@
 pub mod client;
+pub mod router;
 
-use crate::chat::{Completion, CompletionError};
+use crate::chat::{Completion, CompletionError, ResponseFormat};
 use crate::embeddings::{Embeddings, EmbeddingsData, EmbeddingsError};
+pub use alith_client::ledger::{UsageLedger, UsageTotals};
 use anyhow::Result;
 use async_trait::async_trait;
-use client::{Client, CompletionResponse};
+use client::{Client, CompletionResponse, ModelRegistry};
+pub use router::{ProviderHealth, RoutedResponse, RouterLLM, RoutingStrategy};
 
 #[cfg(feature = "inference")]
 use fastembed::TextEmbedding;
@@ -77,6 +80,14 @@ impl LLM {
         })
     }
 
+    /// Creates the model from a registry, falling back to the built-in presets.
+    pub fn from_model_name_with_registry(model: &str, registry: &ModelRegistry) -> Result<Self> {
+        Ok(Self {
+            model: model.to_string(),
+            client: Client::from_registry(model, registry)?,
+        })
+    }
+
     pub fn openai_compatible_model(api_key: &str, base_url: &str, model: &str) -> Result<Self> {
         Ok(Self {
             model: model.to_string(),
@@ -91,6 +102,12 @@ impl LLM {
         }
     }
 
+    /// Records the usage of the model in the ledger and enforces its budget.
+    pub fn ledger(mut self, ledger: UsageLedger) -> Self {
+        self.client.client.ledger = Some(ledger);
+        self
+    }
+
     #[inline]
     pub fn client(&self) -> &Client {
         &self.client
@@ -106,6 +123,10 @@ impl Completion for LLM {
     ) -> Result<Self::Response, CompletionError> {
         self.client.completion(request).await
     }
+
+    fn supports_response_format(&self, response_format: &ResponseFormat) -> bool {
+        self.client.supports_response_format(response_format)
+    }
 }
 
 #[derive(Clone)]
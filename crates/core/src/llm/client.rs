@@ -1,30 +1,47 @@
-use std::ops::Deref;
-use std::ops::DerefMut;
-use std::sync::Arc;
-
+use crate::chat::BudgetExceeded;
 use crate::chat::CallFunction;
 use crate::chat::Completion;
 use crate::chat::CompletionError;
 use crate::chat::Request;
 use crate::chat::ResponseContent;
+use crate::chat::ResponseFormat;
 use crate::chat::ResponseToolCalls;
+use crate::chat::ResponseUsage;
+use crate::chat::ThinkingBlock;
 use crate::chat::ToolCall;
+use crate::chat::UsageRecord;
 use crate::embeddings::EmbeddingsData;
 use crate::embeddings::EmbeddingsError;
 use anyhow::Result;
+use std::ops::Deref;
+use std::ops::DerefMut;
 
 pub use alith_client as client;
 pub use alith_client::basic_completion::BasicCompletion;
 pub use alith_client::embeddings::Embeddings;
 pub use alith_client::prelude::*;
 pub use alith_client::LLMClient;
+use alith_interface::llms::api::config::LLMApiConfigTrait;
 pub use alith_interface::requests::completion::{CompletionRequest, CompletionResponse};
-pub use alith_models::api_model::ApiLLMModel;
+pub use alith_models::api_model::registry::{ModelProvider, ModelRegistry, ResolvedModel};
+pub use alith_models::api_model::{ApiLLMModel, ApiModelCapabilities};
 
 impl ResponseContent for CompletionResponse {
     fn content(&self) -> String {
         self.content.to_string()
     }
+
+    fn reasoning(&self) -> Option<String> {
+        self.reasoning.clone()
+    }
+
+    fn thinking_blocks(&self) -> Vec<ThinkingBlock> {
+        self.thinking_blocks.clone()
+    }
+
+    fn response_id(&self) -> Option<String> {
+        self.response_id.clone()
+    }
 }
 
 pub struct Client {
@@ -48,37 +65,62 @@ impl DerefMut for Client {
 impl Clone for Client {
     fn clone(&self) -> Self {
         Self {
-            client: LLMClient::new(Arc::clone(&self.client.backend)),
+            client: self.client.clone(),
         }
     }
 }
 
 impl Client {
+    /// Creates the client of a model from the registry file set in `ALITH_MODEL_REGISTRY`,
+    /// falling back to the built-in presets.
     pub fn from_model_name(model: &str) -> Result<Client> {
-        if model.starts_with("gpt") {
-            let mut builder = LLMClient::openai();
-            builder.model = ApiLLMModel::openai_model_from_model_id(model);
-            let client = builder.init()?;
-            Ok(Client { client })
-        } else if model.starts_with("claude") {
-            let mut builder = LLMClient::anthropic();
-            builder.model = ApiLLMModel::anthropic_model_from_model_id(model);
-            let client = builder.init()?;
-            Ok(Client { client })
-        } else if model.starts_with("llama") || model.starts_with("sonar") {
-            let mut builder = LLMClient::perplexity();
-            builder.model = ApiLLMModel::perplexity_model_from_model_id(model);
-            let client = builder.init()?;
-            Ok(Client { client })
-        } else {
-            Err(anyhow::anyhow!("unknown model {model}"))
-        }
+        Self::from_registry(model, &ModelRegistry::from_env()?)
+    }
+
+    pub fn from_registry(model: &str, registry: &ModelRegistry) -> Result<Client> {
+        let ResolvedModel {
+            provider,
+            base_url,
+            api_key_env,
+            model,
+        } = registry.resolve(model)?;
+        let client = match provider {
+            ModelProvider::OpenAI
+            | ModelProvider::OpenAIResponses
+            | ModelProvider::OpenAICompatible => {
+                let mut builder = LLMClient::openai();
+                builder.model = model;
+                builder.responses_api = provider == ModelProvider::OpenAIResponses;
+                if provider == ModelProvider::OpenAICompatible {
+                    builder.config.logging_config.logger_name = "generic".to_string();
+                }
+                configure_endpoint(&mut builder, base_url, api_key_env);
+                builder.init()?
+            }
+            ModelProvider::Anthropic => {
+                let mut builder = LLMClient::anthropic();
+                builder.model = model;
+                configure_endpoint(&mut builder, base_url, api_key_env);
+                builder.init()?
+            }
+            ModelProvider::Perplexity => {
+                let mut builder = LLMClient::perplexity();
+                builder.model = model;
+                configure_endpoint(&mut builder, base_url, api_key_env);
+                builder.init()?
+            }
+        };
+        Ok(Client { client })
     }
 
     pub fn openai_compatible_client(api_key: &str, base_url: &str, model: &str) -> Result<Client> {
         let mut builder = LLMClient::openai();
         builder.model = ApiLLMModel::gpt_4();
         builder.model.model_base.model_id = model.to_string();
+        builder.model.capabilities = ApiModelCapabilities::from_model_id(model);
+        // The pricing of the provider is unknown
+        builder.model.cost_per_m_in_tokens = 0.0;
+        builder.model.cost_per_m_out_tokens = 0.0;
         builder.config.api_config.api_key = Some(api_key.to_string().into());
         builder.config.api_config.host = base_url.to_string();
         builder.config.logging_config.logger_name = "generic".to_string();
@@ -87,6 +129,20 @@ impl Client {
     }
 }
 
+fn configure_endpoint<B: LLMApiConfigTrait>(
+    builder: &mut B,
+    base_url: Option<String>,
+    api_key_env: Option<String>,
+) {
+    let api_config = builder.api_base_config_mut();
+    if let Some(base_url) = base_url {
+        api_config.host = base_url;
+    }
+    if let Some(api_key_env) = api_key_env {
+        api_config.api_key_env_var = api_key_env;
+    }
+}
+
 impl ResponseToolCalls for CompletionResponse {
     fn toolcalls(&self) -> Vec<ToolCall> {
         self.tool_calls
@@ -105,6 +161,12 @@ impl ResponseToolCalls for CompletionResponse {
     }
 }
 
+impl ResponseUsage for CompletionResponse {
+    fn usage(&self) -> Option<UsageRecord> {
+        Some(UsageRecord::from_response(self))
+    }
+}
+
 impl Drop for Client {
     fn drop(&mut self) {
         self.client.shutdown();
@@ -123,6 +185,16 @@ impl Completion for Client {
         if let Some(max_tokens) = request.max_tokens {
             completion.max_tokens(max_tokens.try_into().unwrap());
         }
+        if let Some(thinking_budget_tokens) = request.thinking_budget_tokens {
+            completion.thinking_budget_tokens(thinking_budget_tokens);
+        }
+        if let Some(reasoning_effort) = request.reasoning_effort {
+            completion.reasoning_effort(reasoning_effort);
+        }
+        completion.cache_prompt(request.cache_prompt);
+        if let Some(cancellation_token) = request.cancellation_token.clone() {
+            completion.cancellation_token(cancellation_token);
+        }
         // Construct the prompt
         let prompt = completion.prompt();
         // Add preamble if provided
@@ -164,11 +236,48 @@ impl Completion for Client {
         }
         // Add custom tools
         completion.base_req.tools.append(&mut request.tools.clone());
+        completion.base_req.tool_choice = request.tool_choice.clone();
+        completion.base_req.parallel_tool_calls = request.parallel_tool_calls;
+        completion.base_req.previous_response_id = request.previous_response_id.clone();
+        completion.base_req.assistant_thinking = request
+            .history
+            .iter()
+            .filter(|msg| msg.role == "assistant")
+            .map(|msg| msg.thinking_blocks.clone())
+            .collect();
+        // Set the structured output format
+        if let Some(response_format) = request.response_format {
+            completion.base_req.set_response_format(response_format);
+        }
         // Execute the completion request
+        let model = completion.base_req.backend.model_id().to_string();
         completion
             .run()
             .await
-            .map_err(|err| CompletionError::Normal(err.to_string()))
+            .map_err(|err| completion_error(err, &model))
+    }
+
+    fn supports_response_format(&self, response_format: &ResponseFormat) -> bool {
+        self.client
+            .backend
+            .supports_response_format(response_format)
+    }
+}
+
+/// Converts the error of a completion, keeping the provider error so callers can tell rate
+/// limits and outages apart, and the usage of the retries discarded before a cancellation.
+fn completion_error(err: anyhow::Error, model: &str) -> CompletionError {
+    match err.downcast::<alith_interface::requests::completion::CompletionError>() {
+        Ok(alith_interface::requests::completion::CompletionError::Cancelled { usage }) => {
+            CompletionError::Cancelled {
+                usage: (usage.total_tokens > 0).then(|| UsageRecord::from_usage(model, &usage)),
+            }
+        }
+        Ok(err) => CompletionError::Provider(err),
+        Err(err) => match err.downcast::<BudgetExceeded>() {
+            Ok(err) => CompletionError::BudgetExceeded(err),
+            Err(err) => CompletionError::Normal(err.to_string()),
+        },
     }
 }
 
@@ -181,19 +290,115 @@ impl Client {
         let mut embeddings = self.client.embeddings();
         embeddings.set_input(input.clone());
         embeddings.set_model(model.to_string());
-        embeddings
+        let resp = embeddings
             .run()
             .await
-            .map(|resp| {
-                resp.data
-                    .iter()
-                    .zip(input)
-                    .map(|(data, document)| EmbeddingsData {
-                        document,
-                        vec: data.embedding.clone(),
-                    })
-                    .collect()
-            })
-            .map_err(|err| EmbeddingsError::ResponseError(err.to_string()))
+            .map_err(|err| EmbeddingsError::ResponseError(err.to_string()))?;
+        crate::embeddings::zip_embeddings(input, resp.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::Message;
+
+    #[test]
+    fn test_cancelled_usage() {
+        use alith_interface::requests::completion::{CompletionError as ProviderError, TokenUsage};
+
+        let usage = TokenUsage {
+            prompt_tokens: 10,
+            completion_tokens: 5,
+            total_tokens: 15,
+            ..Default::default()
+        };
+        let err = completion_error(ProviderError::Cancelled { usage }.into(), "gpt-4o");
+        let CompletionError::Cancelled { usage: Some(usage) } = err else {
+            panic!("expected the partial usage, got {err:?}");
+        };
+        assert_eq!(usage.model, "gpt-4o");
+        assert_eq!((usage.prompt_tokens, usage.completion_tokens), (10, 5));
+
+        let err = completion_error(
+            ProviderError::Cancelled {
+                usage: TokenUsage::default(),
+            }
+            .into(),
+            "gpt-4o",
+        );
+        assert!(matches!(err, CompletionError::Cancelled { usage: None }));
+    }
+
+    #[tokio::test]
+    async fn test_thinking_blocks_sent_back() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/messages")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "messages": [
+                    {"role": "user", "content": "Weather in Rome?"},
+                    {"role": "assistant", "content": [
+                        {"type": "thinking", "thinking": "Use the tool.", "signature": "sig"},
+                        {"type": "text", "text": "Calling the weather tool."}
+                    ]},
+                    {"role": "user", "content": "Rainy."}
+                ],
+                "thinking": {"type": "enabled", "budget_tokens": 2048}
+            })))
+            .with_header("content-type", "application/json")
+            .with_body(
+                serde_json::json!({
+                    "id": "msg_2",
+                    "model": "claude-3-7-sonnet-20250219",
+                    "stop_reason": "end_turn",
+                    "stop_sequence": null,
+                    "content": [
+                        {"type": "thinking", "thinking": "Answer.", "signature": "sig2"},
+                        {"type": "text", "text": "It rains in Rome."}
+                    ],
+                    "usage": {"input_tokens": 10, "output_tokens": 5}
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let mut builder = LLMClient::anthropic();
+        builder.model = ApiLLMModel::claude_3_7_sonnet();
+        builder.config.api_config.api_key = Some("test-key".to_string().into());
+        builder.config.api_config.host = server.url();
+        builder.config.logging_config.logging_enabled = false;
+        let mut client = Client {
+            client: builder.init().unwrap(),
+        };
+
+        let mut request = Request::new("Rainy.".to_string(), String::new());
+        request.thinking_budget_tokens = Some(2048);
+        request.history = vec![
+            Message {
+                role: "user".to_string(),
+                content: "Weather in Rome?".to_string(),
+                thinking_blocks: Vec::new(),
+            },
+            Message {
+                role: "assistant".to_string(),
+                content: "Calling the weather tool.".to_string(),
+                thinking_blocks: vec![ThinkingBlock::Thinking {
+                    thinking: "Use the tool.".to_string(),
+                    signature: "sig".to_string(),
+                }],
+            },
+        ];
+        let response = client.completion(request).await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(response.content(), "It rains in Rome.");
+        assert_eq!(
+            response.thinking_blocks(),
+            vec![ThinkingBlock::Thinking {
+                thinking: "Answer.".to_string(),
+                signature: "sig2".to_string(),
+            }]
+        );
     }
 }
@@ -0,0 +1,504 @@
+use std::ops::Deref;
+use std::time::{Duration, Instant};
+
+use super::client::CompletionResponse;
+use super::LLM;
+use crate::chat::{
+    Completion, CompletionError, Request, ResponseContent, ResponseFormat, ResponseToolCalls,
+    ResponseUsage, ThinkingBlock, ToolCall, UsageRecord,
+};
+
+/// The default number of consecutive failures that opens a provider circuit.
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+/// The default time an open circuit waits before the provider is tried again.
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// How the router picks the first provider for a request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoutingStrategy {
+    /// Always start with the first healthy provider in order, the others are fallbacks.
+    #[default]
+    Failover,
+    /// Spread requests over the healthy providers according to their weights.
+    WeightedRoundRobin,
+}
+
+/// The health of a provider as tracked by its circuit breaker.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderHealth {
+    /// Failures since the last successful request.
+    pub consecutive_failures: u32,
+    pub total_requests: u64,
+    pub total_failures: u64,
+    /// While set and in the future, the circuit is open and the provider is skipped.
+    pub open_until: Option<Instant>,
+    pub last_error: Option<String>,
+}
+
+impl ProviderHealth {
+    /// Whether the circuit is closed, or half open after the cooldown.
+    #[inline]
+    pub fn is_available(&self) -> bool {
+        self.open_until.is_none_or(|until| until <= Instant::now())
+    }
+}
+
+/// A completion response along with the provider that produced it.
+pub struct RoutedResponse {
+    /// The index of the provider in the router.
+    pub provider_index: usize,
+    /// The model name of the provider.
+    pub provider: String,
+    pub response: CompletionResponse,
+}
+
+impl Deref for RoutedResponse {
+    type Target = CompletionResponse;
+
+    fn deref(&self) -> &Self::Target {
+        &self.response
+    }
+}
+
+impl ResponseContent for RoutedResponse {
+    fn content(&self) -> String {
+        self.response.content()
+    }
+
+    fn reasoning(&self) -> Option<String> {
+        self.response.reasoning()
+    }
+
+    fn thinking_blocks(&self) -> Vec<ThinkingBlock> {
+        self.response.thinking_blocks()
+    }
+
+    fn response_id(&self) -> Option<String> {
+        self.response.response_id()
+    }
+}
+
+impl ResponseToolCalls for RoutedResponse {
+    fn toolcalls(&self) -> Vec<ToolCall> {
+        self.response.toolcalls()
+    }
+}
+
+impl ResponseUsage for RoutedResponse {
+    fn usage(&self) -> Option<UsageRecord> {
+        self.response.usage()
+    }
+}
+
+struct Provider {
+    llm: LLM,
+    weight: u32,
+    current_weight: i64,
+    health: ProviderHealth,
+}
+
+/// A [Completion] implementation that routes requests over an ordered list of [LLM]s.
+///
+/// When a provider fails with a transient error (rate limits, server errors, timeouts) or a spent
+/// quota, the request fails over to the next provider. Other errors are returned as is, as
+/// another provider would reject the request as well. Each provider has a circuit breaker: after
+/// `failure_threshold` consecutive failures, or at once when its quota is spent, it is skipped
+/// for the cooldown period, then tried again with a single request.
+///
+/// # Example
+///
+/// ```no_run
+/// use alith_core::llm::{RouterLLM, RoutingStrategy, LLM};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let router = RouterLLM::new([
+///     LLM::from_model_name("gpt-4o-mini")?,
+///     LLM::from_model_name("claude-3-5-sonnet")?,
+/// ])
+/// .strategy(RoutingStrategy::Failover);
+/// # Ok(())
+/// # }
+/// ```
+pub struct RouterLLM {
+    providers: Vec<Provider>,
+    strategy: RoutingStrategy,
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+impl RouterLLM {
+    /// Creates a router over the given providers, each with a weight of 1.
+    pub fn new<I>(llms: I) -> Self
+    where
+        I: IntoIterator<Item = LLM>,
+    {
+        Self {
+            providers: llms
+                .into_iter()
+                .map(|llm| Provider {
+                    llm,
+                    weight: 1,
+                    current_weight: 0,
+                    health: ProviderHealth::default(),
+                })
+                .collect(),
+            strategy: RoutingStrategy::default(),
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+
+    /// Appends a provider with the given weight for [RoutingStrategy::WeightedRoundRobin].
+    pub fn provider(mut self, llm: LLM, weight: u32) -> Self {
+        self.providers.push(Provider {
+            llm,
+            weight,
+            current_weight: 0,
+            health: ProviderHealth::default(),
+        });
+        self
+    }
+
+    /// Sets how the first provider of a request is picked.
+    pub fn strategy(mut self, strategy: RoutingStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Configures the circuit breaker of every provider.
+    pub fn circuit_breaker(mut self, failure_threshold: u32, cooldown: Duration) -> Self {
+        self.failure_threshold = failure_threshold.max(1);
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// The model name and health of every provider, in order.
+    pub fn health(&self) -> Vec<(&str, &ProviderHealth)> {
+        self.providers
+            .iter()
+            .map(|p| (p.llm.model.as_str(), &p.health))
+            .collect()
+    }
+
+    #[inline]
+    pub fn providers(&self) -> impl Iterator<Item = &LLM> {
+        self.providers.iter().map(|p| &p.llm)
+    }
+
+    /// The order to try the providers in for the next request.
+    fn route(&mut self) -> Vec<usize> {
+        let mut available: Vec<usize> = (0..self.providers.len())
+            .filter(|i| self.providers[*i].health.is_available())
+            .collect();
+        if available.is_empty() {
+            // Every circuit is open, try the providers that recover first.
+            available = (0..self.providers.len()).collect();
+            available.sort_by_key(|i| self.providers[*i].health.open_until);
+            return available;
+        }
+        if self.strategy == RoutingStrategy::WeightedRoundRobin {
+            // Smooth weighted round robin over the available providers.
+            let total: i64 = available
+                .iter()
+                .map(|i| self.providers[*i].weight as i64)
+                .sum();
+            for i in &available {
+                let provider = &mut self.providers[*i];
+                provider.current_weight += provider.weight as i64;
+            }
+            let selected = *available
+                .iter()
+                .rev()
+                .max_by_key(|i| self.providers[**i].current_weight)
+                .expect("available is not empty");
+            self.providers[selected].current_weight -= total;
+            available.retain(|i| *i != selected);
+            available.insert(0, selected);
+        }
+        available
+    }
+
+    fn record_success(&mut self, index: usize) {
+        let health = &mut self.providers[index].health;
+        health.total_requests += 1;
+        health.consecutive_failures = 0;
+        health.open_until = None;
+    }
+
+    fn record_failure(&mut self, index: usize, err: &CompletionError) {
+        let health = &mut self.providers[index].health;
+        health.total_requests += 1;
+        health.total_failures += 1;
+        health.consecutive_failures += 1;
+        health.last_error = Some(err.to_string());
+        // A half open circuit opens again on the first failure, and a spent quota will not
+        // recover with the next requests.
+        if health.consecutive_failures >= self.failure_threshold
+            || health.open_until.is_some()
+            || err.is_quota_exhausted()
+        {
+            health.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+impl Completion for RouterLLM {
+    type Response = RoutedResponse;
+
+    async fn completion(&mut self, request: Request) -> Result<Self::Response, CompletionError> {
+        let mut last_error = None;
+        for index in self.route() {
+            let provider = &mut self.providers[index];
+            match provider.llm.completion(request.clone()).await {
+                Ok(response) => {
+                    let provider = provider.llm.model.clone();
+                    self.record_success(index);
+                    return Ok(RoutedResponse {
+                        provider_index: index,
+                        provider,
+                        response,
+                    });
+                }
+                Err(err) if err.is_transient() || err.is_quota_exhausted() => {
+                    tracing::warn!(
+                        "Provider {} failed, trying the next one: {}",
+                        provider.llm.model,
+                        err
+                    );
+                    self.record_failure(index, &err);
+                    last_error = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| CompletionError::Normal("The router has no providers".to_string())))
+    }
+
+    fn supports_response_format(&self, response_format: &ResponseFormat) -> bool {
+        // Any of the providers may answer the request.
+        !self.providers.is_empty()
+            && self
+                .providers
+                .iter()
+                .all(|p| p.llm.supports_response_format(response_format))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::client::{Client, LLMClient};
+    use mockito::{Mock, Server, ServerGuard};
+
+    const COMPLETIONS_PATH: &str = "/chat/completions";
+
+    fn llm(server: &ServerGuard, model: &str) -> LLM {
+        let mut builder = LLMClient::openai();
+        builder.config.api_config.api_key = Some("test-key".to_string().into());
+        builder.config.api_config.host = server.url();
+        builder.config.logging_config.logging_enabled = false;
+        LLM {
+            model: model.to_string(),
+            client: Client {
+                client: builder.init().unwrap(),
+            },
+        }
+    }
+
+    fn request() -> Request {
+        Request::new("Hello".to_string(), String::new())
+    }
+
+    async fn reply(server: &mut ServerGuard, content: &str, hits: usize) -> Mock {
+        server
+            .mock("POST", COMPLETIONS_PATH)
+            .with_header("content-type", "application/json")
+            .with_body(
+                serde_json::json!({
+                    "id": "chatcmpl-1",
+                    "created": 0,
+                    "model": "test",
+                    "choices": [{
+                        "index": 0,
+                        "message": {"role": "assistant", "content": content},
+                        "finish_reason": "stop"
+                    }],
+                    "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}
+                })
+                .to_string(),
+            )
+            .expect(hits)
+            .create_async()
+            .await
+    }
+
+    async fn fail(server: &mut ServerGuard, status: usize, r#type: &str, hits: usize) -> Mock {
+        server
+            .mock("POST", COMPLETIONS_PATH)
+            .with_status(status)
+            .with_header("content-type", "application/json")
+            .with_body(
+                serde_json::json!({"error": {"message": "failed", "type": r#type}}).to_string(),
+            )
+            .expect(hits)
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_failover_order() {
+        let (mut a, mut b, mut c) = (
+            Server::new_async().await,
+            Server::new_async().await,
+            Server::new_async().await,
+        );
+        let mock_a = fail(&mut a, 500, "server_error", 1).await;
+        let mock_b = reply(&mut b, "from b", 1).await;
+        let mock_c = reply(&mut c, "from c", 0).await;
+        let mut router = RouterLLM::new([llm(&a, "a"), llm(&b, "b"), llm(&c, "c")]);
+
+        let response = router.completion(request()).await.unwrap();
+        assert_eq!(response.provider_index, 1);
+        assert_eq!(response.provider, "b");
+        assert_eq!(response.content(), "from b");
+        let health = router.health();
+        assert_eq!(health[0].1.consecutive_failures, 1);
+        assert_eq!(health[1].1.total_requests, 1);
+        mock_a.assert_async().await;
+        mock_b.assert_async().await;
+
+        // A rejected request is not failed over.
+        mock_a.remove_async().await;
+        let mock_a = fail(&mut a, 400, "invalid_request_error", 1).await;
+        assert!(router.completion(request()).await.is_err());
+        mock_a.assert_async().await;
+        mock_b.assert_async().await;
+        mock_c.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_weighted_round_robin() {
+        let (mut a, mut b) = (Server::new_async().await, Server::new_async().await);
+        let mock_a = reply(&mut a, "from a", 6).await;
+        let mock_b = reply(&mut b, "from b", 2).await;
+        let mut router = RouterLLM::new([])
+            .strategy(RoutingStrategy::WeightedRoundRobin)
+            .provider(llm(&a, "a"), 3)
+            .provider(llm(&b, "b"), 1);
+
+        let mut indices = Vec::new();
+        for _ in 0..8 {
+            indices.push(router.completion(request()).await.unwrap().provider_index);
+        }
+        // Smooth weighted round robin interleaves the providers.
+        assert_eq!(indices, [0, 0, 1, 0, 0, 0, 1, 0]);
+        mock_a.assert_async().await;
+        mock_b.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker() {
+        let (mut a, mut b) = (Server::new_async().await, Server::new_async().await);
+        let mock_a = fail(&mut a, 500, "server_error", 2).await;
+        let mock_b = reply(&mut b, "from b", 4).await;
+        let mut router = RouterLLM::new([llm(&a, "a"), llm(&b, "b")])
+            .circuit_breaker(2, Duration::from_secs(60));
+
+        // The circuit opens after two failures, then the provider is skipped.
+        for _ in 0..3 {
+            let response = router.completion(request()).await.unwrap();
+            assert_eq!(response.provider_index, 1);
+        }
+        assert!(!router.health()[0].1.is_available());
+        mock_a.assert_async().await;
+
+        // Half open after the cooldown, a single failure opens the circuit again.
+        router.providers[0].health.open_until = Some(Instant::now());
+        mock_a.remove_async().await;
+        let mock_a = fail(&mut a, 500, "server_error", 1).await;
+        let response = router.completion(request()).await.unwrap();
+        assert_eq!(response.provider_index, 1);
+        assert!(!router.health()[0].1.is_available());
+        mock_a.assert_async().await;
+        mock_b.assert_async().await;
+
+        // A successful request closes the circuit.
+        router.providers[0].health.open_until = Some(Instant::now());
+        mock_a.remove_async().await;
+        let mock_a = reply(&mut a, "from a", 1).await;
+        let response = router.completion(request()).await.unwrap();
+        assert_eq!(response.provider_index, 0);
+        assert_eq!(response.content(), "from a");
+        let health = &router.health()[0].1;
+        assert!(health.open_until.is_none());
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!(health.total_failures, 3);
+        mock_a.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_quota_fails_over() {
+        let (mut a, mut b) = (Server::new_async().await, Server::new_async().await);
+        let mock_a = fail(&mut a, 429, "insufficient_quota", 1).await;
+        let mock_b = reply(&mut b, "from b", 2).await;
+        let mut router = RouterLLM::new([llm(&a, "a"), llm(&b, "b")]);
+
+        // The spent provider fails over and is not tried again.
+        for _ in 0..2 {
+            let response = router.completion(request()).await.unwrap();
+            assert_eq!(response.provider_index, 1);
+        }
+        assert!(!router.health()[0].1.is_available());
+        mock_a.assert_async().await;
+        mock_b.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_thinking_blocks_forwarded() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/messages")
+            .with_header("content-type", "application/json")
+            .with_body(
+                serde_json::json!({
+                    "id": "msg_1",
+                    "model": "claude-3-7-sonnet-20250219",
+                    "stop_reason": "end_turn",
+                    "stop_sequence": null,
+                    "content": [
+                        {"type": "thinking", "thinking": "Answer.", "signature": "sig"},
+                        {"type": "text", "text": "Hello."}
+                    ],
+                    "usage": {"input_tokens": 10, "output_tokens": 5}
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        let mut builder = LLMClient::anthropic();
+        builder.config.api_config.api_key = Some("test-key".to_string().into());
+        builder.config.api_config.host = server.url();
+        builder.config.logging_config.logging_enabled = false;
+        let claude = LLM {
+            model: "claude".to_string(),
+            client: Client {
+                client: builder.init().unwrap(),
+            },
+        };
+        let mut router = RouterLLM::new([claude]);
+
+        let mut request = request();
+        request.thinking_budget_tokens = Some(2048);
+        let response = router.completion(request).await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(response.content(), "Hello.");
+        assert_eq!(
+            response.thinking_blocks(),
+            vec![ThinkingBlock::Thinking {
+                thinking: "Answer.".to_string(),
+                signature: "sig".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! The `mcpServers` config of the MCP servers, with the schema of the common MCP hosts.
+//!
+//! ```json
+//! {
+//!     "mcpServers": {
+//!         "github": {
+//!             "command": "npx",
+//!             "args": ["-y", "@modelcontextprotocol/server-github"],
+//!             "env": { "GITHUB_PERSONAL_ACCESS_TOKEN": "${GITHUB_TOKEN}" },
+//!             "excludeTools": ["delete_repository"]
+//!         },
+//!         "search": {
+//!             "url": "https://example.com/mcp",
+//!             "headers": { "Authorization": "Bearer ${SEARCH_API_KEY}" },
+//!             "timeout": 60
+//!         },
+//!         "calculator": { "url": "http://localhost:8000/sse", "disabled": true }
+//!     }
+//! }
+//! ```
+
+use super::MCPError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// The default timeout of the requests to an MCP server.
+pub const DEFAULT_MCP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The transport of an MCP server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MCPTransportKind {
+    /// A local process spawned with `command`, talking over its stdin and stdout.
+    Stdio,
+    /// A remote server streaming its messages as server-sent events.
+    Sse,
+    /// A remote server of the streamable HTTP transport.
+    #[serde(rename = "http", alias = "streamable_http", alias = "streamableHttp")]
+    StreamableHttp,
+}
+
+/// The config of an MCP server, either spawned with a `command` or reached at a `url`.
+///
+/// The `${VAR}` references in the command, args, env, url and headers are replaced by the
+/// environment variables when connecting.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MCPServerConfig {
+    /// The command spawning a stdio server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// The URL of a remote server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The transport, inferred when not set: stdio for a command, SSE for a URL ending with
+    /// `/sse` and streamable HTTP for the other URLs.
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub transport: Option<MCPTransportKind>,
+    /// The headers sent with every request to a remote server, e.g. `Authorization`.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Skips the server when setting up the clients of a config.
+    #[serde(default)]
+    pub disabled: bool,
+    /// The timeout of each request to the server, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// The tools exposed to the agent, all of them when empty.
+    #[serde(default)]
+    pub include_tools: Vec<String>,
+    /// The tools hidden from the agent, applied after `include_tools`.
+    #[serde(default)]
+    pub exclude_tools: Vec<String>,
+}
+
+impl MCPServerConfig {
+    /// A server spawned with a command.
+    pub fn stdio<S: Into<String>>(command: S, args: Vec<String>) -> Self {
+        Self {
+            command: Some(command.into()),
+            args,
+            ..Default::default()
+        }
+    }
+
+    /// A remote server, its transport inferred from the URL.
+    pub fn remote<S: Into<String>>(url: S) -> Self {
+        Self {
+            url: Some(url.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn transport_kind(&self) -> Result<MCPTransportKind, MCPError> {
+        match (self.transport, &self.url, &self.command) {
+            (Some(kind), _, _) => Ok(kind),
+            (None, Some(url), _) if url.trim_end_matches('/').ends_with("/sse") => {
+                Ok(MCPTransportKind::Sse)
+            }
+            (None, Some(_), _) => Ok(MCPTransportKind::StreamableHttp),
+            (None, None, Some(_)) => Ok(MCPTransportKind::Stdio),
+            (None, None, None) => Err(MCPError::InvalidConfig(
+                "an MCP server requires a command or a url".to_string(),
+            )),
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_MCP_TIMEOUT)
+    }
+
+    /// Whether a tool of the server is exposed to the agent.
+    pub fn is_tool_enabled(&self, name: &str) -> bool {
+        (self.include_tools.is_empty() || self.include_tools.iter().any(|tool| tool == name))
+            && !self.exclude_tools.iter().any(|tool| tool == name)
+    }
+
+    /// The config with the `${VAR}` references replaced by the environment variables.
+    pub fn expand_env(&self) -> Result<Self, MCPError> {
+        let expand_map = |map: &HashMap<String, String>| {
+            map.iter()
+                .map(|(key, value)| Ok((key.clone(), expand_env(value)?)))
+                .collect::<Result<HashMap<_, _>, MCPError>>()
+        };
+        Ok(Self {
+            command: self.command.as_deref().map(expand_env).transpose()?,
+            args: self
+                .args
+                .iter()
+                .map(|arg| expand_env(arg))
+                .collect::<Result<_, _>>()?,
+            env: expand_map(&self.env)?,
+            url: self.url.as_deref().map(expand_env).transpose()?,
+            headers: expand_map(&self.headers)?,
+            ..self.clone()
+        })
+    }
+}
+
+/// The config of the MCP servers by name.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MCPConfig {
+    #[serde(rename = "mcpServers")]
+    pub mcp_servers: HashMap<String, MCPServerConfig>,
+}
+
+impl MCPConfig {
+    pub async fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, MCPError> {
+        let config_str = tokio::fs::read_to_string(path).await?;
+        Ok(serde_json::from_str(&config_str)?)
+    }
+}
+
+/// Replaces the `${VAR}` references of a value by the environment variables.
+pub fn expand_env(value: &str) -> Result<String, MCPError> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 2..start + 2 + len];
+        let var = std::env::var(name).map_err(|_| MCPError::EnvVarNotFound(name.to_string()))?;
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&var);
+        rest = &rest[start + 3 + len..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mcp_config() {
+        let config: MCPConfig = serde_json::from_str(
+            r#"{
+                "mcpServers": {
+                    "github": {
+                        "command": "npx",
+                        "args": ["-y", "@modelcontextprotocol/server-github"],
+                        "excludeTools": ["delete_repository"]
+                    },
+                    "search": {
+                        "url": "https://example.com/mcp",
+                        "headers": { "Authorization": "Bearer ${ALITH_TEST_MCP_KEY}" },
+                        "timeout": 60
+                    },
+                    "calculator": { "url": "http://localhost:8000/sse/", "disabled": true },
+                    "legacy": { "url": "http://localhost:8000/events", "type": "sse" }
+                }
+            }"#,
+        )
+        .unwrap();
+        let servers = &config.mcp_servers;
+
+        let github = &servers["github"];
+        assert_eq!(github.transport_kind().unwrap(), MCPTransportKind::Stdio);
+        assert_eq!(github.timeout(), DEFAULT_MCP_TIMEOUT);
+        assert!(github.is_tool_enabled("create_issue"));
+        assert!(!github.is_tool_enabled("delete_repository"));
+
+        let search = &servers["search"];
+        assert_eq!(
+            search.transport_kind().unwrap(),
+            MCPTransportKind::StreamableHttp
+        );
+        assert_eq!(search.timeout(), Duration::from_secs(60));
+        std::env::set_var("ALITH_TEST_MCP_KEY", "secret");
+        assert_eq!(
+            search.expand_env().unwrap().headers["Authorization"],
+            "Bearer secret"
+        );
+
+        assert!(servers["calculator"].disabled);
+        assert_eq!(
+            servers["calculator"].transport_kind().unwrap(),
+            MCPTransportKind::Sse
+        );
+        assert_eq!(
+            servers["legacy"].transport_kind().unwrap(),
+            MCPTransportKind::Sse
+        );
+        assert!(MCPServerConfig::default().transport_kind().is_err());
+    }
+
+    #[test]
+    fn test_expand_env() {
+        std::env::set_var("ALITH_TEST_MCP_HOST", "localhost");
+        assert_eq!(
+            expand_env("http://${ALITH_TEST_MCP_HOST}:8000/${ALITH_TEST_MCP_HOST}").unwrap(),
+            "http://localhost:8000/localhost"
+        );
+        assert_eq!(expand_env("price: ${").unwrap(), "price: ${");
+        assert!(matches!(
+            expand_env("${ALITH_TEST_MCP_MISSING}"),
+            Err(MCPError::EnvVarNotFound(name)) if name == "ALITH_TEST_MCP_MISSING"
+        ));
+    }
+}
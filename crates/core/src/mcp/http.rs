@@ -0,0 +1,389 @@
+//! The transports of the remote MCP servers, sending the configured headers with every request.
+
+use super::MCPError;
+use futures::StreamExt;
+use mcp_client::transport::{Error, TransportHandle};
+use mcp_spec::protocol::JsonRpcMessage;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{oneshot, Mutex, RwLock};
+use tokio::task::JoinHandle;
+
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+const TOOLS_CHANGED_METHOD: &str = "notifications/tools/list_changed";
+
+/// Converts the configured headers, failing on the invalid names and values.
+pub fn header_map(headers: &HashMap<String, String>) -> Result<HeaderMap, MCPError> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| MCPError::InvalidConfig(format!("invalid header {name}: {e}")))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| MCPError::InvalidConfig(format!("invalid header {name}: {e}")))?;
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// A server of the streamable HTTP transport: every message is posted to the URL and the
+/// response comes back either as JSON or as a stream of server-sent events.
+pub struct StreamableHttpTransport {
+    url: String,
+    headers: HeaderMap,
+    tools_changed: Arc<AtomicBool>,
+}
+
+impl StreamableHttpTransport {
+    pub fn new<S: Into<String>>(url: S, headers: HeaderMap) -> Self {
+        Self {
+            url: url.into(),
+            headers,
+            tools_changed: Arc::default(),
+        }
+    }
+
+    /// Sets the flag when the server notifies that its tools changed.
+    pub fn notify_tools_changed(mut self, tools_changed: Arc<AtomicBool>) -> Self {
+        self.tools_changed = tools_changed;
+        self
+    }
+
+    pub async fn start(&self) -> Result<StreamableHttpHandle, Error> {
+        Ok(StreamableHttpHandle {
+            client: build_client(&self.headers)?,
+            url: self.url.clone(),
+            session_id: Arc::new(RwLock::new(None)),
+            tools_changed: Arc::clone(&self.tools_changed),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct StreamableHttpHandle {
+    client: reqwest::Client,
+    url: String,
+    /// The session assigned by the server when initializing, sent back with every message.
+    session_id: Arc<RwLock<Option<String>>>,
+    tools_changed: Arc<AtomicBool>,
+}
+
+#[async_trait::async_trait]
+impl TransportHandle for StreamableHttpHandle {
+    async fn send(&self, message: JsonRpcMessage) -> Result<JsonRpcMessage, Error> {
+        let id = request_id(&message);
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(ACCEPT, "application/json, text/event-stream")
+            .json(&message);
+        if let Some(session_id) = self.session_id.read().await.as_ref() {
+            request = request.header(SESSION_ID_HEADER, session_id);
+        }
+        let response = request.send().await.map_err(connection_error)?;
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            *self.session_id.write().await = Some(session_id.to_string());
+        }
+        let response = check_status(response).await?;
+        // Notifications are only acknowledged.
+        let Some(id) = id else {
+            return Ok(JsonRpcMessage::Nil);
+        };
+
+        let is_event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("text/event-stream"));
+        if !is_event_stream {
+            let body = response.bytes().await.map_err(connection_error)?;
+            return Ok(serde_json::from_slice(&body)?);
+        }
+        // The server may send requests and notifications before the response, skip them.
+        let mut parser = SseParser::default();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            for event in parser.push(&chunk.map_err(connection_error)?) {
+                let message: JsonRpcMessage = serde_json::from_str(&event.data)?;
+                if response_id(&message) == Some(id) {
+                    return Ok(message);
+                }
+                if is_tools_changed(&message) {
+                    self.tools_changed.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+        Err(Error::SseConnection(
+            "the stream ended before the response".to_string(),
+        ))
+    }
+}
+
+/// A server of the SSE transport: the messages are posted to the endpoint announced on the
+/// event stream, and the responses come back on the stream.
+pub struct SseHttpTransport {
+    url: String,
+    headers: HeaderMap,
+    tools_changed: Arc<AtomicBool>,
+}
+
+impl SseHttpTransport {
+    pub fn new<S: Into<String>>(url: S, headers: HeaderMap) -> Self {
+        Self {
+            url: url.into(),
+            headers,
+            tools_changed: Arc::default(),
+        }
+    }
+
+    /// Sets the flag when the server notifies that its tools changed.
+    pub fn notify_tools_changed(mut self, tools_changed: Arc<AtomicBool>) -> Self {
+        self.tools_changed = tools_changed;
+        self
+    }
+
+    /// Connects to the event stream and waits for the endpoint of the messages.
+    pub async fn start(&self) -> Result<SseHttpHandle, Error> {
+        let client = build_client(&self.headers)?;
+        let response = client
+            .get(&self.url)
+            .header(ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .map_err(connection_error)?;
+        let response = check_status(response).await?;
+        let base_url =
+            url::Url::parse(&self.url).map_err(|e| Error::SseConnection(e.to_string()))?;
+
+        let pending: PendingResponses = Arc::new(Mutex::new(HashMap::new()));
+        let (endpoint_tx, endpoint_rx) = oneshot::channel();
+        let task = tokio::spawn(read_events(
+            response,
+            base_url,
+            endpoint_tx,
+            Arc::clone(&pending),
+            Arc::clone(&self.tools_changed),
+        ));
+        let endpoint = endpoint_rx.await.map_err(|_| {
+            Error::SseConnection("the stream ended before the endpoint event".to_string())
+        })?;
+        Ok(SseHttpHandle {
+            client,
+            endpoint,
+            pending,
+            _reader: Arc::new(AbortOnDrop(task)),
+        })
+    }
+}
+
+pub(super) type PendingResponses = Arc<Mutex<HashMap<u64, oneshot::Sender<JsonRpcMessage>>>>;
+
+#[derive(Clone)]
+pub struct SseHttpHandle {
+    client: reqwest::Client,
+    endpoint: String,
+    pending: PendingResponses,
+    _reader: Arc<AbortOnDrop>,
+}
+
+#[async_trait::async_trait]
+impl TransportHandle for SseHttpHandle {
+    async fn send(&self, message: JsonRpcMessage) -> Result<JsonRpcMessage, Error> {
+        let id = request_id(&message);
+        let receiver = match id {
+            Some(id) => {
+                let (sender, receiver) = oneshot::channel();
+                self.pending.lock().await.insert(id, sender);
+                Some(receiver)
+            }
+            None => None,
+        };
+        let sent = match self.client.post(&self.endpoint).json(&message).send().await {
+            Ok(response) => check_status(response).await.map(|_| ()),
+            Err(e) => Err(connection_error(e)),
+        };
+        if let Err(e) = sent {
+            if let Some(id) = id {
+                self.pending.lock().await.remove(&id);
+            }
+            return Err(e);
+        }
+        match receiver {
+            Some(receiver) => receiver.await.map_err(|_| {
+                Error::SseConnection("the stream ended before the response".to_string())
+            }),
+            None => Ok(JsonRpcMessage::Nil),
+        }
+    }
+}
+
+/// Reads the event stream, announcing the endpoint and dispatching the responses.
+async fn read_events(
+    response: reqwest::Response,
+    base_url: url::Url,
+    endpoint_tx: oneshot::Sender<String>,
+    pending: PendingResponses,
+    tools_changed: Arc<AtomicBool>,
+) {
+    let mut endpoint_tx = Some(endpoint_tx);
+    let mut parser = SseParser::default();
+    let mut stream = response.bytes_stream();
+    while let Some(Ok(chunk)) = stream.next().await {
+        for event in parser.push(&chunk) {
+            match event.event.as_deref() {
+                Some("endpoint") => {
+                    if let (Some(tx), Ok(endpoint)) =
+                        (endpoint_tx.take(), base_url.join(event.data.trim()))
+                    {
+                        let _ = tx.send(endpoint.to_string());
+                    }
+                }
+                None | Some("message") => {
+                    let Ok(message) = serde_json::from_str::<JsonRpcMessage>(&event.data) else {
+                        tracing::warn!("skipping an invalid MCP message: {}", event.data);
+                        continue;
+                    };
+                    if is_tools_changed(&message) {
+                        tools_changed.store(true, Ordering::Relaxed);
+                    } else if let Some(id) = response_id(&message) {
+                        if let Some(sender) = pending.lock().await.remove(&id) {
+                            let _ = sender.send(message);
+                        }
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    // Dropping the senders fails the requests still waiting for a response.
+    pending.lock().await.clear();
+}
+
+pub(super) struct AbortOnDrop(pub(super) JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct SseEvent {
+    event: Option<String>,
+    data: String,
+}
+
+/// An incremental parser of server-sent events.
+#[derive(Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Adds a chunk of the stream, returning the events it completes.
+    fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        // Buffered as bytes, a chunk may end in the middle of a character.
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(SseEvent {
+                        event: self.event.take(),
+                        data: self.data.join("\n"),
+                    });
+                    self.data.clear();
+                }
+                self.event = None;
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+fn build_client(headers: &HeaderMap) -> Result<reqwest::Client, Error> {
+    reqwest::Client::builder()
+        .default_headers(headers.clone())
+        .build()
+        .map_err(connection_error)
+}
+
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response, Error> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response)
+    } else {
+        Err(Error::HttpError {
+            status: status.as_u16(),
+            message: response.text().await.unwrap_or_default(),
+        })
+    }
+}
+
+fn connection_error(e: reqwest::Error) -> Error {
+    Error::SseConnection(e.to_string())
+}
+
+pub(super) fn request_id(message: &JsonRpcMessage) -> Option<u64> {
+    match message {
+        JsonRpcMessage::Request(request) => request.id,
+        _ => None,
+    }
+}
+
+pub(super) fn is_tools_changed(message: &JsonRpcMessage) -> bool {
+    matches!(message, JsonRpcMessage::Notification(notification) if notification.method == TOOLS_CHANGED_METHOD)
+}
+
+pub(super) fn response_id(message: &JsonRpcMessage) -> Option<u64> {
+    match message {
+        JsonRpcMessage::Response(response) => response.id,
+        JsonRpcMessage::Error(error) => error.id,
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_parser() {
+        let mut parser = SseParser::default();
+        assert!(parser
+            .push(b"event: endpoint\r\ndata: /messages?session")
+            .is_empty());
+        assert_eq!(
+            parser.push(b"_id=1\r\n\r\n: ping\n\ndata: {\"a\":\ndata: 1}\n\n"),
+            vec![
+                SseEvent {
+                    event: Some("endpoint".to_string()),
+                    data: "/messages?session_id=1".to_string(),
+                },
+                SseEvent {
+                    event: None,
+                    data: "{\"a\":\n1}".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use super::{MCPClient, MCPError};
+use crate::chunking::{chunk_text, ChunkError, Chunker};
+use crate::knowledge::{Knowledge, KnowledgeError};
+
+/// The text of an MCP resource as a knowledge source, read once when created.
+#[derive(Debug, Clone)]
+pub struct MCPResourceKnowledge {
+    pub uri: String,
+    content: String,
+}
+
+impl MCPResourceKnowledge {
+    /// Reads the resource from the server.
+    pub async fn read(client: &MCPClient, uri: impl ToString) -> Result<Self, MCPError> {
+        let uri = uri.to_string();
+        let content = client.read_resource_text(&uri).await?;
+        Ok(Self { uri, content })
+    }
+}
+
+impl Chunker for MCPResourceKnowledge {
+    fn chunk(&self) -> Result<Vec<String>, ChunkError> {
+        Ok(chunk_text(
+            &self.content,
+            self.chunk_size() as u32,
+            self.overlap_percent(),
+        )
+        .map_err(|err| ChunkError::Normal(err.to_string()))?
+        .unwrap_or_default())
+    }
+}
+
+impl Knowledge for MCPResourceKnowledge {
+    fn load(&self) -> Result<String, KnowledgeError> {
+        Ok(self.content.clone())
+    }
+
+    fn enrich(&self, _input: &str) -> Result<String, KnowledgeError> {
+        Ok(self.content.clone())
+    }
+}
@@ -0,0 +1,693 @@
+mod config;
+mod http;
+mod knowledge;
+mod server;
+mod stdio;
+mod supervisor;
+
+pub use config::{expand_env, MCPConfig, MCPServerConfig, MCPTransportKind, DEFAULT_MCP_TIMEOUT};
+pub use http::{header_map, SseHttpTransport, StreamableHttpTransport};
+pub use knowledge::MCPResourceKnowledge;
+pub use server::{MCPServer, DEFAULT_PROMPT_TOOL, MCP_PROTOCOL_VERSION, SEARCH_TOOL};
+pub use stdio::{StdioProcessHandle, StdioProcessTransport};
+pub use supervisor::{
+    reconnect_client, refresh_changed_tools, supervise, MCPHealth, RECONNECT_INITIAL_DELAY,
+    RECONNECT_MAX_ATTEMPTS, RECONNECT_MAX_DELAY,
+};
+
+use crate::tool::ToolDefinition;
+pub use mcp_client::client::{ClientCapabilities, ClientInfo};
+use mcp_client::client::{McpClient, McpClientTrait};
+pub use mcp_client::transport::{SseTransport, StdioTransport, Transport, TransportHandle};
+pub use mcp_client::Error;
+pub use mcp_client::McpService;
+pub use mcp_spec::prompt::Prompt;
+pub use mcp_spec::resource::Resource;
+use mcp_spec::{content::Content, prompt::PromptMessageContent, resource::ResourceContents};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// The separator between the server name and the tool name of the MCP tools exposed to the
+/// model, e.g. `github__search`.
+pub const TOOL_NAME_SEPARATOR: &str = "__";
+/// The longest tool name accepted by the OpenAI and Anthropic APIs.
+pub const MAX_TOOL_NAME_LEN: usize = 64;
+
+pub struct MCPClient {
+    /// The name of the server, prefixing its tools. The name reported by the server, or its
+    /// name in the config file.
+    pub name: String,
+    pub client: Box<dyn McpClientTrait>,
+    /// The tools of the server, by their name on the server.
+    pub tools: HashMap<String, ToolDefinition>,
+    /// The resources of the server, empty when it does not support them.
+    pub resources: Vec<Resource>,
+    /// The prompt templates of the server, empty when it does not support them.
+    pub prompts: Vec<Prompt>,
+    /// The config reconnecting to the server, with its environment variables expanded.
+    pub config: Option<MCPServerConfig>,
+    pub health: MCPHealth,
+    /// Set when the server notifies that its tools changed.
+    tools_changed: Arc<AtomicBool>,
+    /// Held while reconnecting, so concurrent reconnections of the server wait for the first.
+    reconnecting: Arc<tokio::sync::Mutex<()>>,
+    /// The number of times the client reconnected.
+    generation: u64,
+}
+
+impl MCPClient {
+    /// The prefix of the tools exposed to the model, the server name with the characters not
+    /// allowed in tool names replaced by `_`.
+    pub fn tool_prefix(&self) -> String {
+        format!("{}{TOOL_NAME_SEPARATOR}", sanitize_tool_name(&self.name))
+    }
+
+    /// The definitions of the tools exposed to the model, named `server__tool` with only the
+    /// characters `[A-Za-z0-9_-]` allowed in tool names, see [MCPClient::exposed_tool_name].
+    pub fn tool_definitions(&self) -> Vec<ToolDefinition> {
+        self.tools
+            .values()
+            .map(|tool| ToolDefinition {
+                name: self.exposed_tool_name(&tool.name),
+                ..tool.clone()
+            })
+            .collect()
+    }
+
+    /// The name exposed to the model of a tool of the server. The names longer than
+    /// [MAX_TOOL_NAME_LEN] are truncated and end with a hash of the full name instead.
+    pub fn exposed_tool_name(&self, tool: &str) -> String {
+        let name = format!("{}{}", self.tool_prefix(), sanitize_tool_name(tool));
+        if name.len() <= MAX_TOOL_NAME_LEN {
+            return name;
+        }
+        let hash = format!("{:08x}", fnv1a(name.as_bytes()));
+        // The sanitized names are ASCII.
+        format!("{}_{hash}", &name[..MAX_TOOL_NAME_LEN - hash.len() - 1])
+    }
+
+    /// The name on the server of a tool exposed to the model, if it is one of this server.
+    pub fn resolve_tool(&self, name: &str) -> Option<&str> {
+        self.tools
+            .keys()
+            .find(|server_name| self.exposed_tool_name(server_name) == name)
+            .map(String::as_str)
+    }
+
+    /// Lists the tools of the server again, keeping the ones enabled by the config.
+    pub async fn refresh_tools(&mut self) -> Result<(), MCPError> {
+        self.tools_changed.store(false, Ordering::Relaxed);
+        let result = self.client.list_tools(None).await?;
+        self.tools = tool_definitions(result.tools, self.config.as_ref());
+        Ok(())
+    }
+
+    /// Whether the server notified that its tools changed since they were last listed.
+    pub fn tools_changed(&self) -> bool {
+        self.tools_changed.load(Ordering::Relaxed)
+    }
+
+    /// Replaces the connection, tools, resources and prompts by the ones of a new connection
+    /// to the same server, keeping the name.
+    pub fn replace_connection(&mut self, client: MCPClient) {
+        let name = std::mem::take(&mut self.name);
+        let reconnecting = Arc::clone(&self.reconnecting);
+        let generation = self.generation + 1;
+        *self = MCPClient {
+            name,
+            reconnecting,
+            generation,
+            ..client
+        };
+    }
+
+    /// Calls a tool, converting every part of its result to text. A result flagged as an
+    /// error is returned as [`MCPError::ToolError`].
+    pub async fn call_tool_text(&self, name: &str, arguments: Value) -> Result<String, MCPError> {
+        let result = self.client.call_tool(name, arguments).await?;
+        let text = result
+            .content
+            .into_iter()
+            .map(content_to_text)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if result.is_error.unwrap_or(false) {
+            Err(MCPError::ToolError(text))
+        } else {
+            Ok(text)
+        }
+    }
+
+    /// Reads a resource, joining its text contents. The binary contents are skipped.
+    pub async fn read_resource_text(&self, uri: &str) -> Result<String, MCPError> {
+        let result = self.client.read_resource(uri).await?;
+        let texts: Vec<String> = result
+            .contents
+            .into_iter()
+            .filter_map(|contents| match contents {
+                ResourceContents::TextResourceContents { text, .. } => Some(text),
+                ResourceContents::BlobResourceContents { .. } => None,
+            })
+            .collect();
+        Ok(texts.join("\n\n"))
+    }
+
+    /// Reads every resource of the server as a knowledge source.
+    pub async fn resource_knowledges(&self) -> Result<Vec<MCPResourceKnowledge>, MCPError> {
+        let mut knowledges = Vec::with_capacity(self.resources.len());
+        for resource in &self.resources {
+            knowledges.push(MCPResourceKnowledge::read(self, &resource.uri).await?);
+        }
+        Ok(knowledges)
+    }
+
+    /// Gets a prompt with the given arguments, joining the text of its messages, e.g. to use
+    /// it as the preamble of an agent.
+    pub async fn prompt_text(
+        &self,
+        name: &str,
+        arguments: HashMap<String, String>,
+    ) -> Result<String, MCPError> {
+        let result = self
+            .client
+            .get_prompt(name, serde_json::to_value(arguments)?)
+            .await?;
+        let texts: Vec<String> = result
+            .messages
+            .into_iter()
+            .filter_map(|message| match message.content {
+                PromptMessageContent::Text { text } => Some(text),
+                _ => None,
+            })
+            .collect();
+        Ok(texts.join("\n\n"))
+    }
+}
+
+impl Deref for MCPClient {
+    type Target = Box<dyn McpClientTrait>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.client
+    }
+}
+
+/// Sets up the MCP clients of the enabled servers of a config file, by server name.
+pub async fn setup_mcp_clients<P: AsRef<Path>>(
+    path: P,
+) -> Result<HashMap<String, MCPClient>, MCPError> {
+    let config = MCPConfig::from_path(path).await?;
+
+    let mut mcp_clients_map = HashMap::new();
+    for (server_name, server_conf) in config.mcp_servers {
+        if server_conf.disabled {
+            continue;
+        }
+        let mut client = server_conf.connect().await?;
+        client.name = server_name.clone();
+        mcp_clients_map.insert(server_name, client);
+    }
+
+    Ok(mcp_clients_map)
+}
+
+impl MCPServerConfig {
+    /// Connects to the server, spawning it for the stdio transport.
+    pub async fn connect(&self) -> Result<MCPClient, MCPError> {
+        self.expand_env()?.connect_expanded().await
+    }
+
+    /// Connects with a config whose environment variables are already expanded.
+    pub(crate) async fn connect_expanded(&self) -> Result<MCPClient, MCPError> {
+        let config = self;
+        let timeout = config.timeout();
+        let tools_changed = Arc::new(AtomicBool::new(false));
+        let mut client = match config.transport_kind()? {
+            MCPTransportKind::Stdio => {
+                let transport = StdioProcessTransport::new(
+                    config.command.clone().unwrap_or_default(),
+                    config.args.clone(),
+                    config.env.clone(),
+                )
+                .notify_tools_changed(Arc::clone(&tools_changed));
+                let handle = transport.start().await.map_err(Error::Transport)?;
+                connect_client(handle, timeout, tools_changed).await?
+            }
+            MCPTransportKind::Sse => {
+                let transport = SseHttpTransport::new(
+                    config.url.clone().unwrap_or_default(),
+                    header_map(&config.headers)?,
+                )
+                .notify_tools_changed(Arc::clone(&tools_changed));
+                let handle = transport.start().await.map_err(Error::Transport)?;
+                connect_client(handle, timeout, tools_changed).await?
+            }
+            MCPTransportKind::StreamableHttp => {
+                let transport = StreamableHttpTransport::new(
+                    config.url.clone().unwrap_or_default(),
+                    header_map(&config.headers)?,
+                )
+                .notify_tools_changed(Arc::clone(&tools_changed));
+                let handle = transport.start().await.map_err(Error::Transport)?;
+                connect_client(handle, timeout, tools_changed).await?
+            }
+        };
+        client.tools.retain(|name, _| config.is_tool_enabled(name));
+        client.config = Some(config.clone());
+        Ok(client)
+    }
+}
+
+impl MCPError {
+    /// Whether the connection to the server is lost, e.g. when a stdio server crashed.
+    pub fn is_transport_error(&self) -> bool {
+        matches!(self, MCPError::MCPError(Error::Transport(_)))
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum MCPError {
+    #[error("Failed to read config file: {0}")]
+    ConfigReadError(#[from] std::io::Error),
+    #[error("Failed to parse config file: {0}")]
+    ConfigParseError(#[from] serde_json::Error),
+    #[error("MCP error {0}")]
+    MCPError(#[from] Error),
+    #[error("Invalid MCP server config: {0}")]
+    InvalidConfig(String),
+    #[error("Environment variable {0} referenced by the MCP config is not set")]
+    EnvVarNotFound(String),
+    #[error("MCP server error: {0}")]
+    ServerError(String),
+    #[error("MCP tool error: {0}")]
+    ToolError(String),
+    #[error("MCP tool collision: {0}")]
+    ToolCollision(String),
+    #[error("The knowledges of the agent are shared and can't be extended")]
+    SharedKnowledges,
+}
+
+/// Create a sse mcp client. The env is only passed to the stdio servers and is unused here.
+pub async fn sse_client<S: AsRef<str>>(
+    sse_url: S,
+    env: HashMap<String, String>,
+) -> Result<MCPClient, MCPError> {
+    MCPServerConfig {
+        url: Some(sse_url.as_ref().to_string()),
+        transport: Some(MCPTransportKind::Sse),
+        env,
+        ..Default::default()
+    }
+    .connect_expanded()
+    .await
+}
+
+pub async fn stdio_client<S: AsRef<str>>(
+    command: S,
+    args: Vec<S>,
+    env: HashMap<String, String>,
+) -> Result<MCPClient, MCPError> {
+    MCPServerConfig {
+        command: Some(command.as_ref().to_string()),
+        args: args.iter().map(|s| s.as_ref().to_string()).collect(),
+        env,
+        ..Default::default()
+    }
+    .connect_expanded()
+    .await
+}
+
+/// Initializes the client of a started transport and lists the tools, resources and prompts
+/// of the server.
+async fn connect_client<T: TransportHandle>(
+    handle: T,
+    timeout: Duration,
+    tools_changed: Arc<AtomicBool>,
+) -> Result<MCPClient, MCPError> {
+    // Create the service with timeout middleware
+    let service = McpService::with_timeout(handle, timeout);
+    // Create client
+    let mut client = McpClient::new(service);
+    // Initialize
+    let initialize_result = client
+        .initialize(
+            ClientInfo {
+                name: "alith-client".into(),
+                version: "1.0.0".into(),
+            },
+            ClientCapabilities::default(),
+        )
+        .await?;
+    let mut resources = Vec::new();
+    if initialize_result.capabilities.resources.is_some() {
+        let mut cursor = None;
+        loop {
+            let result = client.list_resources(cursor).await?;
+            resources.extend(result.resources);
+            cursor = result.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+    }
+    let mut prompts = Vec::new();
+    if initialize_result.capabilities.prompts.is_some() {
+        let mut cursor = None;
+        loop {
+            let result = client.list_prompts(cursor).await?;
+            prompts.extend(result.prompts);
+            cursor = result.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+    }
+    let tools = tool_definitions(client.list_tools(None).await?.tools, None);
+    Ok(MCPClient {
+        name: initialize_result.server_info.name,
+        client: Box::new(client),
+        tools,
+        resources,
+        prompts,
+        config: None,
+        health: MCPHealth::Healthy,
+        tools_changed,
+        reconnecting: Arc::default(),
+        generation: 0,
+    })
+}
+
+/// The definitions of the tools by name, the ones enabled by the config if any.
+fn tool_definitions(
+    tools: Vec<mcp_spec::tool::Tool>,
+    config: Option<&MCPServerConfig>,
+) -> HashMap<String, ToolDefinition> {
+    tools
+        .into_iter()
+        .filter(|tool| config.is_none_or(|config| config.is_tool_enabled(&tool.name)))
+        .map(|tool| {
+            (
+                tool.name.clone(),
+                ToolDefinition {
+                    name: tool.name,
+                    description: tool.description,
+                    parameters: tool.input_schema,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Replaces the characters not allowed in the tool names of the model APIs by `_`.
+fn sanitize_tool_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// The 32 bits FNV-1a hash, stable across builds unlike the hasher of the standard library.
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x01000193)
+    })
+}
+
+/// Converts a part of a tool result to text. The binary data is only summarized by its MIME
+/// type and size, its base64 would fill the context of the model.
+fn content_to_text(content: Content) -> String {
+    match content {
+        Content::Text(text) => text.text,
+        Content::Image(image) => format!(
+            "[image: {}, {} bytes]",
+            image.mime_type,
+            base64_decoded_len(&image.data)
+        ),
+        Content::Resource(resource) => match resource.resource {
+            ResourceContents::TextResourceContents { uri, text, .. } => {
+                format!("[{uri}]\n{text}")
+            }
+            ResourceContents::BlobResourceContents {
+                uri,
+                mime_type,
+                blob,
+            } => format!(
+                "[{uri}: {}, {} bytes]",
+                mime_type.as_deref().unwrap_or("application/octet-stream"),
+                base64_decoded_len(&blob)
+            ),
+        },
+    }
+}
+
+/// The size of the data encoded in base64, without decoding it.
+fn base64_decoded_len(data: &str) -> usize {
+    let data = data.trim_end();
+    let padding = data.bytes().rev().take_while(|b| *b == b'=').count();
+    (data.len() * 3 / 4).saturating_sub(padding)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::knowledge::Knowledge;
+    use mcp_client::transport::Error as TransportError;
+    use mcp_spec::protocol::JsonRpcMessage;
+    use serde_json::json;
+
+    type Responder = dyn Fn(&str, &Value) -> Result<Value, TransportError> + Send + Sync;
+
+    /// A transport answering every request with the result of a function of its method and
+    /// parameters.
+    #[derive(Clone)]
+    pub(crate) struct ScriptedHandle(Arc<Responder>);
+
+    impl ScriptedHandle {
+        pub(crate) fn new(
+            responder: impl Fn(&str, &Value) -> Result<Value, TransportError> + Send + Sync + 'static,
+        ) -> Self {
+            Self(Arc::new(responder))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TransportHandle for ScriptedHandle {
+        async fn send(&self, message: JsonRpcMessage) -> Result<JsonRpcMessage, TransportError> {
+            let JsonRpcMessage::Request(request) = message else {
+                return Ok(JsonRpcMessage::Nil);
+            };
+            let params = request.params.unwrap_or_default();
+            let result = (self.0)(&request.method, &params)?;
+            Ok(serde_json::from_value(
+                json!({"jsonrpc": "2.0", "id": request.id, "result": result}),
+            )?)
+        }
+    }
+
+    /// The result of `initialize` for a server with tools, resources and prompts.
+    pub(crate) fn initialize_result(name: &str) -> Value {
+        json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {"tools": {}, "resources": {}, "prompts": {}},
+            "serverInfo": {"name": name, "version": "1.0.0"}
+        })
+    }
+
+    /// A server with a `search` tool, two pages of resources and two pages of prompts.
+    pub(crate) fn docs_server(method: &str, params: &Value) -> Result<Value, TransportError> {
+        let second_page = params.get("cursor").is_some_and(|cursor| !cursor.is_null());
+        Ok(match method {
+            "initialize" => initialize_result("docs"),
+            "tools/list" => json!({"tools": [{
+                "name": "search",
+                "description": "Search the docs",
+                "inputSchema": {"type": "object"}
+            }]}),
+            "resources/list" if second_page => json!({"resources": [
+                {"uri": "docs://faq", "name": "FAQ", "mimeType": "text/plain"}
+            ]}),
+            "resources/list" => json!({
+                "resources": [
+                    {"uri": "docs://readme", "name": "README", "mimeType": "text/plain"}
+                ],
+                "nextCursor": "2"
+            }),
+            "resources/read" => json!({"contents": [{
+                "uri": params["uri"],
+                "mimeType": "text/plain",
+                "text": format!("The content of {}", params["uri"].as_str().unwrap_or_default())
+            }]}),
+            "prompts/list" if second_page => json!({"prompts": [{"name": "reviewer"}]}),
+            "prompts/list" => json!({"prompts": [{"name": "writer"}], "nextCursor": "2"}),
+            "prompts/get" => json!({"messages": [
+                {"role": "user", "content": {
+                    "type": "text",
+                    "text": format!("You write about {}.", params["arguments"]["topic"].as_str().unwrap_or_default())
+                }},
+                {"role": "user", "content": {"type": "text", "text": "Be brief."}}
+            ]}),
+            _ => json!({}),
+        })
+    }
+
+    /// Serves a scripted server on the streamable HTTP transport at `/mcp`.
+    pub(crate) async fn http_server(
+        server: &mut mockito::ServerGuard,
+        responder: impl Fn(&str, &Value) -> Result<Value, TransportError> + Send + Sync + 'static,
+    ) -> mockito::Mock {
+        server
+            .mock("POST", "/mcp")
+            .with_header("content-type", "application/json")
+            .with_body_from_request(move |request| {
+                let message: Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+                let method = message["method"].as_str().unwrap_or_default();
+                let result = responder(method, &message["params"]).unwrap();
+                json!({"jsonrpc": "2.0", "id": message["id"], "result": result})
+                    .to_string()
+                    .into_bytes()
+            })
+            .create_async()
+            .await
+    }
+
+    /// Connects a client to a scripted server.
+    pub(crate) async fn scripted_client(handle: ScriptedHandle) -> MCPClient {
+        connect_client(
+            handle,
+            Duration::from_secs(5),
+            Arc::new(AtomicBool::new(false)),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_connect_lists_every_page() {
+        let client = scripted_client(ScriptedHandle::new(docs_server)).await;
+        assert_eq!(client.name, "docs");
+        assert!(client.tools.contains_key("search"));
+        let uris: Vec<&str> = client.resources.iter().map(|r| r.uri.as_str()).collect();
+        assert_eq!(uris, ["docs://readme", "docs://faq"]);
+        let prompts: Vec<&str> = client.prompts.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(prompts, ["writer", "reviewer"]);
+    }
+
+    #[tokio::test]
+    async fn test_resource_knowledges() {
+        let client = scripted_client(ScriptedHandle::new(docs_server)).await;
+        let knowledges = client.resource_knowledges().await.unwrap();
+        assert_eq!(knowledges.len(), 2);
+        assert_eq!(knowledges[0].uri, "docs://readme");
+        assert_eq!(knowledges[1].load().unwrap(), "The content of docs://faq");
+    }
+
+    #[tokio::test]
+    async fn test_prompt_text() {
+        let client = scripted_client(ScriptedHandle::new(docs_server)).await;
+        let text = client
+            .prompt_text(
+                "writer",
+                HashMap::from([("topic".to_string(), "Rust".to_string())]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(text, "You write about Rust.\n\nBe brief.");
+    }
+
+    #[tokio::test]
+    async fn test_tool_names() {
+        let client = scripted_client(ScriptedHandle::new(|method, _| {
+            Ok(match method {
+                "initialize" => initialize_result("my docs"),
+                "tools/list" => json!({"tools": [
+                    {"name": "get.weather", "description": "The weather", "inputSchema": {}},
+                    {"name": "search", "description": "Search", "inputSchema": {}}
+                ]}),
+                _ => json!({}),
+            })
+        }))
+        .await;
+        let mut names: Vec<String> = client
+            .tool_definitions()
+            .into_iter()
+            .map(|definition| definition.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["my_docs__get_weather", "my_docs__search"]);
+        assert_eq!(
+            client.resolve_tool("my_docs__get_weather"),
+            Some("get.weather")
+        );
+        assert_eq!(client.resolve_tool("my_docs__search"), Some("search"));
+        assert_eq!(client.resolve_tool("my_docs__missing"), None);
+        assert_eq!(client.resolve_tool("other__search"), None);
+    }
+
+    #[tokio::test]
+    async fn test_long_tool_names() {
+        let long = |suffix: &str| format!("{}_{suffix}", "a".repeat(60));
+        let (first, second) = (long("first"), long("second"));
+        let tools = json!({"tools": [
+            {"name": first, "description": "First", "inputSchema": {}},
+            {"name": second, "description": "Second", "inputSchema": {}}
+        ]});
+        let client = scripted_client(ScriptedHandle::new(move |method, _| {
+            Ok(match method {
+                "initialize" => initialize_result("docs"),
+                "tools/list" => tools.clone(),
+                _ => json!({}),
+            })
+        }))
+        .await;
+        let names: Vec<String> = client
+            .tool_definitions()
+            .into_iter()
+            .map(|definition| definition.name)
+            .collect();
+        assert!(names.iter().all(|name| name.len() == MAX_TOOL_NAME_LEN));
+        assert!(names.iter().all(|name| name.starts_with("docs__aaaa")));
+        assert_ne!(names[0], names[1]);
+        assert_eq!(
+            client.resolve_tool(&client.exposed_tool_name(&first)),
+            Some(first.as_str())
+        );
+        assert_eq!(
+            client.resolve_tool(&client.exposed_tool_name(&second)),
+            Some(second.as_str())
+        );
+        assert_eq!(client.exposed_tool_name("search"), "docs__search");
+    }
+
+    #[test]
+    fn test_content_to_text() {
+        let text = |content: Value| content_to_text(serde_json::from_value(content).unwrap());
+        assert_eq!(text(json!({"type": "text", "text": "Sunny"})), "Sunny");
+        assert_eq!(
+            text(json!({"type": "image", "data": "aGVsbG8=", "mimeType": "image/png"})),
+            "[image: image/png, 5 bytes]"
+        );
+        assert_eq!(
+            text(json!({"type": "resource", "resource": {
+                "uri": "file:///notes.txt", "mimeType": "text/plain", "text": "Notes"
+            }})),
+            "[file:///notes.txt]\nNotes"
+        );
+        assert_eq!(
+            text(json!({"type": "resource", "resource": {
+                "uri": "file:///report.pdf", "mimeType": "application/pdf", "blob": "aGVsbG8gd29ybGQ="
+            }})),
+            "[file:///report.pdf: application/pdf, 11 bytes]"
+        );
+    }
+}
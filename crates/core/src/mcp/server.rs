@@ -0,0 +1,598 @@
+//! An MCP server publishing an agent to the other MCP hosts: the tools of the agent, a tool
+//! prompting the agent and, optionally, a tool searching its stores.
+
+use super::MCPError;
+use crate::agent::Agent;
+use crate::chat::Completion;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
+    Json, Router,
+};
+use futures::{stream::FuturesUnordered, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::IntoFuture;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use uuid::Uuid;
+
+/// The MCP protocol version of the server.
+pub const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+/// The responses queued for an SSE session before its client is disconnected.
+const SESSION_EVENTS: usize = 64;
+/// The default name of the tool prompting the agent.
+pub const DEFAULT_PROMPT_TOOL: &str = "prompt";
+/// The name of the tool searching the stores of the agent.
+pub const SEARCH_TOOL: &str = "search_documents";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn invalid_params(message: impl ToString) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Serves an agent over MCP.
+///
+/// ```no_run
+/// # async fn serve(agent: alith_core::agent::Agent<alith_core::llm::LLM>) -> Result<(), alith_core::mcp::MCPError> {
+/// use alith_core::mcp::MCPServer;
+///
+/// MCPServer::new(agent).search_tool(true).serve_stdio().await
+/// # }
+/// ```
+pub struct MCPServer<M: Completion> {
+    agent: Agent<M>,
+    name: String,
+    version: String,
+    prompt_tool: Option<String>,
+    search_tool: bool,
+}
+
+impl<M: Completion> MCPServer<M> {
+    /// A server named after the agent, publishing its tools and the `prompt` tool.
+    pub fn new(agent: Agent<M>) -> Self {
+        Self {
+            name: agent.name.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            prompt_tool: Some(DEFAULT_PROMPT_TOOL.to_string()),
+            search_tool: false,
+            agent,
+        }
+    }
+
+    pub fn name(mut self, name: impl ToString) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn version(mut self, version: impl ToString) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
+    /// Renames the tool prompting the agent.
+    pub fn prompt_tool(mut self, name: impl ToString) -> Self {
+        self.prompt_tool = Some(name.to_string());
+        self
+    }
+
+    /// Only publishes the tools of the agent, not the one prompting it.
+    pub fn without_prompt_tool(mut self) -> Self {
+        self.prompt_tool = None;
+        self
+    }
+
+    /// Publishes the [`SEARCH_TOOL`] searching the stores of the agent.
+    pub fn search_tool(mut self, search_tool: bool) -> Self {
+        self.search_tool = search_tool;
+        self
+    }
+
+    /// Serves the requests read from stdin, one JSON-RPC message per line, until stdin is
+    /// closed. The requests are handled concurrently.
+    pub async fn serve_stdio(&self) -> Result<(), MCPError> {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        let mut stdout = tokio::io::stdout();
+        let mut pending = FuturesUnordered::new();
+        loop {
+            tokio::select! {
+                line = lines.next_line() => {
+                    let Some(line) = line.map_err(server_error)? else {
+                        break;
+                    };
+                    if !line.trim().is_empty() {
+                        pending.push(self.handle_line(line));
+                    }
+                }
+                Some(response) = pending.next(), if !pending.is_empty() => {
+                    if let Some(response) = response {
+                        write_line(&mut stdout, &response).await?;
+                    }
+                }
+            }
+        }
+        // Answers the requests still running once stdin is closed.
+        while let Some(response) = pending.next().await {
+            if let Some(response) = response {
+                write_line(&mut stdout, &response).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serves the SSE transport: the clients connect to `/sse` and post their messages to the
+    /// endpoint it announces.
+    pub async fn serve_sse(&self, addr: SocketAddr) -> Result<(), MCPError> {
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(server_error)?;
+        let (messages, mut message_rx) = mpsc::channel(64);
+        let state = SseState {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            messages,
+        };
+        let app = Router::new()
+            .route("/sse", get(sse_handler))
+            .route("/messages", post(message_handler))
+            .with_state(state.clone());
+        let server = axum::serve(listener, app).into_future();
+        tokio::pin!(server);
+
+        // The agent is not shared with the HTTP tasks: the messages are handled here.
+        let mut pending = FuturesUnordered::new();
+        loop {
+            tokio::select! {
+                result = &mut server => return result.map_err(server_error),
+                Some((session_id, message)) = message_rx.recv() => {
+                    pending.push(async move { (session_id, self.handle_message(message).await) });
+                }
+                Some((session_id, response)) = pending.next(), if !pending.is_empty() => {
+                    if let Some(response) = response {
+                        state.send(&session_id, &response);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn handle_line(&self, line: String) -> Option<Value> {
+        match serde_json::from_str(&line) {
+            Ok(message) => self.handle_message(message).await,
+            Err(e) => Some(error_response(
+                Value::Null,
+                RpcError {
+                    code: PARSE_ERROR,
+                    message: e.to_string(),
+                },
+            )),
+        }
+    }
+
+    /// Handles a JSON-RPC message, returning the response of the requests. The notifications
+    /// and the responses are not answered.
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let is_response = message.get("result").is_some() || message.get("error").is_some();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            let error = RpcError {
+                code: INVALID_REQUEST,
+                message: "missing method".to_string(),
+            };
+            return id
+                .filter(|_| !is_response)
+                .map(|id| error_response(id, error));
+        };
+        let id = id?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        Some(match self.handle_request(method, params).await {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => error_response(id, e),
+        })
+    }
+
+    async fn handle_request(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": MCP_PROTOCOL_VERSION,
+                "capabilities": { "tools": { "listChanged": false } },
+                "serverInfo": { "name": self.name, "version": self.version },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": self.list_tools().await })),
+            "tools/call" => self.call_tool(params).await,
+            _ => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("method {method} not found"),
+            }),
+        }
+    }
+
+    async fn list_tools(&self) -> Vec<Value> {
+        let mut tools: Vec<Value> = self
+            .agent
+            .tools
+            .read()
+            .await
+            .iter()
+            .map(|tool| {
+                let definition = tool.definition();
+                json!({
+                    "name": definition.name,
+                    "description": definition.description,
+                    "inputSchema": definition.parameters,
+                })
+            })
+            .collect();
+        if let Some(name) = &self.prompt_tool {
+            tools.push(json!({
+                "name": name,
+                "description": format!("Sends a prompt to the {} agent and returns its answer.", self.agent.name),
+                "inputSchema": {
+                    "type": "object",
+                    "properties": { "prompt": { "type": "string" } },
+                    "required": ["prompt"],
+                },
+            }));
+        }
+        if self.search_tool {
+            tools.push(json!({
+                "name": SEARCH_TOOL,
+                "description": "Searches the documents of the agent stores.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": { "query": { "type": "string" } },
+                    "required": ["query"],
+                },
+            }));
+        }
+        tools
+    }
+
+    async fn call_tool(&self, params: Value) -> Result<Value, RpcError> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::invalid_params("missing tool name"))?;
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+        let string_argument = |key: &str| {
+            arguments
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| RpcError::invalid_params(format!("missing argument {key}")))
+        };
+
+        let result = if self.prompt_tool.as_deref() == Some(name) {
+            let prompt = string_argument("prompt")?;
+            self.agent.prompt(&prompt).await.map_err(|e| e.to_string())
+        } else if self.search_tool && name == SEARCH_TOOL {
+            let query = string_argument("query")?;
+            self.search(&query).await
+        } else {
+            let tools = self.agent.tools.read().await;
+            let tool = tools
+                .iter()
+                .find(|tool| tool.name().eq_ignore_ascii_case(name))
+                .ok_or_else(|| RpcError::invalid_params(format!("unknown tool {name}")))?;
+            tool.run(&arguments.to_string())
+                .await
+                .map_err(|e| e.to_string())
+        };
+        let (text, is_error) = match result {
+            Ok(text) => (text, false),
+            Err(text) => (text, true),
+        };
+        Ok(json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": is_error,
+        }))
+    }
+
+    /// Searches every store, returning the documents as a JSON array.
+    async fn search(&self, query: &str) -> Result<String, String> {
+        let mut documents = Vec::new();
+        for (sample, store) in &self.agent.store_indices {
+            let results = store
+                .search(query, *sample, 0.5)
+                .await
+                .map_err(|e| e.to_string())?;
+            documents.extend(results.into_iter().map(|(_, document, _)| document));
+        }
+        serde_json::to_string(&documents).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Clone)]
+struct SseState {
+    /// The event streams of the connected clients, by session id.
+    sessions: Arc<Mutex<HashMap<String, mpsc::Sender<Event>>>>,
+    /// The messages posted by the clients, with their session id.
+    messages: mpsc::Sender<(String, Value)>,
+}
+
+impl SseState {
+    /// The sessions, only locked while they are read or updated, never across an await.
+    fn sessions(&self) -> MutexGuard<'_, HashMap<String, mpsc::Sender<Event>>> {
+        self.sessions.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Queues a response on the event stream of a session without waiting, so that a slow
+    /// client cannot hold the other sessions back. A client falling more than
+    /// [SESSION_EVENTS] responses behind is disconnected.
+    fn send(&self, session_id: &str, response: &Value) {
+        let mut sessions = self.sessions();
+        let Some(sender) = sessions.get(session_id) else {
+            return;
+        };
+        let event = Event::default().event("message").data(response.to_string());
+        match sender.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                tracing::warn!("closing the MCP session {session_id}, too many pending responses");
+                sessions.remove(session_id);
+            }
+            // The client is gone.
+            Err(TrySendError::Closed(_)) => {
+                sessions.remove(session_id);
+            }
+        }
+    }
+}
+
+/// Removes a session once its event stream is dropped, when the client disconnects.
+struct SessionGuard {
+    state: SseState,
+    session_id: String,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        self.state.sessions().remove(&self.session_id);
+    }
+}
+
+async fn sse_handler(
+    State(state): State<SseState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    Sse::new(session_events(state).map(Ok::<_, Infallible>)).keep_alive(KeepAlive::default())
+}
+
+/// Opens a session: the endpoint the messages are posted to, then the responses. The session
+/// is removed once the stream is dropped.
+fn session_events(state: SseState) -> impl Stream<Item = Event> {
+    let session_id = Uuid::new_v4().to_string();
+    let (sender, receiver) = mpsc::channel(SESSION_EVENTS);
+    let endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("/messages?session_id={session_id}"));
+    state.sessions().insert(session_id.clone(), sender);
+    let guard = SessionGuard { state, session_id };
+    futures::stream::once(async { endpoint }).chain(futures::stream::unfold(
+        (receiver, guard),
+        |(mut receiver, guard)| async move {
+            receiver
+                .recv()
+                .await
+                .map(|event| (event, (receiver, guard)))
+        },
+    ))
+}
+
+#[derive(Deserialize)]
+struct SessionQuery {
+    session_id: String,
+}
+
+async fn message_handler(
+    State(state): State<SseState>,
+    Query(query): Query<SessionQuery>,
+    Json(message): Json<Value>,
+) -> StatusCode {
+    if !state.sessions().contains_key(&query.session_id) {
+        return StatusCode::NOT_FOUND;
+    }
+    match state.messages.send((query.session_id, message)).await {
+        Ok(()) => StatusCode::ACCEPTED,
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+async fn write_line(stdout: &mut tokio::io::Stdout, message: &Value) -> Result<(), MCPError> {
+    let mut line = message.to_string();
+    line.push('\n');
+    stdout
+        .write_all(line.as_bytes())
+        .await
+        .map_err(server_error)?;
+    stdout.flush().await.map_err(server_error)
+}
+
+fn server_error(e: std::io::Error) -> MCPError {
+    MCPError::ServerError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::client::{Client, LLMClient};
+    use crate::tool::tests::DummyTool;
+    use crate::tool::Tool;
+
+    fn server(host: String) -> MCPServer<Client> {
+        let mut builder = LLMClient::openai();
+        builder.config.api_config.api_key = Some("test-key".to_string().into());
+        builder.config.api_config.host = host;
+        builder.config.logging_config.logging_enabled = false;
+        let client = Client {
+            client: builder.init().unwrap(),
+        };
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(DummyTool)];
+        MCPServer::new(Agent::new_with_tools("assistant", client, tools))
+    }
+
+    fn request(method: &str, params: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params})
+    }
+
+    #[tokio::test]
+    async fn test_initialize_and_list_tools() {
+        let server = server("http://localhost".to_string());
+        let response = server
+            .handle_message(request("initialize", json!({})))
+            .await
+            .unwrap();
+        assert_eq!(
+            response["result"]["protocolVersion"],
+            json!(MCP_PROTOCOL_VERSION)
+        );
+        assert_eq!(response["result"]["serverInfo"]["name"], json!("assistant"));
+
+        let response = server
+            .handle_message(request("tools/list", json!({})))
+            .await
+            .unwrap();
+        let names: Vec<&str> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|tool| tool["name"].as_str())
+            .collect();
+        assert_eq!(names, ["dummy", DEFAULT_PROMPT_TOOL]);
+
+        // Notifications are not answered
+        assert!(server
+            .handle_message(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_call_tools() {
+        let mut llm = mockito::Server::new_async().await;
+        let mock = llm
+            .mock("POST", "/chat/completions")
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "id": "chatcmpl-1",
+                    "created": 0,
+                    "model": "gpt-4",
+                    "choices": [{
+                        "index": 0,
+                        "message": {"role": "assistant", "content": "Hello from the agent"},
+                        "finish_reason": "stop"
+                    }],
+                    "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        let server = server(llm.url());
+
+        let response = server
+            .handle_message(request(
+                "tools/call",
+                json!({"name": "dummy", "arguments": {"x": 1, "y": 2}}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            response["result"],
+            json!({"content": [{"type": "text", "text": "\"x: 1, y: 2\""}], "isError": false})
+        );
+
+        let response = server
+            .handle_message(request(
+                "tools/call",
+                json!({"name": DEFAULT_PROMPT_TOOL, "arguments": {"prompt": "Hello"}}),
+            ))
+            .await
+            .unwrap();
+        mock.assert_async().await;
+        assert_eq!(
+            response["result"]["content"][0]["text"],
+            json!("Hello from the agent")
+        );
+        assert_eq!(response["result"]["isError"], json!(false));
+
+        let response = server
+            .handle_message(request("tools/call", json!({"name": "missing"})))
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], json!(INVALID_PARAMS));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method() {
+        let server = server("http://localhost".to_string());
+        let response = server
+            .handle_message(request("resources/list", json!({})))
+            .await
+            .unwrap();
+        assert_eq!(response["id"], json!(1));
+        assert_eq!(response["error"]["code"], json!(METHOD_NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_session_removed_when_stream_dropped() {
+        let (messages, _message_rx) = mpsc::channel(1);
+        let state = SseState {
+            sessions: Arc::default(),
+            messages,
+        };
+        let mut events = Box::pin(session_events(state.clone()));
+        assert!(events.next().await.is_some());
+        assert_eq!(state.sessions().len(), 1);
+        drop(events);
+        assert!(state.sessions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_slow_session_closed() {
+        let (messages, _message_rx) = mpsc::channel(1);
+        let state = SseState {
+            sessions: Arc::default(),
+            messages,
+        };
+        let mut events = Box::pin(session_events(state.clone()));
+        assert!(events.next().await.is_some());
+        let session_id = state.sessions().keys().next().unwrap().clone();
+        for _ in 0..SESSION_EVENTS {
+            state.send(&session_id, &json!({}));
+        }
+        assert_eq!(state.sessions().len(), 1);
+        // The client is not reading its events: the session is closed instead of waiting.
+        state.send(&session_id, &json!({}));
+        assert!(state.sessions().is_empty());
+        assert_eq!(events.count().await, SESSION_EVENTS);
+    }
+}
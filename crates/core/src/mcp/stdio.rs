@@ -0,0 +1,181 @@
+//! The transport of the stdio servers: a child process exchanging one JSON-RPC message per
+//! line on its stdin and stdout.
+
+use super::http::{is_tools_changed, request_id, response_id, AbortOnDrop, PendingResponses};
+use mcp_client::transport::{Error, TransportHandle};
+use mcp_spec::protocol::JsonRpcMessage;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::{oneshot, Mutex};
+
+/// A server spawned as a child process, killed once every handle is dropped.
+pub struct StdioProcessTransport {
+    command: String,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    tools_changed: Arc<AtomicBool>,
+}
+
+impl StdioProcessTransport {
+    pub fn new<S: Into<String>>(
+        command: S,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            command: command.into(),
+            args,
+            env,
+            tools_changed: Arc::default(),
+        }
+    }
+
+    /// Sets the flag when the server notifies that its tools changed.
+    pub fn notify_tools_changed(mut self, tools_changed: Arc<AtomicBool>) -> Self {
+        self.tools_changed = tools_changed;
+        self
+    }
+
+    /// Spawns the server and starts reading its messages.
+    pub async fn start(&self) -> Result<StdioProcessHandle, Error> {
+        let mut child = Command::new(&self.command)
+            .args(&self.args)
+            .envs(&self.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let (Some(stdin), Some(stdout), Some(stderr)) =
+            (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            return Err(Error::StdioProcessError(
+                "the pipes of the server are not available".to_string(),
+            ));
+        };
+        // The logs of the server, until it exits.
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                tracing::debug!("MCP server: {line}");
+            }
+        });
+
+        let pending: PendingResponses = Arc::new(Mutex::new(HashMap::new()));
+        let task = tokio::spawn(read_messages(
+            stdout,
+            Arc::clone(&pending),
+            Arc::clone(&self.tools_changed),
+        ));
+        Ok(StdioProcessHandle {
+            stdin: Arc::new(Mutex::new(stdin)),
+            pending,
+            _reader: Arc::new(AbortOnDrop(task)),
+            _child: Arc::new(child),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct StdioProcessHandle {
+    stdin: Arc<Mutex<ChildStdin>>,
+    pending: PendingResponses,
+    _reader: Arc<AbortOnDrop>,
+    _child: Arc<Child>,
+}
+
+#[async_trait::async_trait]
+impl TransportHandle for StdioProcessHandle {
+    async fn send(&self, message: JsonRpcMessage) -> Result<JsonRpcMessage, Error> {
+        let id = request_id(&message);
+        let receiver = match id {
+            Some(id) => {
+                let (sender, receiver) = oneshot::channel();
+                self.pending.lock().await.insert(id, sender);
+                Some(receiver)
+            }
+            None => None,
+        };
+        let mut line = serde_json::to_string(&message)?;
+        line.push('\n');
+        let sent = {
+            let mut stdin = self.stdin.lock().await;
+            match stdin.write_all(line.as_bytes()).await {
+                Ok(()) => stdin.flush().await,
+                Err(e) => Err(e),
+            }
+        };
+        if let Err(e) = sent {
+            if let Some(id) = id {
+                self.pending.lock().await.remove(&id);
+            }
+            return Err(e.into());
+        }
+        match receiver {
+            Some(receiver) => receiver.await.map_err(|_| {
+                Error::StdioProcessError("the server exited before the response".to_string())
+            }),
+            None => Ok(JsonRpcMessage::Nil),
+        }
+    }
+}
+
+/// Reads the messages of the server, dispatching the responses.
+async fn read_messages(
+    stdout: ChildStdout,
+    pending: PendingResponses,
+    tools_changed: Arc<AtomicBool>,
+) {
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(message) = serde_json::from_str::<JsonRpcMessage>(&line) else {
+            tracing::warn!("skipping an invalid MCP message: {line}");
+            continue;
+        };
+        if is_tools_changed(&message) {
+            tools_changed.store(true, Ordering::Relaxed);
+        } else if let Some(id) = response_id(&message) {
+            if let Some(sender) = pending.lock().await.remove(&id) {
+                let _ = sender.send(message);
+            }
+        }
+    }
+    // Dropping the senders fails the requests still waiting for a response.
+    pending.lock().await.clear();
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_stdio_notifications() {
+        let tools_changed = Arc::new(AtomicBool::new(false));
+        let script = r#"read line
+echo '{"jsonrpc": "2.0", "method": "notifications/tools/list_changed"}'
+echo '{"jsonrpc": "2.0", "id": 7, "result": {}}'
+read line"#;
+        let handle = StdioProcessTransport::new(
+            "sh",
+            vec!["-c".to_string(), script.to_string()],
+            HashMap::new(),
+        )
+        .notify_tools_changed(Arc::clone(&tools_changed))
+        .start()
+        .await
+        .unwrap();
+        let request =
+            serde_json::from_value(json!({"jsonrpc": "2.0", "id": 7, "method": "ping"})).unwrap();
+        let response = handle.send(request).await.unwrap();
+        assert_eq!(response_id(&response), Some(7));
+        assert!(tools_changed.load(Ordering::Relaxed));
+    }
+}
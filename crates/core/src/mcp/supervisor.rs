@@ -0,0 +1,258 @@
+//! The supervision of the MCP clients: the servers whose connection is lost are reconnected,
+//! respawned for the stdio ones, with an exponential backoff.
+
+use super::{tool_definitions, MCPClient, MCPError, MCPServerConfig};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// The wait before the first reconnection attempt.
+pub const RECONNECT_INITIAL_DELAY: Duration = Duration::from_millis(500);
+/// The maximum wait between two reconnection attempts.
+pub const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+/// The number of reconnection attempts before a server is considered down.
+pub const RECONNECT_MAX_ATTEMPTS: u32 = 5;
+
+/// The health of an MCP server.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum MCPHealth {
+    #[default]
+    Healthy,
+    /// The connection was lost and the client is reconnecting.
+    Reconnecting,
+    /// The server could not be reconnected, with the last error.
+    Down(String),
+}
+
+impl MCPServerConfig {
+    /// Connects to the server, retrying with an exponential backoff.
+    pub async fn connect_with_backoff(&self) -> Result<MCPClient, MCPError> {
+        let mut delay = RECONNECT_INITIAL_DELAY;
+        let mut attempt = 1;
+        loop {
+            match self.connect_expanded().await {
+                Ok(client) => return Ok(client),
+                Err(e) if attempt >= RECONNECT_MAX_ATTEMPTS => return Err(e),
+                Err(e) => {
+                    tracing::warn!("failed to connect to the MCP server, attempt {attempt}: {e}");
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Reconnects the client at the given index, replacing its connection. The lock is only held
+/// to read the config and to swap the connection, not while reconnecting.
+///
+/// A reconnection requested while another one of the same server is running waits for it
+/// and does not connect again.
+pub async fn reconnect_client(
+    clients: &RwLock<Vec<MCPClient>>,
+    index: usize,
+) -> Result<(), MCPError> {
+    let (reconnecting, generation) = {
+        let clients = clients.read().await;
+        let client = &clients[index];
+        (Arc::clone(&client.reconnecting), client.generation)
+    };
+    let _reconnecting = reconnecting.lock().await;
+    let config = {
+        let mut clients = clients.write().await;
+        let client = &mut clients[index];
+        if client.generation != generation {
+            // Reconnected while waiting
+            return Ok(());
+        }
+        let config = client.config.clone().ok_or_else(|| {
+            MCPError::InvalidConfig(format!(
+                "the MCP server {} can't be reconnected without its config",
+                client.name
+            ))
+        })?;
+        client.health = MCPHealth::Reconnecting;
+        config
+    };
+    let result = config.connect_with_backoff().await;
+
+    let mut clients = clients.write().await;
+    let client = &mut clients[index];
+    match result {
+        Ok(connection) => {
+            tracing::info!("reconnected to the MCP server {}", client.name);
+            client.replace_connection(connection);
+            Ok(())
+        }
+        Err(e) => {
+            client.health = MCPHealth::Down(e.to_string());
+            Err(e)
+        }
+    }
+}
+
+/// Lists the tools again of the servers that notified a change.
+pub async fn refresh_changed_tools(clients: &RwLock<Vec<MCPClient>>) -> Result<(), MCPError> {
+    if !clients.read().await.iter().any(MCPClient::tools_changed) {
+        return Ok(());
+    }
+    let mut clients = clients.write().await;
+    for client in clients.iter_mut().filter(|client| client.tools_changed()) {
+        client.refresh_tools().await?;
+    }
+    Ok(())
+}
+
+/// Checks the servers at every interval until the clients are dropped: the tools are listed
+/// again, and the servers not answering are reconnected.
+pub async fn supervise(clients: Weak<RwLock<Vec<MCPClient>>>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    // The first tick completes immediately, right after the clients connected.
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let Some(clients) = clients.upgrade() else {
+            return;
+        };
+        let len = clients.read().await.len();
+        for index in 0..len {
+            let tools = {
+                let clients = clients.read().await;
+                clients[index].client.list_tools(None).await
+            };
+            match tools {
+                Ok(result) => {
+                    let mut clients = clients.write().await;
+                    let client = &mut clients[index];
+                    client.tools = tool_definitions(result.tools, client.config.as_ref());
+                    client.tools_changed.store(false, Ordering::Relaxed);
+                    client.health = MCPHealth::Healthy;
+                }
+                Err(e) => {
+                    tracing::warn!("the MCP server at index {index} is not answering: {e}");
+                    if let Err(e) = reconnect_client(&clients, index).await {
+                        tracing::warn!("failed to reconnect the MCP server: {e}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::tests::http_server;
+    use crate::mcp::MCP_PROTOCOL_VERSION;
+    use mcp_client::transport::Error as TransportError;
+    use serde_json::{json, Value};
+    use std::sync::atomic::AtomicUsize;
+
+    /// A server with a single tool, counting its initializations.
+    fn tool_server(
+        tool: &'static str,
+        initializations: Arc<AtomicUsize>,
+    ) -> impl Fn(&str, &Value) -> Result<Value, TransportError> + Send + Sync + 'static {
+        move |method, _| {
+            Ok(match method {
+                "initialize" => {
+                    initializations.fetch_add(1, Ordering::SeqCst);
+                    json!({
+                        "protocolVersion": MCP_PROTOCOL_VERSION,
+                        "capabilities": {"tools": {}},
+                        "serverInfo": {"name": "tools", "version": "1.0.0"}
+                    })
+                }
+                "tools/list" => json!({"tools": [
+                    {"name": tool, "description": tool, "inputSchema": {"type": "object"}}
+                ]}),
+                _ => json!({}),
+            })
+        }
+    }
+
+    async fn connect(server: &mockito::ServerGuard) -> RwLock<Vec<MCPClient>> {
+        let client = MCPServerConfig::remote(format!("{}/mcp", server.url()))
+            .connect()
+            .await
+            .unwrap();
+        RwLock::new(vec![client])
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_client() {
+        let mut server = mockito::Server::new_async().await;
+        let initializations = Arc::new(AtomicUsize::new(0));
+        let mock = http_server(
+            &mut server,
+            tool_server("search", Arc::clone(&initializations)),
+        )
+        .await;
+        let clients = connect(&server).await;
+        clients.write().await[0].name = "remote".to_string();
+
+        mock.remove_async().await;
+        http_server(
+            &mut server,
+            tool_server("lookup", Arc::clone(&initializations)),
+        )
+        .await;
+        // The second reconnection waits for the first one instead of connecting again
+        let (first, second) =
+            tokio::join!(reconnect_client(&clients, 0), reconnect_client(&clients, 0));
+        first.unwrap();
+        second.unwrap();
+        assert_eq!(initializations.load(Ordering::SeqCst), 2);
+
+        let clients = clients.read().await;
+        assert_eq!(clients[0].name, "remote");
+        assert_eq!(clients[0].health, MCPHealth::Healthy);
+        assert!(clients[0].tools.contains_key("lookup"));
+        assert!(!clients[0].tools.contains_key("search"));
+    }
+
+    #[tokio::test]
+    async fn test_health_transitions() {
+        let mut server = mockito::Server::new_async().await;
+        http_server(&mut server, tool_server("search", Arc::default())).await;
+        let clients = connect(&server).await;
+        let config = clients.read().await[0].config.clone();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed_url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        drop(listener);
+        clients.write().await[0].config = Some(MCPServerConfig::remote(closed_url));
+        // The backoff between the attempts elapses instantly
+        tokio::time::pause();
+        assert!(reconnect_client(&clients, 0).await.is_err());
+        assert!(matches!(clients.read().await[0].health, MCPHealth::Down(_)));
+        tokio::time::resume();
+
+        clients.write().await[0].config = config;
+        reconnect_client(&clients, 0).await.unwrap();
+        assert_eq!(clients.read().await[0].health, MCPHealth::Healthy);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_changed_tools() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = http_server(&mut server, tool_server("search", Arc::default())).await;
+        let clients = connect(&server).await;
+        mock.remove_async().await;
+        http_server(&mut server, tool_server("lookup", Arc::default())).await;
+
+        // Only the servers that notified a change are listed again
+        refresh_changed_tools(&clients).await.unwrap();
+        assert!(clients.read().await[0].tools.contains_key("search"));
+
+        clients.read().await[0]
+            .tools_changed
+            .store(true, Ordering::Relaxed);
+        refresh_changed_tools(&clients).await.unwrap();
+        let clients = clients.read().await;
+        assert!(clients[0].tools.contains_key("lookup"));
+        assert!(!clients[0].tools_changed());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::chat::ThinkingBlock;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{num::NonZeroUsize, sync::Arc};
@@ -35,13 +36,19 @@ impl MessageType {
     }
 }
 
-/// Represents a message with content, type, optional ID, and optional tool calls.
+/// Represents a message with content, type, optional ID, optional tool calls and optional reasoning.
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Message {
     pub content: String,
     pub message_type: MessageType,
     pub id: Option<String>,
     pub tool_calls: Option<Value>,
+    /// The reasoning of the model before the content, kept apart from the answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    /// The signed thinking blocks of the model, sent back with the message in the next turns.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thinking_blocks: Vec<ThinkingBlock>,
 }
 
 impl Message {
@@ -52,6 +59,8 @@ impl Message {
             message_type: MessageType::Human,
             id: None,
             tool_calls: None,
+            reasoning: None,
+            thinking_blocks: Vec::new(),
         }
     }
 
@@ -62,6 +71,8 @@ impl Message {
             message_type: MessageType::System,
             id: None,
             tool_calls: None,
+            reasoning: None,
+            thinking_blocks: Vec::new(),
         }
     }
 
@@ -72,6 +83,8 @@ impl Message {
             message_type: MessageType::Tool,
             id: Some(id.into()),
             tool_calls: None,
+            reasoning: None,
+            thinking_blocks: Vec::new(),
         }
     }
 
@@ -82,6 +95,8 @@ impl Message {
             message_type: MessageType::AI,
             id: None,
             tool_calls: None,
+            reasoning: None,
+            thinking_blocks: Vec::new(),
         }
     }
 
@@ -91,6 +106,18 @@ impl Message {
         self
     }
 
+    /// Adds the model reasoning to the message.
+    pub fn with_reasoning<T: Into<String>>(mut self, reasoning: T) -> Self {
+        self.reasoning = Some(reasoning.into());
+        self
+    }
+
+    /// Adds the signed thinking blocks of the model to the message.
+    pub fn with_thinking_blocks(mut self, thinking_blocks: Vec<ThinkingBlock>) -> Self {
+        self.thinking_blocks = thinking_blocks;
+        self
+    }
+
     /// Deserializes a `Value` into a vector of `Message` objects.
     pub fn messages_from_value(value: &Value) -> Result<Vec<Message>, serde_json::error::Error> {
         serde_json::from_value(value.clone())
@@ -127,6 +154,15 @@ pub trait Memory: Send + Sync {
     /// Clears all messages from memory.
     fn clear(&mut self);
 
+    /// The id of the last response on stateful APIs, continued by the next request instead of
+    /// resending the whole conversation.
+    fn previous_response_id(&self) -> Option<String> {
+        None
+    }
+
+    /// Sets the id of the last response, `None` to resend the whole conversation next time.
+    fn set_previous_response_id(&mut self, _response_id: Option<String>) {}
+
     /// Converts the memory's messages to a string representation.
     fn to_string(&self) -> String {
         self.messages()
@@ -151,6 +187,7 @@ where
 pub struct WindowBufferMemory {
     window_size: usize,
     messages: Vec<Message>,
+    previous_response_id: Option<String>,
 }
 
 impl Default for WindowBufferMemory {
@@ -166,6 +203,7 @@ impl WindowBufferMemory {
         Self {
             messages: Vec::new(),
             window_size,
+            previous_response_id: None,
         }
     }
 
@@ -207,6 +245,15 @@ impl Memory for WindowBufferMemory {
     /// Clears all messages from the buffer.
     fn clear(&mut self) {
         self.messages.clear();
+        self.previous_response_id = None;
+    }
+
+    fn previous_response_id(&self) -> Option<String> {
+        self.previous_response_id.clone()
+    }
+
+    fn set_previous_response_id(&mut self, response_id: Option<String>) {
+        self.previous_response_id = response_id;
     }
 }
 
@@ -214,6 +261,7 @@ impl Memory for WindowBufferMemory {
 pub struct RLUCacheMemory {
     cache: lru::LruCache<String, Message>,
     capacity: usize,
+    previous_response_id: Option<String>,
 }
 
 impl RLUCacheMemory {
@@ -222,6 +270,7 @@ impl RLUCacheMemory {
         Self {
             cache: lru::LruCache::new(NonZeroUsize::new(capacity).unwrap()),
             capacity,
+            previous_response_id: None,
         }
     }
 
@@ -250,6 +299,15 @@ impl Memory for RLUCacheMemory {
     /// Clears all messages from the cache.
     fn clear(&mut self) {
         self.cache.clear();
+        self.previous_response_id = None;
+    }
+
+    fn previous_response_id(&self) -> Option<String> {
+        self.previous_response_id.clone()
+    }
+
+    fn set_previous_response_id(&mut self, response_id: Option<String>) {
+        self.previous_response_id = response_id;
     }
 }
 
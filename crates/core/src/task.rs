@@ -1,3 +1,4 @@
+use crate::chat::BudgetExceeded;
 use crate::mcp::MCPError;
 use crate::{agent::Agent, chat::Completion};
 use std::sync::Arc;
@@ -81,4 +82,8 @@ pub enum TaskError {
     Unknown(String),
     #[error("MCP error: {0}")]
     MCPError(#[from] MCPError),
+    #[error("{0}")]
+    BudgetExceeded(#[from] BudgetExceeded),
+    #[error("The task was cancelled")]
+    Cancelled,
 }
@@ -126,6 +126,9 @@ pub enum ToolError {
     InvalidOutput,
     #[error("The tool is not available or not configured properly")]
     InvalidTool,
+    /// The action was refused by a policy, with the reason the model can explain to the user.
+    #[error("Policy violation: {0}")]
+    PolicyViolation(String),
     #[error("An unknown error occurred: {0}")]
     Unknown(String),
     #[error("JsonError: {0}")]
@@ -133,7 +136,7 @@ pub enum ToolError {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::{StructureTool, Tool, ToolError};
     use async_trait::async_trait;
     use schemars::JsonSchema;
@@ -6,6 +6,8 @@ pub(crate) use tracing::{debug, error, info, span, trace, warn, Level};
 pub mod llms;
 pub mod requests;
 
+pub use tokio_util::sync::CancellationToken;
+
 use llms::api::anthropic::builder::AnthropicBackendBuilder;
 use llms::api::openai::builder::OpenAIBackendBuilder;
 use llms::api::perplexity::builder::PerplexityBackendBuilder;
@@ -0,0 +1,70 @@
+use super::{completion::AnthropicCompletionRequest, AnthropicBackend};
+use crate::llms::api::error::{ApiError, ClientError};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// A request of a message batch, also a line of its JSONL representation.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnthropicBatchRequest {
+    pub custom_id: String,
+    pub params: AnthropicCompletionRequest,
+}
+
+/// A message batch, returned when a batch is created and retrieved.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AnthropicBatch {
+    pub id: String,
+    /// One of `in_progress`, `canceling` or `ended`.
+    pub processing_status: String,
+    /// The URL of the JSONL results, once the batch has ended.
+    pub results_url: Option<String>,
+}
+
+/// A line of the JSONL results of a message batch.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AnthropicBatchResultLine {
+    pub custom_id: String,
+    pub result: AnthropicBatchResult,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicBatchResult {
+    Succeeded { message: serde_json::Value },
+    Errored { error: AnthropicBatchError },
+    Canceled,
+    Expired,
+}
+
+/// The error of a failed request, nested like the error responses of the API.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AnthropicBatchError {
+    pub error: ApiError,
+}
+
+impl AnthropicBackend {
+    pub(crate) async fn batch_create(
+        &self,
+        requests: Vec<AnthropicBatchRequest>,
+    ) -> Result<AnthropicBatch, ClientError> {
+        self.client
+            .post("/messages/batches", json!({ "requests": requests }))
+            .await
+    }
+
+    pub(crate) async fn batch_retrieve(
+        &self,
+        batch_id: &str,
+    ) -> Result<AnthropicBatch, ClientError> {
+        self.client
+            .get(&format!("/messages/batches/{batch_id}"))
+            .await
+    }
+
+    pub(crate) async fn batch_results(&self, batch_id: &str) -> Result<Bytes, ClientError> {
+        self.client
+            .get_bytes(&format!("/messages/batches/{batch_id}/results"))
+            .await
+    }
+}
@@ -1,5 +1,5 @@
 use crate::requests::completion::{
-    error::CompletionError, request::CompletionRequest, ToolChoice, ToolDefinition,
+    error::CompletionError, request::CompletionRequest, ThinkingBlock, ToolChoice,
 };
 use serde::{Deserialize, Serialize};
 
@@ -38,7 +38,7 @@ pub struct AnthropicCompletionRequest {
     ///
     /// A system prompt is a way of providing context and instructions to Claude, such as specifying a particular goal or role. See our [guide to system prompts](https://docs.anthropic.com/claude/docs/system-prompts).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub system: Option<String>,
+    pub system: Option<RequestContent>,
 
     /// Amount of randomness injected into the response.
     ///
@@ -53,16 +53,50 @@ pub struct AnthropicCompletionRequest {
 
     /// The tools for the request, default: None
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tools: Option<Vec<ToolDefinition>>,
+    pub tools: Option<Vec<AnthropicToolDefinition>>,
 
     /// The tool choice for the request, default: None
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tool_choice: Option<ToolChoice>,
+    pub tool_choice: Option<AnthropicToolChoice>,
+
+    /// Extended thinking configuration, default: None
+    ///
+    /// The thinking tokens are part of `max_tokens`, and thinking requires the default temperature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<Thinking>,
 }
 
+/// The maximum number of cache breakpoints of a request.
+pub const MAX_CACHE_BREAKPOINTS: usize = 4;
+/// Messages shorter than this are not worth a cache breakpoint, the minimum cacheable prompt
+/// is 1024 tokens for most models.
+pub const MIN_CACHEABLE_MESSAGE_CHARS: usize = 4096;
+/// The minimum extended thinking budget accepted by the API.
+pub const MIN_THINKING_BUDGET_TOKENS: u64 = 1024;
+
 impl AnthropicCompletionRequest {
     pub fn new(req: &CompletionRequest) -> crate::Result<Self, CompletionError> {
+        if !req.response_format.is_text() {
+            return Err(CompletionError::RequestBuilderError(
+                "Anthropic does not support structured response formats".to_string(),
+            ));
+        }
+        if req.config.n_choices > 1 {
+            return Err(CompletionError::RequestBuilderError(
+                "Anthropic does not support more than one choice per request".to_string(),
+            ));
+        }
+        let thinking = req
+            .config
+            .thinking_budget_tokens
+            .map(|budget_tokens| Thinking {
+                r#type: "enabled".to_string(),
+                budget_tokens,
+            });
+        let max_tokens = max_tokens(req, thinking.as_ref())?;
+
         let mut messages = Vec::new();
+        let mut assistant_messages = 0;
         let mut system_prompt = None;
         match req.prompt.get_built_prompt_messages() {
             Ok(prompt_message) => {
@@ -75,11 +109,34 @@ impl AnthropicCompletionRequest {
                     })?;
 
                     match role.as_str() {
-                        "user" | "assistant" => messages.push(CompletionRequestMessage {
+                        "user" => messages.push(CompletionRequestMessage {
                             role: role.to_string(),
-                            content: content.to_string(),
+                            content: RequestContent::Text(content.to_string()),
                         }),
-                        "system" => system_prompt = Some(content.to_string()),
+                        "assistant" => {
+                            // Signed thinking blocks go back before the text while thinking
+                            let thinking_blocks = req
+                                .assistant_thinking
+                                .get(assistant_messages)
+                                .filter(|blocks| thinking.is_some() && !blocks.is_empty());
+                            assistant_messages += 1;
+                            let content = match thinking_blocks {
+                                Some(blocks) => RequestContent::Blocks(
+                                    blocks
+                                        .iter()
+                                        .cloned()
+                                        .map(ContentBlock::Thinking)
+                                        .chain([ContentBlock::Text(TextBlock::new(content))])
+                                        .collect(),
+                                ),
+                                None => RequestContent::Text(content.to_string()),
+                            };
+                            messages.push(CompletionRequestMessage {
+                                role: role.to_string(),
+                                content,
+                            })
+                        }
+                        "system" => system_prompt = Some(RequestContent::Text(content.to_string())),
                         _ => {
                             return Err(CompletionError::RequestBuilderError(format!(
                                 "Role {} not supported",
@@ -100,28 +157,110 @@ impl AnthropicCompletionRequest {
         let stop = req.stop_sequences.to_vec();
         let stop_sequences = if stop.is_empty() { None } else { Some(stop) };
 
-        Ok(AnthropicCompletionRequest {
+        let mut request = AnthropicCompletionRequest {
             model: req.backend.model_id().to_owned(),
             messages,
-            max_tokens: req.config.actual_request_tokens.unwrap(),
+            max_tokens,
             stop_sequences,
             system: system_prompt,
-            temperature: temperature(req.config.temperature)?,
-            top_p: top_p(req.config.top_p)?,
+            temperature: if thinking.is_some() {
+                1.0
+            } else {
+                temperature(req.config.temperature)?
+            },
+            top_p: if thinking.is_some() {
+                None
+            } else {
+                top_p(req.config.top_p)?
+            },
             tools: if !req.tools.is_empty() {
-                Some(req.tools.clone())
+                Some(
+                    req.tools
+                        .iter()
+                        .map(|tool| AnthropicToolDefinition {
+                            name: tool.name.clone(),
+                            description: tool.description.clone(),
+                            input_schema: tool.parameters.clone(),
+                            cache_control: None,
+                        })
+                        .collect(),
+                )
             } else {
                 None
             },
             tool_choice: if !req.tools.is_empty() {
-                Some(req.tool_choice.clone())
+                Some(AnthropicToolChoice::new(
+                    &req.tool_choice,
+                    req.parallel_tool_calls,
+                ))
             } else {
                 None
             },
-        })
+            thinking,
+        };
+        if req.config.cache_prompt {
+            request.set_cache_breakpoints();
+        }
+        Ok(request)
+    }
+
+    /// Marks the cacheable prefixes of the request: the tools, the system prompt and the latest
+    /// long messages, such as documents, within the limit of [MAX_CACHE_BREAKPOINTS].
+    ///
+    /// Anthropic caches everything up to a breakpoint, so later requests sharing the prefix are
+    /// billed at the cache read price.
+    pub fn set_cache_breakpoints(&mut self) {
+        let mut breakpoints = 0;
+        if let Some(tool) = self.tools.as_mut().and_then(|tools| tools.last_mut()) {
+            tool.cache_control = Some(CacheControl::ephemeral());
+            breakpoints += 1;
+        }
+        if let Some(system) = self.system.as_mut() {
+            system.set_cache_control();
+            breakpoints += 1;
+        }
+        for message in self.messages.iter_mut().rev() {
+            if breakpoints >= MAX_CACHE_BREAKPOINTS {
+                break;
+            }
+            if message.content.len() >= MIN_CACHEABLE_MESSAGE_CHARS {
+                message.content.set_cache_control();
+                breakpoints += 1;
+            }
+        }
     }
 }
 
+/// The response tokens plus the thinking budget, within the maximum output tokens of the model.
+///
+/// The response tokens are reduced to make room for the budget, which must leave at least one
+/// response token.
+fn max_tokens(
+    req: &CompletionRequest,
+    thinking: Option<&Thinking>,
+) -> crate::Result<u64, CompletionError> {
+    let response_tokens = req.config.actual_request_tokens.ok_or_else(|| {
+        CompletionError::RequestBuilderError("Response tokens not set".to_string())
+    })?;
+    let Some(budget_tokens) = thinking.map(|t| t.budget_tokens) else {
+        return Ok(response_tokens);
+    };
+    let max_output_tokens = req.config.inference_ctx_size;
+    if budget_tokens < MIN_THINKING_BUDGET_TOKENS {
+        return Err(CompletionError::RequestBuilderError(format!(
+            "Thinking budget of {budget_tokens} tokens is below the minimum of {MIN_THINKING_BUDGET_TOKENS}"
+        )));
+    }
+    if budget_tokens >= max_output_tokens {
+        return Err(CompletionError::RequestBuilderError(format!(
+            "Thinking budget of {budget_tokens} tokens leaves no response tokens within the model maximum of {max_output_tokens} output tokens"
+        )));
+    }
+    Ok(response_tokens
+        .saturating_add(budget_tokens)
+        .min(max_output_tokens))
+}
+
 /// Convert the native temperature from 0.0 to 2.0 to 0.0 to 1.0
 fn temperature(value: f32) -> crate::Result<f32, CompletionError> {
     if (0.0..=2.0).contains(&value) {
@@ -151,5 +290,271 @@ fn top_p(value: Option<f32>) -> crate::Result<Option<f32>, CompletionError> {
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct CompletionRequestMessage {
     pub role: String,
-    pub content: String,
+    pub content: RequestContent,
+}
+
+/// The content of a message or system prompt, either plain text or content blocks.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum RequestContent {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+impl RequestContent {
+    /// The number of characters of the content.
+    pub fn len(&self) -> usize {
+        match self {
+            RequestContent::Text(text) => text.len(),
+            RequestContent::Blocks(blocks) => blocks
+                .iter()
+                .map(|block| match block {
+                    ContentBlock::Text(text) => text.text.len(),
+                    ContentBlock::Thinking(_) => 0,
+                })
+                .sum(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sets a cache breakpoint on the last block of the content.
+    pub fn set_cache_control(&mut self) {
+        if let RequestContent::Text(text) = self {
+            *self = RequestContent::Blocks(vec![ContentBlock::Text(TextBlock::new(
+                std::mem::take(text),
+            ))]);
+        }
+        if let RequestContent::Blocks(blocks) = self {
+            if let Some(ContentBlock::Text(block)) = blocks.last_mut() {
+                block.cache_control = Some(CacheControl::ephemeral());
+            }
+        }
+    }
+}
+
+/// A block of a message content, the thinking blocks of an assistant message coming first.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ContentBlock {
+    Text(TextBlock),
+    Thinking(ThinkingBlock),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TextBlock {
+    pub r#type: String,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
+}
+
+impl TextBlock {
+    pub fn new<T: Into<String>>(text: T) -> Self {
+        Self {
+            r#type: "text".to_string(),
+            text: text.into(),
+            cache_control: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CacheControl {
+    pub r#type: String,
+}
+
+impl CacheControl {
+    /// The only cache type, with a five minutes lifetime refreshed on every read.
+    pub fn ephemeral() -> Self {
+        Self {
+            r#type: "ephemeral".to_string(),
+        }
+    }
+}
+
+/// How the model may use the tools: `{"type": "auto" | "any" | "tool" | "none"}`, with the
+/// tool `name` for `tool`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AnthropicToolChoice {
+    #[serde(flatten)]
+    pub choice: ToolChoice,
+    /// Limits the model to at most one tool call, default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_parallel_tool_use: Option<bool>,
+}
+
+impl AnthropicToolChoice {
+    pub fn new(choice: &ToolChoice, parallel_tool_calls: Option<bool>) -> Self {
+        Self {
+            choice: choice.clone(),
+            disable_parallel_tool_use: parallel_tool_calls
+                .filter(|_| *choice != ToolChoice::None)
+                .map(|parallel| !parallel),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AnthropicToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub input_schema: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Thinking {
+    pub r#type: String,
+    /// The tokens the model may use to think, at least 1024.
+    pub budget_tokens: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llms::{
+        api::anthropic::{AnthropicBackend, AnthropicConfig},
+        LLMBackend,
+    };
+    use alith_models::api_model::ApiLLMModel;
+    use std::sync::Arc;
+
+    fn thinking_request(budget_tokens: u64) -> CompletionRequest {
+        let mut config = AnthropicConfig::new();
+        config.api_config.api_key = Some("test-key".to_string().into());
+        config.logging_config.logging_enabled = false;
+        let backend = Arc::new(LLMBackend::Anthropic(
+            AnthropicBackend::new(config, ApiLLMModel::claude_3_7_sonnet()).unwrap(),
+        ));
+        let mut request = CompletionRequest::new(backend);
+        request
+            .prompt
+            .add_user_message()
+            .unwrap()
+            .set_content("Weather in Rome?");
+        request
+            .prompt
+            .add_assistant_message()
+            .unwrap()
+            .set_content("Calling the weather tool.");
+        request
+            .prompt
+            .add_user_message()
+            .unwrap()
+            .set_content("Rainy.");
+        request.config.thinking_budget_tokens = Some(budget_tokens);
+        request
+    }
+
+    #[test]
+    fn test_thinking_blocks_sent_back() {
+        let mut request = thinking_request(2048);
+        request.config.requested_response_tokens = Some(1000);
+        request.assistant_thinking = vec![vec![
+            ThinkingBlock::Thinking {
+                thinking: "Use the tool.".to_string(),
+                signature: "sig".to_string(),
+            },
+            ThinkingBlock::RedactedThinking {
+                data: "encrypted".to_string(),
+            },
+        ]];
+        request.prepare_request().unwrap();
+
+        let json =
+            serde_json::to_value(AnthropicCompletionRequest::new(&request).unwrap()).unwrap();
+        assert_eq!(json["max_tokens"], serde_json::json!(3048));
+        assert_eq!(
+            json["messages"][0]["content"],
+            serde_json::json!("Weather in Rome?")
+        );
+        assert_eq!(
+            json["messages"][1]["content"],
+            serde_json::json!([
+                {"type": "thinking", "thinking": "Use the tool.", "signature": "sig"},
+                {"type": "redacted_thinking", "data": "encrypted"},
+                {"type": "text", "text": "Calling the weather tool."}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_thinking_budget_limits() {
+        // The response tokens make room for the budget within the 8192 output tokens
+        let mut request = thinking_request(2048);
+        request.prepare_request().unwrap();
+        let anthropic_request = AnthropicCompletionRequest::new(&request).unwrap();
+        assert_eq!(anthropic_request.max_tokens, 8192);
+
+        for budget_tokens in [512, 8192] {
+            let mut request = thinking_request(budget_tokens);
+            request.prepare_request().unwrap();
+            assert!(matches!(
+                AnthropicCompletionRequest::new(&request),
+                Err(CompletionError::RequestBuilderError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_tool_choice() {
+        let json = |choice: &ToolChoice, parallel: Option<bool>| {
+            serde_json::to_value(AnthropicToolChoice::new(choice, parallel)).unwrap()
+        };
+        assert_eq!(
+            json(&ToolChoice::tool("extractor"), Some(false)),
+            serde_json::json!({"type": "tool", "name": "extractor", "disable_parallel_tool_use": true})
+        );
+        assert_eq!(
+            json(&ToolChoice::Any, None),
+            serde_json::json!({"type": "any"})
+        );
+        assert_eq!(
+            json(&ToolChoice::None, Some(false)),
+            serde_json::json!({"type": "none"})
+        );
+    }
+
+    #[test]
+    fn test_cache_breakpoints() {
+        let message = |content: String| CompletionRequestMessage {
+            role: "user".to_string(),
+            content: RequestContent::Text(content),
+        };
+        let mut request = AnthropicCompletionRequest {
+            system: Some(RequestContent::Text(
+                "You are a helpful assistant.".to_string(),
+            )),
+            messages: vec![
+                message("a".repeat(MIN_CACHEABLE_MESSAGE_CHARS)),
+                message("short".to_string()),
+                message("b".repeat(MIN_CACHEABLE_MESSAGE_CHARS)),
+                message("c".repeat(MIN_CACHEABLE_MESSAGE_CHARS)),
+            ],
+            tools: Some(vec![AnthropicToolDefinition {
+                name: "search".to_string(),
+                description: "Search the web".to_string(),
+                input_schema: serde_json::json!({"type": "object"}),
+                cache_control: None,
+            }]),
+            ..Default::default()
+        };
+        request.set_cache_breakpoints();
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            json["system"][0]["cache_control"]["type"],
+            serde_json::json!("ephemeral")
+        );
+        assert!(json["tools"][0]["cache_control"].is_object());
+        // The tools, the system prompt and the two latest long messages.
+        assert!(json["messages"][0]["content"].is_string());
+        assert!(json["messages"][1]["content"].is_string());
+        assert!(json["messages"][2]["content"][0]["cache_control"].is_object());
+        assert!(json["messages"][3]["content"][0]["cache_control"].is_object());
+    }
 }
@@ -25,53 +25,81 @@ impl CompletionResponse {
                 }
             }
             StopReason::MaxTokens => CompletionFinishReason::StopLimit,
-            StopReason::ToolUse => {
-                return Err(CompletionError::StopReasonUnsupported(
-                    "StopReason::ToolUse is not supported".to_owned(),
-                ))
-            }
+            StopReason::ToolUse => CompletionFinishReason::ToolsCall,
         };
 
         if res.content.is_empty() {
             return Err(CompletionError::ResponseContentEmpty);
         }
 
-        if res.content.len() > 1 {
-            return Err(CompletionError::ResponseContentEmpty);
-        }
-        let content = res
-            .content
-            .first()
-            .ok_or_else(|| CompletionError::ResponseContentEmpty)?
-            .text()
-            .to_owned();
-
-        Ok(Self {
-            id: res.id.to_owned(),
-            index: None,
-            content,
-            finish_reason,
-            completion_probabilities: None,
-            truncated: false,
-            generation_settings: GenerationSettings::new_from_anthropic(req, &res),
-            timing_usage: TimingUsage::new_from_generic(req.start_time),
-            token_usage: TokenUsage::new_from_anthropic(&res),
-            tool_calls: match res.content.as_slice() {
-                [CompletionContent::ToolUse {
+        let mut content = String::new();
+        let mut reasoning: Option<String> = None;
+        let mut thinking_blocks = Vec::new();
+        let mut tool_calls = Vec::new();
+        for block in &res.content {
+            match block {
+                CompletionContent::String(text) | CompletionContent::Text { text, .. } => {
+                    content.push_str(text)
+                }
+                CompletionContent::Thinking {
+                    thinking,
+                    signature,
+                    ..
+                } => {
+                    reasoning.get_or_insert_with(String::new).push_str(thinking);
+                    thinking_blocks.push(ThinkingBlock::Thinking {
+                        thinking: thinking.to_owned(),
+                        signature: signature.to_owned(),
+                    });
+                }
+                CompletionContent::RedactedThinking { data, .. } => {
+                    thinking_blocks.push(ThinkingBlock::RedactedThinking {
+                        data: data.to_owned(),
+                    })
+                }
+                CompletionContent::ToolUse {
                     name,
                     input,
                     id,
                     r#type,
-                }, ..] => Some(vec![ToolCall {
+                } => tool_calls.push(ToolCall {
                     id: id.to_owned(),
                     r#type: r#type.to_owned(),
                     function: Function {
                         name: name.to_owned(),
                         arguments: serde_json::to_string(input)?,
                     },
-                }]),
-                _ => None,
-            },
+                }),
+            }
+        }
+        let tool_calls = if tool_calls.is_empty() {
+            None
+        } else {
+            Some(tool_calls)
+        };
+
+        Ok(Self {
+            id: res.id.to_owned(),
+            response_id: None,
+            index: None,
+            content: content.clone(),
+            reasoning: reasoning.clone(),
+            thinking_blocks,
+            finish_reason: finish_reason.clone(),
+            completion_probabilities: None,
+            truncated: false,
+            generation_settings: GenerationSettings::new_from_anthropic(req, &res),
+            timing_usage: TimingUsage::new_from_generic(req.start_time),
+            token_usage: TokenUsage::new_from_anthropic(&res).with_cost(req.backend.api_model()),
+            tool_calls: tool_calls.clone(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                content,
+                reasoning,
+                finish_reason,
+                completion_probabilities: None,
+                tool_calls,
+            }],
         })
     }
 }
@@ -126,6 +154,17 @@ pub enum CompletionContent {
         name: String,
         input: serde_json::Value,
     },
+    /// The extended thinking of the model.
+    Thinking {
+        r#type: String,
+        thinking: String,
+        signature: String,
+    },
+    /// Thinking encrypted by the safety systems.
+    RedactedThinking {
+        r#type: String,
+        data: String,
+    },
 }
 
 impl CompletionContent {
@@ -135,7 +174,9 @@ impl CompletionContent {
             CompletionContent::Text {
                 r#type: _, text, ..
             } => text.to_string(),
-            CompletionContent::ToolUse { .. } => "".to_string(),
+            CompletionContent::ToolUse { .. }
+            | CompletionContent::Thinking { .. }
+            | CompletionContent::RedactedThinking { .. } => "".to_string(),
         }
     }
 }
@@ -147,6 +188,12 @@ pub struct CompletionUsage {
     pub input_tokens: u32,
     /// The number of output tokens which were used.
     pub output_tokens: u32,
+    /// The number of input tokens used to create the cache entry.
+    #[serde(default)]
+    pub cache_creation_input_tokens: Option<u32>,
+    /// The number of input tokens read from the cache.
+    #[serde(default)]
+    pub cache_read_input_tokens: Option<u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
@@ -161,3 +208,63 @@ pub enum StopReason {
     /// Claude wants to use an external tool.
     ToolUse,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llms::{
+        api::anthropic::{AnthropicBackend, AnthropicConfig},
+        LLMBackend,
+    };
+    use alith_models::api_model::ApiLLMModel;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_thinking_and_cache_usage() {
+        let res: AnthropicCompletionResponse = serde_json::from_value(serde_json::json!({
+            "id": "msg_1",
+            "model": "claude-3-7-sonnet-20250219",
+            "stop_reason": "end_turn",
+            "stop_sequence": null,
+            "content": [
+                {"type": "thinking", "thinking": "Let me think.", "signature": "sig"},
+                {"type": "text", "text": "Hello"}
+            ],
+            "usage": {
+                "input_tokens": 100,
+                "output_tokens": 10,
+                "cache_creation_input_tokens": 0,
+                "cache_read_input_tokens": 30000
+            }
+        }))
+        .unwrap();
+        assert_eq!(res.content[0].text(), "");
+        assert_eq!(res.content[1].text(), "Hello");
+
+        let usage =
+            TokenUsage::new_from_anthropic(&res).with_cost(&ApiLLMModel::claude_3_7_sonnet());
+        assert_eq!(usage.tokens_cached, Some(30000));
+        assert_eq!(usage.prompt_tokens, 30100);
+        // 100 input tokens at $3/M, 30000 cached tokens at $0.30/M and 10 output tokens at $15/M
+        let expected = (100.0 * 3.0 + 30000.0 * 0.3 + 10.0 * 15.0) / 1_000_000.0;
+        assert!((usage.dollar_cost.unwrap() - expected).abs() < 1e-6);
+
+        let mut config = AnthropicConfig::new();
+        config.api_config.api_key = Some("test-key".to_string().into());
+        config.logging_config.logging_enabled = false;
+        let backend = Arc::new(LLMBackend::Anthropic(
+            AnthropicBackend::new(config, ApiLLMModel::claude_3_7_sonnet()).unwrap(),
+        ));
+        let response =
+            CompletionResponse::new_from_anthropic(&CompletionRequest::new(backend), res).unwrap();
+        assert_eq!(response.content, "Hello");
+        assert_eq!(response.reasoning.as_deref(), Some("Let me think."));
+        assert_eq!(
+            response.thinking_blocks,
+            vec![ThinkingBlock::Thinking {
+                thinking: "Let me think.".to_string(),
+                signature: "sig".to_string(),
+            }]
+        );
+    }
+}
@@ -1,9 +1,12 @@
+pub mod batch;
 pub mod builder;
 pub mod completion;
 
 use super::{
     client::ApiClient,
     config::{ApiConfig, ApiConfigTrait},
+    http::HttpConfig,
+    rate_limit::RateLimiter,
 };
 use crate::requests::completion::{
     error::CompletionError, request::CompletionRequest, response::CompletionResponse,
@@ -13,6 +16,7 @@ use alith_models::api_model::ApiLLMModel;
 use completion::AnthropicCompletionRequest;
 use reqwest::header::HeaderMap;
 use secrecy::{ExposeSecret, SecretString};
+use std::sync::Arc;
 
 /// Default v1 API base url
 pub const ANTHROPIC_API_HOST: &str = "api.anthropic.com/v1";
@@ -31,7 +35,7 @@ impl AnthropicBackend {
         config.logging_config.load_logger()?;
         config.api_config.api_key = Some(config.api_config.load_api_key()?);
         Ok(Self {
-            client: ApiClient::new(config),
+            client: ApiClient::new(config)?,
             model,
         })
     }
@@ -41,7 +45,12 @@ impl AnthropicBackend {
     ) -> crate::Result<CompletionResponse, CompletionError> {
         match self
             .client
-            .post("/messages", AnthropicCompletionRequest::new(request)?)
+            .post_with_tokens(
+                "/messages",
+                AnthropicCompletionRequest::new(request)?,
+                request.estimated_tokens(),
+                request.cancellation_token.as_ref(),
+            )
             .await
         {
             Err(e) => Err(CompletionError::ClientError(e)),
@@ -66,6 +75,8 @@ impl Default for AnthropicConfig {
                 port: None,
                 api_key: None,
                 api_key_env_var: "ANTHROPIC_API_KEY".to_string(),
+                rate_limiter: None,
+                http: Default::default(),
             },
             logging_config: LoggingConfig {
                 logger_name: "anthropic".to_string(),
@@ -119,10 +130,45 @@ impl ApiConfigTrait for AnthropicConfig {
     }
 
     fn url(&self, path: &str) -> String {
-        format!("https://{}{}", self.api_config.host, path)
+        if self.api_config.host.starts_with("http") {
+            format!("{}{}", self.api_config.host, path)
+        } else {
+            format!("https://{}{}", self.api_config.host, path)
+        }
     }
 
     fn api_key(&self) -> &Option<SecretString> {
         &self.api_config.api_key
     }
+
+    fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.api_config.rate_limiter.as_ref()
+    }
+
+    fn http_config(&self) -> &HttpConfig {
+        &self.api_config.http
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llms::LLMBackend;
+    use crate::requests::embeddings::{EmbeddingsError, EmbeddingsRequest};
+
+    #[tokio::test]
+    async fn test_anthropic_embeddings_unsupported() {
+        let mut config = AnthropicConfig::new();
+        config.api_config.api_key = Some("test-key".to_string().into());
+        config.logging_config.logging_enabled = false;
+        let backend = Arc::new(LLMBackend::Anthropic(
+            AnthropicBackend::new(config, ApiLLMModel::claude_3_5_sonnet()).unwrap(),
+        ));
+        let mut request = EmbeddingsRequest::new(backend);
+        request.set_input(vec!["hello".to_string()]);
+        assert!(matches!(
+            request.request().await,
+            Err(EmbeddingsError::UnsupportedBackend(_))
+        ));
+    }
 }
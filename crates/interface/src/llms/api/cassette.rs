@@ -0,0 +1,383 @@
+use super::error::ClientError;
+use bytes::Bytes;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Selects the cassette mode of every API client: `record`, `replay` or `passthrough`.
+pub const CASSETTE_MODE_ENV_VAR: &str = "ALITH_CASSETTE_MODE";
+/// The directory of the cassette files, defaults to [DEFAULT_CASSETTE_DIR].
+pub const CASSETTE_DIR_ENV_VAR: &str = "ALITH_CASSETTE_DIR";
+pub const DEFAULT_CASSETTE_DIR: &str = "cassettes";
+/// Replaces secrets in recorded interactions.
+pub const REDACTED: &str = "<REDACTED>";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Requests go to the API and nothing is recorded.
+    #[default]
+    Passthrough,
+    /// Requests go to the API and the interactions are stored in the cassette.
+    Record,
+    /// Requests are answered from the cassette without a network call.
+    Replay,
+}
+
+impl FromStr for CassetteMode {
+    type Err = ClientError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "passthrough" | "none" | "off" => Ok(CassetteMode::Passthrough),
+            "record" => Ok(CassetteMode::Record),
+            "replay" => Ok(CassetteMode::Replay),
+            other => Err(ClientError::InvalidArgument(format!(
+                "Unknown cassette mode {other}, expected record, replay or passthrough"
+            ))),
+        }
+    }
+}
+
+impl CassetteMode {
+    /// Reads the mode from [CASSETTE_MODE_ENV_VAR], defaulting to passthrough.
+    pub fn from_env() -> Self {
+        match std::env::var(CASSETTE_MODE_ENV_VAR) {
+            Ok(mode) => mode.parse().unwrap_or_else(|e| {
+                crate::warn!("{e}");
+                CassetteMode::Passthrough
+            }),
+            Err(_) => CassetteMode::Passthrough,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    /// The JSON body with sorted keys, or the raw body if it is not JSON.
+    pub body: Option<Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    /// The JSON body, or the raw body as a string if it is not JSON.
+    pub body: Value,
+}
+
+/// Request/response pairs stored in a JSON file, used to record API calls once and replay them
+/// deterministically, e.g. to run agent tests offline.
+///
+/// Requests are matched on their method, url and normalized body. Identical requests are
+/// replayed in the order they were recorded, the last one repeats once they run out.
+/// Request headers are never recorded and the API key is redacted everywhere else.
+#[derive(Debug)]
+pub struct Cassette {
+    mode: CassetteMode,
+    path: PathBuf,
+    state: Mutex<CassetteState>,
+}
+
+#[derive(Debug, Default)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    /// Keys recorded by this process, older interactions with these keys were dropped.
+    recorded: HashSet<String>,
+    /// The number of times each key was replayed.
+    replayed: HashMap<String, usize>,
+}
+
+impl Cassette {
+    /// Opens the cassette file, an absent file is an empty cassette.
+    pub fn open<P: AsRef<Path>>(path: P, mode: CassetteMode) -> Result<Self, ClientError> {
+        let path = path.as_ref().to_path_buf();
+        let interactions = if mode != CassetteMode::Passthrough && path.exists() {
+            let file = std::fs::read(&path).map_err(|e| ClientError::GenericError {
+                message: format!("Failed to read cassette {}: {e}", path.display()),
+            })?;
+            serde_json::from_slice(&file).map_err(ClientError::JSONDeserialize)?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            mode,
+            path,
+            state: Mutex::new(CassetteState {
+                interactions,
+                ..Default::default()
+            }),
+        })
+    }
+
+    /// The cassette shared by every client of the host in this process, as selected by
+    /// [CASSETTE_MODE_ENV_VAR] and [CASSETTE_DIR_ENV_VAR]. `None` in passthrough mode.
+    pub fn from_env(host: &str) -> Option<Arc<Cassette>> {
+        static CASSETTES: OnceLock<Mutex<HashMap<PathBuf, Arc<Cassette>>>> = OnceLock::new();
+
+        let mode = CassetteMode::from_env();
+        if mode == CassetteMode::Passthrough {
+            return None;
+        }
+        let dir = std::env::var(CASSETTE_DIR_ENV_VAR)
+            .unwrap_or_else(|_| DEFAULT_CASSETTE_DIR.to_string());
+        let name: String = host
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let path = Path::new(&dir).join(format!("{name}.json"));
+
+        let mut cassettes = CASSETTES
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(cassette) = cassettes.get(&path) {
+            return Some(Arc::clone(cassette));
+        }
+        match Cassette::open(&path, mode) {
+            Ok(cassette) => {
+                let cassette = Arc::new(cassette);
+                cassettes.insert(path, Arc::clone(&cassette));
+                Some(cassette)
+            }
+            Err(e) => {
+                crate::error!("{e}");
+                None
+            }
+        }
+    }
+
+    #[inline]
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Answers the request from the cassette.
+    pub fn replay(
+        &self,
+        request: &reqwest::Request,
+        secrets: &[&str],
+    ) -> Result<(StatusCode, HeaderMap, Bytes), ClientError> {
+        let recorded = record_request(request, secrets);
+        let key = match_key(&recorded);
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let matches: Vec<usize> = state
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| match_key(&i.request) == key)
+            .map(|(index, _)| index)
+            .collect();
+        let Some(last) = matches.last() else {
+            return Err(ClientError::GenericError {
+                message: format!(
+                    "No interaction in cassette {} matches {} {}",
+                    self.path.display(),
+                    recorded.method,
+                    recorded.url
+                ),
+            });
+        };
+        let replayed = state.replayed.entry(key).or_default();
+        let index = *matches.get(*replayed).unwrap_or(last);
+        *replayed += 1;
+
+        let response = &state.interactions[index].response;
+        let status = StatusCode::from_u16(response.status)
+            .map_err(|e| ClientError::InvalidArgument(e.to_string()))?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &response.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+        let body = match &response.body {
+            Value::String(body) => Bytes::from(body.clone()),
+            body => Bytes::from(serde_json::to_vec(body).map_err(ClientError::JSONSerialize)?),
+        };
+        Ok((status, headers, body))
+    }
+
+    /// Stores the interaction and saves the cassette file.
+    pub fn record(
+        &self,
+        request: &reqwest::Request,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+        secrets: &[&str],
+    ) -> Result<(), ClientError> {
+        let request = record_request(request, secrets);
+        let response = RecordedResponse {
+            status: status.as_u16(),
+            headers: headers
+                .iter()
+                .filter(|(name, _)| *name != reqwest::header::SET_COOKIE)
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), redact(value.to_str().ok()?, secrets)))
+                })
+                .collect(),
+            body: match serde_json::from_slice::<Value>(body) {
+                Ok(body) => redact_json(body, secrets),
+                Err(_) => Value::String(redact(&String::from_utf8_lossy(body), secrets)),
+            },
+        };
+
+        let key = match_key(&request);
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.recorded.insert(key.clone()) {
+            // Re-recording replaces the interactions of earlier runs.
+            state.interactions.retain(|i| match_key(&i.request) != key);
+        }
+        state.interactions.push(Interaction { request, response });
+
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| ClientError::GenericError {
+                message: format!("Failed to create cassette dir {}: {e}", dir.display()),
+            })?;
+        }
+        let file =
+            serde_json::to_vec_pretty(&state.interactions).map_err(ClientError::JSONSerialize)?;
+        std::fs::write(&self.path, file).map_err(|e| ClientError::GenericError {
+            message: format!("Failed to write cassette {}: {e}", self.path.display()),
+        })
+    }
+}
+
+fn record_request(request: &reqwest::Request, secrets: &[&str]) -> RecordedRequest {
+    let body = request.body().and_then(|body| body.as_bytes()).map(|body| {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(body) => redact_json(normalize(body), secrets),
+            Err(_) => Value::String(redact(&String::from_utf8_lossy(body), secrets)),
+        }
+    });
+    RecordedRequest {
+        method: request.method().to_string(),
+        url: redact(request.url().as_str(), secrets),
+        body,
+    }
+}
+
+fn match_key(request: &RecordedRequest) -> String {
+    format!(
+        "{} {} {}",
+        request.method,
+        request.url,
+        request
+            .body
+            .as_ref()
+            .map(Value::to_string)
+            .unwrap_or_default()
+    )
+}
+
+/// Sorts object keys so that the serialization order of the request doesn't matter.
+fn normalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let sorted: BTreeMap<String, Value> =
+                map.into_iter().map(|(k, v)| (k, normalize(v))).collect();
+            Value::Object(sorted.into_iter().collect())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(normalize).collect()),
+        value => value,
+    }
+}
+
+fn redact(value: &str, secrets: &[&str]) -> String {
+    secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(value.to_string(), |value, secret| {
+            value.replace(secret, REDACTED)
+        })
+}
+
+fn redact_json(value: Value, secrets: &[&str]) -> Value {
+    match value {
+        Value::String(s) => Value::String(redact(&s, secrets)),
+        Value::Array(items) => {
+            Value::Array(items.into_iter().map(|v| redact_json(v, secrets)).collect())
+        }
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, redact_json(v, secrets)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_replay() {
+        let path = std::env::temp_dir().join(format!("alith-cassette-{}.json", std::process::id()));
+        let client = reqwest::Client::new();
+        let request = |body: &str| {
+            client
+                .post("https://api.example.com/v1/chat?key=secret-key")
+                .body(body.to_string())
+                .build()
+                .unwrap()
+        };
+
+        let cassette = Cassette::open(&path, CassetteMode::Record).unwrap();
+        cassette
+            .record(
+                &request(r#"{"model": "m", "messages": []}"#),
+                StatusCode::OK,
+                &HeaderMap::new(),
+                br#"{"echo": "secret-key"}"#,
+                &["secret-key"],
+            )
+            .unwrap();
+        let file = std::fs::read_to_string(&path).unwrap();
+        assert!(!file.contains("secret-key"));
+
+        let cassette = Cassette::open(&path, CassetteMode::Replay).unwrap();
+        // Key order doesn't matter
+        let (status, _, body) = cassette
+            .replay(
+                &request(r#"{"messages": [], "model": "m"}"#),
+                &["secret-key"],
+            )
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_ref(), br#"{"echo":"<REDACTED>"}"#);
+        assert!(cassette
+            .replay(&request(r#"{"model": "other"}"#), &["secret-key"])
+            .is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
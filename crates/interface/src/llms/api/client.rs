@@ -1,31 +1,89 @@
 use super::error::map_serialization_error;
 use super::{
+    cassette::{Cassette, CassetteMode},
     config::ApiConfigTrait,
-    error::{map_deserialization_error, ClientError, WrappedError},
+    error::{map_deserialization_error, map_reqwest_error, ApiError, ClientError, WrappedError},
+    rate_limit::{rate_limit_reset, retry_after},
 };
 use bytes::Bytes;
+use secrecy::ExposeSecret;
 use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
 #[derive(Debug, Clone)]
 pub struct ApiClient<C: ApiConfigTrait> {
     http_client: reqwest::Client,
     pub config: C,
     pub backoff: backoff::ExponentialBackoff,
+    /// Records or replays the HTTP interactions, see [Cassette::from_env].
+    pub cassette: Option<Arc<Cassette>>,
 }
 
 impl<C: ApiConfigTrait> ApiClient<C> {
-    pub fn new(config: C) -> Self {
-        Self {
-            http_client: reqwest::Client::new(),
+    /// Builds the HTTP client from the [HttpConfig] of the config, failing on an invalid proxy.
+    ///
+    /// [HttpConfig]: super::http::HttpConfig
+    pub fn new(config: C) -> Result<Self, ClientError> {
+        let http_config = config.http_config();
+        Ok(Self {
+            http_client: http_config.build_client()?,
+            backoff: http_config.backoff.build(),
+            cassette: Cassette::from_env(&config.url("")),
             config,
-            backoff: backoff::ExponentialBackoffBuilder::new()
-                .with_max_elapsed_time(Some(std::time::Duration::from_secs(60)))
-                .build(),
-        }
+        })
+    }
+
+    /// Record or replay the requests of this client with the given cassette.
+    pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
     }
 
     /// Make a POST request to {path} and deserialize the response body
+    ///
+    /// When a rate limiter is configured, the request is counted as roughly one token
+    /// per four bytes of its body.
     pub async fn post<I, O>(&self, path: &str, request: I) -> Result<O, ClientError>
+    where
+        I: Serialize + std::fmt::Debug,
+        O: DeserializeOwned,
+    {
+        self.post_with_cancellation(path, request, None).await
+    }
+
+    /// Make a POST request to {path}, aborted with [ClientError::Cancelled] once `cancel` is
+    /// cancelled, including while waiting to retry
+    pub async fn post_with_cancellation<I, O>(
+        &self,
+        path: &str,
+        request: I,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<O, ClientError>
+    where
+        I: Serialize + std::fmt::Debug,
+        O: DeserializeOwned,
+    {
+        let tokens = match self.config.rate_limiter() {
+            Some(limiter) if limiter.tokens_per_minute().is_some() => {
+                serde_json::to_vec(&request)
+                    .map_err(map_serialization_error)?
+                    .len() as u64
+                    / 4
+            }
+            _ => 0,
+        };
+        self.post_with_tokens(path, request, tokens, cancel).await
+    }
+
+    /// Make a POST request to {path} counting `tokens` against the tokens per minute limit
+    pub async fn post_with_tokens<I, O>(
+        &self,
+        path: &str,
+        request: I,
+        tokens: u64,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<O, ClientError>
     where
         I: Serialize + std::fmt::Debug,
         O: DeserializeOwned,
@@ -45,7 +103,40 @@ impl<C: ApiConfigTrait> ApiClient<C> {
             // crate::trace!("Serialized post request: {:?}", request_builder); // This will log API keys!
             Ok(request_builder.build()?)
         };
-        self.execute(request_maker).await
+        self.execute(request_maker, tokens, cancel).await
+    }
+
+    /// Make a multipart POST request to {path} and deserialize the response body
+    ///
+    /// The form is made again for every retry, e.g. to upload a file.
+    pub async fn post_form<O, F>(&self, path: &str, form_maker: F) -> Result<O, ClientError>
+    where
+        O: DeserializeOwned,
+        F: Fn() -> reqwest::multipart::Form,
+    {
+        let request_maker = || async {
+            crate::trace!("Multipart post request: {}", path);
+            let request_builder = self
+                .http_client
+                .post(self.config.url(path))
+                .headers(self.config.headers())
+                .multipart(form_maker());
+            Ok(request_builder.build()?)
+        };
+        self.execute(request_maker, 0, None).await
+    }
+
+    /// Make a GET request to {path} and return the raw response body, e.g. a JSONL file
+    pub async fn get_bytes(&self, path: &str) -> Result<Bytes, ClientError> {
+        let request_maker = || async {
+            crate::trace!("Get request: {}", path);
+            let request_builder = self
+                .http_client
+                .get(self.config.url(path))
+                .headers(self.config.headers());
+            Ok(request_builder.build()?)
+        };
+        self.execute_raw(request_maker, 0, None).await
     }
 
     /// Make a GET request to {path} and deserialize the response body
@@ -63,7 +154,7 @@ impl<C: ApiConfigTrait> ApiClient<C> {
             // crate::trace!("Serialized post request: {:?}", request_builder); // This will log API keys!
             Ok(request_builder.build()?)
         };
-        self.execute(request_maker).await
+        self.execute(request_maker, 0, None).await
     }
 
     /// Execute a HTTP request and retry on rate limit
@@ -71,33 +162,82 @@ impl<C: ApiConfigTrait> ApiClient<C> {
     /// request_maker serves one purpose: to be able to create request again
     /// to retry API call after getting rate limited. request_maker is async because
     /// reqwest::multipart::Form is created by async calls to read files for uploads.
-    async fn execute_raw<M, Fut>(&self, request_maker: M) -> Result<Bytes, ClientError>
+    ///
+    /// The wait before a retry honors the `Retry-After` and rate limit reset headers, and
+    /// pauses the shared rate limiter so other clients of the backend wait as well.
+    ///
+    /// Cancelling `cancel` drops the in-flight request or the wait before a retry.
+    async fn execute_raw<M, Fut>(
+        &self,
+        request_maker: M,
+        tokens: u64,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Bytes, ClientError>
     where
         M: Fn() -> Fut,
         Fut: core::future::Future<Output = Result<reqwest::Request, ClientError>>,
     {
         let client = self.http_client.clone();
+        let cassette = self.cassette.as_deref();
+        let replay = cassette.is_some_and(|c| c.mode() == CassetteMode::Replay);
+        let rate_limiter = self.config.rate_limiter().filter(|_| !replay);
+        let api_key = self.config.api_key().as_ref().map(|k| k.expose_secret());
+        let secrets: Vec<&str> = api_key.into_iter().collect();
 
-        backoff::future::retry(self.backoff.clone(), || async {
+        let retry = backoff::future::retry(self.backoff.clone(), || async {
             let request = request_maker().await.map_err(backoff::Error::Permanent)?;
-            let response = client
-                .execute(request)
-                .await
-                .map_err(ClientError::Reqwest)
-                .map_err(backoff::Error::Permanent)?;
-
-            let status = response.status();
-            let bytes = response
-                .bytes()
-                .await
-                .map_err(ClientError::Reqwest)
-                .map_err(backoff::Error::Permanent)?;
+            let (status, headers, bytes) = match cassette {
+                Some(cassette) if replay => cassette
+                    .replay(&request, &secrets)
+                    .map_err(backoff::Error::Permanent)?,
+                _ => {
+                    if let Some(rate_limiter) = rate_limiter {
+                        rate_limiter.acquire(tokens).await;
+                    }
+                    // Keep a copy to record, the body is a buffer for every API request
+                    let recorded_request = cassette.and_then(|_| request.try_clone());
+                    let response = client.execute(request).await.map_err(map_reqwest_error)?;
+
+                    let status = response.status();
+                    let headers = response.headers().clone();
+                    let bytes = response.bytes().await.map_err(map_reqwest_error)?;
+                    if let (Some(cassette), Some(request)) = (cassette, recorded_request) {
+                        if let Err(e) =
+                            cassette.record(&request, status, &headers, &bytes, &secrets)
+                        {
+                            crate::error!("Failed to record interaction: {}", e);
+                        }
+                    }
+                    (status, headers, bytes)
+                }
+            };
 
             // Deserialize response body from either error object or actual response object
             if !status.is_success() {
-                let wrapped_error: WrappedError = serde_json::from_slice(bytes.as_ref())
-                    .map_err(|e| map_deserialization_error(e, bytes.as_ref()))
-                    .map_err(backoff::Error::Permanent)?;
+                let mut wrapped_error = match serde_json::from_slice::<WrappedError>(bytes.as_ref())
+                {
+                    Ok(wrapped_error) => wrapped_error,
+                    // Gateways and proxies answer 5xx errors with plain text or html
+                    Err(e) if status.is_server_error() => {
+                        tracing::error!("failed deserialization of error: {}", e);
+                        WrappedError {
+                            error: ApiError {
+                                message: String::from_utf8_lossy(bytes.as_ref()).into_owned(),
+                                r#type: None,
+                                param: None,
+                                code: None,
+                                status: None,
+                            },
+                        }
+                    }
+                    Err(e) => {
+                        return Err(backoff::Error::Permanent(map_deserialization_error(
+                            e,
+                            bytes.as_ref(),
+                        )))
+                    }
+                };
+                wrapped_error.error.status = Some(status.as_u16());
 
                 if status.as_u16() == 429
                     // API returns 429 also when:
@@ -105,17 +245,25 @@ impl<C: ApiConfigTrait> ApiClient<C> {
                     && wrapped_error.error.r#type != Some("insufficient_quota".to_string())
                 {
                     // Rate limited retry...
-                    tracing::warn!("Rate limited: {}", wrapped_error.error.message);
+                    let retry_after = retry_after(&headers);
+                    tracing::warn!(
+                        "Rate limited, retry after {:?}: {}",
+                        retry_after,
+                        wrapped_error.error.message
+                    );
+                    if let (Some(rate_limiter), Some(retry_after)) = (rate_limiter, retry_after) {
+                        rate_limiter.pause_for(retry_after);
+                    }
                     return Err(backoff::Error::Transient {
                         err: ClientError::ApiError(wrapped_error.error),
-                        retry_after: None,
+                        retry_after,
                     });
                 } else if status.as_u16() == 503 {
                     return Err(backoff::Error::Transient {
                         err: ClientError::ServiceUnavailable {
                             message: wrapped_error.error.message,
                         },
-                        retry_after: None,
+                        retry_after: retry_after(&headers),
                     });
                 } else {
                     return Err(backoff::Error::Permanent(ClientError::ApiError(
@@ -124,9 +272,24 @@ impl<C: ApiConfigTrait> ApiClient<C> {
                 }
             }
 
+            // Wait out an exhausted limit before the next request instead of running into a 429
+            if let (Some(rate_limiter), Some(reset)) =
+                (rate_limiter, rate_limit_reset(&headers, false))
+            {
+                tracing::debug!("Rate limit exhausted, pausing for {:?}", reset);
+                rate_limiter.pause_for(reset);
+            }
+
             Ok(bytes)
-        })
-        .await
+        });
+        match cancel {
+            Some(cancel) => tokio::select! {
+                biased;
+                _ = cancel.cancelled() => Err(ClientError::Cancelled),
+                result = retry => result,
+            },
+            None => retry.await,
+        }
     }
 
     /// Execute a HTTP request and retry on rate limit
@@ -134,13 +297,18 @@ impl<C: ApiConfigTrait> ApiClient<C> {
     /// request_maker serves one purpose: to be able to create request again
     /// to retry API call after getting rate limited. request_maker is async because
     /// reqwest::multipart::Form is created by async calls to read files for uploads.
-    async fn execute<O, M, Fut>(&self, request_maker: M) -> Result<O, ClientError>
+    async fn execute<O, M, Fut>(
+        &self,
+        request_maker: M,
+        tokens: u64,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<O, ClientError>
     where
         O: DeserializeOwned,
         M: Fn() -> Fut,
         Fut: core::future::Future<Output = Result<reqwest::Request, ClientError>>,
     {
-        let bytes = self.execute_raw(request_maker).await?;
+        let bytes = self.execute_raw(request_maker, tokens, cancel).await?;
 
         // Deserialize once into a generic Value
         let value: serde_json::Value =
@@ -157,3 +325,75 @@ impl<C: ApiConfigTrait> ApiClient<C> {
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llms::api::{http::BackoffConfig, openai::OpenAIConfig};
+
+    #[tokio::test]
+    async fn test_cancel_during_backoff() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/chat/completions")
+            .with_status(429)
+            .with_header("retry-after", "30")
+            .with_body(r#"{"error": {"message": "Rate limited", "type": "requests"}}"#)
+            .create_async()
+            .await;
+        let mut config = OpenAIConfig::new();
+        config.api_config.host = server.url();
+        config.api_config.api_key = Some("test-key".to_string().into());
+        let client = ApiClient::new(config).unwrap();
+
+        let cancel = CancellationToken::new();
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            canceller.cancel();
+        });
+        let start = std::time::Instant::now();
+        let res: Result<serde_json::Value, _> = client
+            .post_with_tokens("/chat/completions", serde_json::json!({}), 0, Some(&cancel))
+            .await;
+
+        assert!(matches!(res, Err(ClientError::Cancelled)));
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+        mock.expect(1).assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_timeout_is_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/chat/completions")
+            .with_body_from_request(|_| {
+                std::thread::sleep(std::time::Duration::from_millis(300));
+                b"{}".to_vec()
+            })
+            .expect_at_least(2)
+            .create_async()
+            .await;
+        let mut config = OpenAIConfig::new();
+        config.api_config.host = server.url();
+        config.api_config.api_key = Some("test-key".to_string().into());
+        config.api_config.http.timeout = Some(std::time::Duration::from_millis(50));
+        config.api_config.http.backoff = BackoffConfig {
+            initial_interval: std::time::Duration::from_millis(10),
+            max_interval: std::time::Duration::from_millis(10),
+            multiplier: 1.0,
+            max_elapsed_time: Some(std::time::Duration::from_millis(500)),
+        };
+        let client = ApiClient::new(config).unwrap();
+
+        let res: Result<serde_json::Value, _> = client
+            .post("/chat/completions", serde_json::json!({}))
+            .await;
+
+        match res {
+            Err(e @ ClientError::Timeout(_)) => assert!(e.is_transient()),
+            res => panic!("unexpected result: {res:?}"),
+        }
+        mock.assert_async().await;
+    }
+}
@@ -0,0 +1,285 @@
+use super::{
+    client::ApiClient,
+    config::{ApiConfig, ApiConfigTrait, LLMApiConfigTrait},
+    http::HttpConfig,
+    rate_limit::RateLimiter,
+};
+use crate::requests::embeddings::{EmbeddingsData, EmbeddingsError, EmbeddingsResponse, Usage};
+use alith_devices::logging::{LoggingConfig, LoggingConfigTrait};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Default v2 API base url
+pub const COHERE_API_HOST: &str = "api.cohere.com/v2";
+/// The maximum number of texts accepted by a single embed request.
+pub const COHERE_MAX_DOCUMENTS: usize = 96;
+
+pub struct CohereBackend {
+    pub(crate) client: ApiClient<CohereConfig>,
+}
+
+impl CohereBackend {
+    pub fn new(mut config: CohereConfig) -> crate::Result<Self> {
+        config.logging_config.load_logger()?;
+        config.api_config.api_key = Some(config.api_config.load_api_key()?);
+        Ok(Self {
+            client: ApiClient::new(config)?,
+        })
+    }
+
+    pub async fn embeddings_request(
+        &self,
+        request: &CohereEmbeddingsRequest,
+    ) -> crate::Result<EmbeddingsResponse, EmbeddingsError> {
+        match self
+            .client
+            .post::<_, CohereEmbeddingsResponse>("/embed", request)
+            .await
+        {
+            Ok(res) => res.into_embeddings_response(&request.model),
+            Err(e) => Err(EmbeddingsError::ClientError(e)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CohereEmbeddingsRequest {
+    /// The identifier of the model, e.g. `embed-english-v3.0`.
+    pub model: String,
+    /// An array of strings for the model to embed, at most [COHERE_MAX_DOCUMENTS] items.
+    pub texts: Vec<String>,
+    /// Specifies the type of input passed to the model. Required for embedding models v3 and higher.
+    pub input_type: CohereInputType,
+    /// Specifies the types of embeddings to get back. Only `float` is used by alith.
+    pub embedding_types: Vec<String>,
+    /// Specifies how the API will handle inputs longer than the maximum token length.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncate: Option<CohereTruncate>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CohereInputType {
+    /// Used for embeddings stored in a vector database for search use-cases.
+    #[default]
+    SearchDocument,
+    /// Used for embeddings of search queries run against a vector DB to find relevant documents.
+    SearchQuery,
+    /// Used for embeddings passed through a text classifier.
+    Classification,
+    /// Used for the embeddings run through a clustering algorithm.
+    Clustering,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CohereTruncate {
+    /// Return an error when the input exceeds the maximum input token length.
+    None,
+    /// Discard the start of the input.
+    Start,
+    /// Discard the end of the input.
+    End,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CohereEmbeddingsResponse {
+    pub id: String,
+    pub embeddings: CohereEmbeddingsByType,
+    #[serde(default)]
+    pub texts: Vec<String>,
+    pub meta: Option<CohereMeta>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CohereEmbeddingsByType {
+    pub float: Option<Vec<Vec<f64>>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CohereMeta {
+    pub billed_units: Option<CohereBilledUnits>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CohereBilledUnits {
+    pub input_tokens: Option<usize>,
+}
+
+impl CohereEmbeddingsResponse {
+    fn into_embeddings_response(
+        self,
+        model: &str,
+    ) -> crate::Result<EmbeddingsResponse, EmbeddingsError> {
+        let tokens = self
+            .meta
+            .and_then(|m| m.billed_units)
+            .and_then(|b| b.input_tokens)
+            .unwrap_or_default();
+        let embeddings = self
+            .embeddings
+            .float
+            .ok_or(EmbeddingsError::ResponseContentEmpty)?;
+        Ok(EmbeddingsResponse {
+            object: "list".to_string(),
+            data: embeddings
+                .into_iter()
+                .enumerate()
+                .map(|(index, embedding)| EmbeddingsData {
+                    object: "embedding".to_string(),
+                    embedding,
+                    index,
+                })
+                .collect(),
+            model: model.to_string(),
+            usage: Usage {
+                prompt_tokens: tokens,
+                total_tokens: tokens,
+            },
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CohereConfig {
+    pub api_config: ApiConfig,
+    pub logging_config: LoggingConfig,
+}
+
+impl Default for CohereConfig {
+    fn default() -> Self {
+        Self {
+            api_config: ApiConfig {
+                host: COHERE_API_HOST.to_string(),
+                port: None,
+                api_key: None,
+                api_key_env_var: "COHERE_API_KEY".to_string(),
+                rate_limiter: None,
+                http: Default::default(),
+            },
+            logging_config: LoggingConfig {
+                logger_name: "cohere".to_string(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl CohereConfig {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl ApiConfigTrait for CohereConfig {
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(api_key) = self.api_key() {
+            if let Ok(header_value) =
+                HeaderValue::from_str(&format!("Bearer {}", api_key.expose_secret()))
+            {
+                headers.insert(AUTHORIZATION, header_value);
+            } else {
+                crate::error!("Failed to create header value from authorization value");
+            }
+        }
+
+        headers
+    }
+
+    fn url(&self, path: &str) -> String {
+        if self.api_config.host.starts_with("http") {
+            if let Some(port) = &self.api_config.port {
+                format!("{}:{}{}", self.api_config.host, port, path)
+            } else {
+                format!("{}{}", self.api_config.host, path)
+            }
+        } else {
+            format!("https://{}{}", self.api_config.host, path)
+        }
+    }
+
+    fn api_key(&self) -> &Option<SecretString> {
+        &self.api_config.api_key
+    }
+
+    fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.api_config.rate_limiter.as_ref()
+    }
+
+    fn http_config(&self) -> &HttpConfig {
+        &self.api_config.http
+    }
+}
+
+impl LLMApiConfigTrait for CohereConfig {
+    fn api_base_config_mut(&mut self) -> &mut ApiConfig {
+        &mut self.api_config
+    }
+
+    fn api_config(&self) -> &ApiConfig {
+        &self.api_config
+    }
+}
+
+impl LoggingConfigTrait for CohereConfig {
+    fn logging_config_mut(&mut self) -> &mut LoggingConfig {
+        &mut self.logging_config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cohere_embeddings_request() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/embed")
+            .match_header("authorization", "Bearer test-key")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "embed-english-v3.0",
+                "texts": ["hello", "world"],
+                "input_type": "search_query",
+                "embedding_types": ["float"],
+                "truncate": "END",
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "id": "abc",
+                    "embeddings": {"float": [[0.1, 0.2], [0.3, 0.4]]},
+                    "texts": ["hello", "world"],
+                    "meta": {"billed_units": {"input_tokens": 2}}
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        let config = CohereConfig::new()
+            .with_api_host(server.url())
+            .with_api_key("test-key")
+            .logging_enabled(false);
+        let backend = CohereBackend::new(config).unwrap();
+        let res = backend
+            .embeddings_request(&CohereEmbeddingsRequest {
+                model: "embed-english-v3.0".to_string(),
+                texts: vec!["hello".to_string(), "world".to_string()],
+                input_type: CohereInputType::SearchQuery,
+                embedding_types: vec!["float".to_string()],
+                truncate: Some(CohereTruncate::End),
+            })
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(res.data.len(), 2);
+        assert_eq!(res.data[1].index, 1);
+        assert_eq!(res.data[1].embedding, vec![0.3, 0.4]);
+        assert_eq!(res.usage.total_tokens, 2);
+    }
+}
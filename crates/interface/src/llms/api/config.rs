@@ -1,5 +1,11 @@
+use super::{
+    cassette::{CassetteMode, REDACTED},
+    http::{BackoffConfig, HttpConfig},
+    rate_limit::RateLimiter,
+};
 use reqwest::header::HeaderMap;
 use secrecy::SecretString;
+use std::{sync::Arc, time::Duration};
 
 #[derive(Clone, Debug)]
 pub struct ApiConfig {
@@ -7,6 +13,10 @@ pub struct ApiConfig {
     pub port: Option<String>,
     pub api_key: Option<SecretString>,
     pub api_key_env_var: String,
+    /// Optional client side requests/min and tokens/min limits shared by every client using this config.
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// The timeouts, proxy, connection pool, user agent and retry backoff of the HTTP client.
+    pub http: HttpConfig,
 }
 
 impl ApiConfig {
@@ -23,6 +33,10 @@ impl ApiConfig {
                 crate::trace!("Successfully loaded api_key from .env");
                 Ok(api_key.into())
             }
+            Err(_) if CassetteMode::from_env() == CassetteMode::Replay => {
+                crate::trace!("api_key not set, using a placeholder to replay the cassette");
+                Ok(REDACTED.to_string().into())
+            }
             Err(_) => {
                 crate::trace!(
                     "{} not found in dotenv, nor was it set manually",
@@ -71,6 +85,99 @@ pub trait LLMApiConfigTrait {
         self.api_base_config_mut().api_key_env_var = api_key_env_var.into();
         self
     }
+
+    /// Limit the requests and tokens sent per minute. `None` leaves that limit unbounded.
+    fn with_rate_limits(
+        mut self,
+        requests_per_minute: Option<u32>,
+        tokens_per_minute: Option<u32>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().rate_limiter = Some(Arc::new(RateLimiter::new(
+            requests_per_minute,
+            tokens_per_minute,
+        )));
+        self
+    }
+
+    /// Share an existing rate limiter, e.g. between backends using the same API key.
+    fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// The timeout to establish a connection, `None` to wait forever. Default is 30 seconds.
+    fn with_connect_timeout(mut self, timeout: Option<Duration>) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().http.connect_timeout = timeout;
+        self
+    }
+
+    /// The timeout of each read of a response, `None` to wait forever. Default is 10 minutes.
+    fn with_read_timeout(mut self, timeout: Option<Duration>) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().http.read_timeout = timeout;
+        self
+    }
+
+    /// The total timeout of each attempt of a request, retried like rate limits. Default is none.
+    fn with_timeout(mut self, timeout: Option<Duration>) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().http.timeout = timeout;
+        self
+    }
+
+    /// Send every request through the HTTP(S) proxy, e.g. `http://proxy:8080`.
+    fn with_proxy<S: Into<String>>(mut self, proxy: S) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().http.proxy = Some(proxy.into());
+        self
+    }
+
+    /// How long idle connections are kept, and how many of them per host.
+    fn with_pool_idle(
+        mut self,
+        idle_timeout: Option<Duration>,
+        max_idle_per_host: Option<usize>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        let http = &mut self.api_base_config_mut().http;
+        http.pool_idle_timeout = idle_timeout;
+        http.pool_max_idle_per_host = max_idle_per_host;
+        self
+    }
+
+    fn with_user_agent<S: Into<String>>(mut self, user_agent: S) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().http.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// The backoff between retries. Default retries for up to 60 seconds.
+    fn with_backoff(mut self, backoff: BackoffConfig) -> Self
+    where
+        Self: Sized,
+    {
+        self.api_base_config_mut().http.backoff = backoff;
+        self
+    }
 }
 
 pub trait ApiConfigTrait {
@@ -79,4 +186,8 @@ pub trait ApiConfigTrait {
     fn url(&self, path: &str) -> String;
 
     fn api_key(&self) -> &Option<SecretString>;
+
+    fn rate_limiter(&self) -> Option<&Arc<RateLimiter>>;
+
+    fn http_config(&self) -> &HttpConfig;
 }
@@ -20,6 +20,13 @@ pub enum ClientError {
     /// Error when a response cannot be deserialized into a Rust type
     #[error("failed to deserialize api response: {0}")]
     JSONDeserialize(serde_json::Error),
+    /// The request was cancelled with its cancellation token
+    #[error("request cancelled")]
+    Cancelled,
+    /// The connection, a read of the response or the whole attempt timed out, after the
+    /// retries of the backoff
+    #[error("request timed out: {0}")]
+    Timeout(reqwest::Error),
     /// Error from client side validation
     /// or when builder fails to build request before making API call
     #[error("invalid args: {0}")]
@@ -40,6 +47,19 @@ pub(crate) fn map_deserialization_error(e: serde_json::Error, bytes: &[u8]) -> C
     ClientError::JSONDeserialize(e)
 }
 
+/// Timeouts are retried with the backoff, other transport errors are permanent.
+pub(crate) fn map_reqwest_error(e: reqwest::Error) -> backoff::Error<ClientError> {
+    if e.is_timeout() {
+        tracing::warn!("Request timed out: {}", e);
+        backoff::Error::Transient {
+            err: ClientError::Timeout(e),
+            retry_after: None,
+        }
+    } else {
+        backoff::Error::Permanent(ClientError::Reqwest(e))
+    }
+}
+
 pub(crate) fn map_serialization_error(e: serde_json::Error) -> ClientError {
     tracing::error!("failed serialization: {}", e);
     ClientError::JSONSerialize(e)
@@ -51,4 +71,49 @@ pub struct ApiError {
     pub r#type: Option<String>,
     pub param: Option<String>,
     pub code: Option<String>,
+    /// The HTTP status code of the response
+    #[serde(skip)]
+    pub status: Option<u16>,
+}
+
+impl ClientError {
+    /// The HTTP status code of the failed response, if the API responded.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::Reqwest(e) => e.status().map(|s| s.as_u16()),
+            ClientError::ApiError(e) => e.status,
+            ClientError::ServiceUnavailable { .. } => Some(503),
+            _ => None,
+        }
+    }
+
+    /// Whether the error is caused by the provider rather than the request, i.e. rate limits,
+    /// server errors, timeouts and connection failures. Another provider may succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ClientError::Reqwest(e) => {
+                e.is_timeout() || e.is_connect() || e.status().is_some_and(|s| s.is_server_error())
+            }
+            ClientError::ApiError(e) => match e.status {
+                Some(429) => e.r#type.as_deref() != Some("insufficient_quota"),
+                Some(status) => status == 408 || status >= 500,
+                None => false,
+            },
+            ClientError::ServiceUnavailable { .. } | ClientError::Timeout(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the provider rejected the request because the quota of the account is spent.
+    /// Retrying the provider is pointless, but another provider may succeed.
+    pub fn is_quota_exhausted(&self) -> bool {
+        match self {
+            ClientError::ApiError(e) => {
+                e.status == Some(429)
+                    && (e.r#type.as_deref() == Some("insufficient_quota")
+                        || e.code.as_deref() == Some("insufficient_quota"))
+            }
+            _ => false,
+        }
+    }
 }
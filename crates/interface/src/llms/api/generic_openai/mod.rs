@@ -1,7 +1,9 @@
 use super::{
     client::ApiClient,
     config::{ApiConfig, ApiConfigTrait},
+    http::HttpConfig,
     openai::completion::OpenAICompletionRequest,
+    rate_limit::RateLimiter,
 };
 use crate::requests::{
     completion::{
@@ -14,6 +16,7 @@ use alith_models::api_model::ApiLLMModel;
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 use secrecy::{ExposeSecret, SecretString};
 use serde_json::json;
+use std::sync::Arc;
 
 pub struct GenericApiBackend {
     pub(crate) client: ApiClient<GenericApiConfig>,
@@ -27,7 +30,7 @@ impl GenericApiBackend {
             config.api_config.api_key = Some(api_key);
         }
         Ok(Self {
-            client: ApiClient::new(config),
+            client: ApiClient::new(config)?,
             model,
         })
     }
@@ -38,9 +41,11 @@ impl GenericApiBackend {
     ) -> crate::Result<CompletionResponse, CompletionError> {
         match self
             .client
-            .post(
+            .post_with_tokens(
                 &self.client.config.completion_path,
                 OpenAICompletionRequest::new(request)?,
+                request.estimated_tokens(),
+                request.cancellation_token.as_ref(),
             )
             .await
         {
@@ -55,12 +60,13 @@ impl GenericApiBackend {
     ) -> crate::Result<EmbeddingsResponse, EmbeddingsError> {
         match self
             .client
-            .post(
+            .post_with_cancellation(
                 "/embeddings",
                 json!({
                     "input": request.input,
                     "model": request.model,
                 }),
+                request.cancellation_token.as_ref(),
             )
             .await
         {
@@ -85,6 +91,8 @@ impl Default for GenericApiConfig {
                 port: None,
                 api_key: None,
                 api_key_env_var: Default::default(),
+                rate_limiter: None,
+                http: Default::default(),
             },
             logging_config: LoggingConfig {
                 logger_name: "generic".to_string(),
@@ -133,4 +141,12 @@ impl ApiConfigTrait for GenericApiConfig {
     fn api_key(&self) -> &Option<SecretString> {
         &self.api_config.api_key
     }
+
+    fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.api_config.rate_limiter.as_ref()
+    }
+
+    fn http_config(&self) -> &HttpConfig {
+        &self.api_config.http
+    }
 }
@@ -0,0 +1,107 @@
+use super::error::ClientError;
+use std::time::Duration;
+
+/// The default time to establish a connection.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// The default time to wait for the next read of a response, so a hung connection fails
+/// instead of blocking forever. Reasoning models may think for minutes before answering.
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The HTTP client settings of an API client.
+#[derive(Clone, Debug)]
+pub struct HttpConfig {
+    /// The timeout to establish a connection.
+    pub connect_timeout: Option<Duration>,
+    /// The timeout of each read of a response.
+    pub read_timeout: Option<Duration>,
+    /// The timeout of each attempt, from the connection to the end of the response.
+    pub timeout: Option<Duration>,
+    /// The URL of the HTTP(S) proxy of every request, e.g. `http://proxy:8080`.
+    pub proxy: Option<String>,
+    /// How long idle connections are kept in the pool.
+    pub pool_idle_timeout: Option<Duration>,
+    /// The maximum number of idle connections kept per host.
+    pub pool_max_idle_per_host: Option<usize>,
+    /// The `User-Agent` header of every request.
+    pub user_agent: Option<String>,
+    pub backoff: BackoffConfig,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
+            read_timeout: Some(DEFAULT_READ_TIMEOUT),
+            timeout: None,
+            proxy: None,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            user_agent: None,
+            backoff: BackoffConfig::default(),
+        }
+    }
+}
+
+impl HttpConfig {
+    pub(crate) fn build_client(&self) -> Result<reqwest::Client, ClientError> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(read_timeout) = self.read_timeout {
+            builder = builder.read_timeout(read_timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        if let Some(pool_idle_timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(pool_idle_timeout);
+        }
+        if let Some(pool_max_idle_per_host) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(pool_max_idle_per_host);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(builder.build()?)
+    }
+}
+
+/// The exponential backoff between the retries of rate limited, unavailable and timed out
+/// requests.
+#[derive(Clone, Debug)]
+pub struct BackoffConfig {
+    /// The wait before the first retry.
+    pub initial_interval: Duration,
+    /// The maximum wait between two retries.
+    pub max_interval: Duration,
+    /// The factor the wait grows by after each retry.
+    pub multiplier: f64,
+    /// The time after which the request stops being retried, `None` to retry forever.
+    pub max_elapsed_time: Option<Duration>,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial_interval: Duration::from_millis(backoff::default::INITIAL_INTERVAL_MILLIS),
+            max_interval: Duration::from_millis(backoff::default::MAX_INTERVAL_MILLIS),
+            multiplier: backoff::default::MULTIPLIER,
+            max_elapsed_time: Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+impl BackoffConfig {
+    pub(crate) fn build(&self) -> backoff::ExponentialBackoff {
+        backoff::ExponentialBackoffBuilder::new()
+            .with_initial_interval(self.initial_interval)
+            .with_max_interval(self.max_interval)
+            .with_multiplier(self.multiplier)
+            .with_max_elapsed_time(self.max_elapsed_time)
+            .build()
+    }
+}
@@ -0,0 +1,213 @@
+use super::{
+    client::ApiClient,
+    config::{ApiConfig, ApiConfigTrait, LLMApiConfigTrait},
+    http::HttpConfig,
+    rate_limit::RateLimiter,
+};
+use crate::requests::embeddings::{EmbeddingsError, EmbeddingsResponse};
+use alith_devices::logging::{LoggingConfig, LoggingConfigTrait};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Default v1 API base url
+pub const JINA_API_HOST: &str = "api.jina.ai/v1";
+/// The maximum number of texts accepted by a single embeddings request.
+pub const JINA_MAX_DOCUMENTS: usize = 2048;
+
+pub struct JinaBackend {
+    pub(crate) client: ApiClient<JinaConfig>,
+}
+
+impl JinaBackend {
+    pub fn new(mut config: JinaConfig) -> crate::Result<Self> {
+        config.logging_config.load_logger()?;
+        config.api_config.api_key = Some(config.api_config.load_api_key()?);
+        Ok(Self {
+            client: ApiClient::new(config)?,
+        })
+    }
+
+    pub async fn embeddings_request(
+        &self,
+        request: &JinaEmbeddingsRequest,
+    ) -> crate::Result<EmbeddingsResponse, EmbeddingsError> {
+        // Jina responds with the OpenAI embeddings format.
+        match self.client.post("/embeddings", request).await {
+            Ok(res) => Ok(res),
+            Err(e) => Err(EmbeddingsError::ClientError(e)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JinaEmbeddingsRequest {
+    /// The identifier of the model, e.g. `jina-embeddings-v3`.
+    pub model: String,
+    /// A list of texts to embed, at most [JINA_MAX_DOCUMENTS] items.
+    pub input: Vec<String>,
+    /// The downstream task the embeddings are optimized for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<JinaTask>,
+    /// Whether to truncate inputs that exceed the context length instead of returning an error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncate: Option<bool>,
+    /// Truncates the output embeddings to the given number of dimensions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum JinaTask {
+    #[serde(rename = "retrieval.query")]
+    RetrievalQuery,
+    #[serde(rename = "retrieval.passage")]
+    RetrievalPassage,
+    #[serde(rename = "text-matching")]
+    TextMatching,
+    #[serde(rename = "classification")]
+    Classification,
+    #[serde(rename = "separation")]
+    Separation,
+}
+
+#[derive(Clone, Debug)]
+pub struct JinaConfig {
+    pub api_config: ApiConfig,
+    pub logging_config: LoggingConfig,
+}
+
+impl Default for JinaConfig {
+    fn default() -> Self {
+        Self {
+            api_config: ApiConfig {
+                host: JINA_API_HOST.to_string(),
+                port: None,
+                api_key: None,
+                api_key_env_var: "JINA_API_KEY".to_string(),
+                rate_limiter: None,
+                http: Default::default(),
+            },
+            logging_config: LoggingConfig {
+                logger_name: "jina".to_string(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl JinaConfig {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl ApiConfigTrait for JinaConfig {
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(api_key) = self.api_key() {
+            if let Ok(header_value) =
+                HeaderValue::from_str(&format!("Bearer {}", api_key.expose_secret()))
+            {
+                headers.insert(AUTHORIZATION, header_value);
+            } else {
+                crate::error!("Failed to create header value from authorization value");
+            }
+        }
+
+        headers
+    }
+
+    fn url(&self, path: &str) -> String {
+        if self.api_config.host.starts_with("http") {
+            if let Some(port) = &self.api_config.port {
+                format!("{}:{}{}", self.api_config.host, port, path)
+            } else {
+                format!("{}{}", self.api_config.host, path)
+            }
+        } else {
+            format!("https://{}{}", self.api_config.host, path)
+        }
+    }
+
+    fn api_key(&self) -> &Option<SecretString> {
+        &self.api_config.api_key
+    }
+
+    fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.api_config.rate_limiter.as_ref()
+    }
+
+    fn http_config(&self) -> &HttpConfig {
+        &self.api_config.http
+    }
+}
+
+impl LLMApiConfigTrait for JinaConfig {
+    fn api_base_config_mut(&mut self) -> &mut ApiConfig {
+        &mut self.api_config
+    }
+
+    fn api_config(&self) -> &ApiConfig {
+        &self.api_config
+    }
+}
+
+impl LoggingConfigTrait for JinaConfig {
+    fn logging_config_mut(&mut self) -> &mut LoggingConfig {
+        &mut self.logging_config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_jina_embeddings_request() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/embeddings")
+            .match_header("authorization", "Bearer test-key")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "model": "jina-embeddings-v3",
+                "input": ["hello"],
+                "task": "retrieval.query",
+                "truncate": true,
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "object": "list",
+                    "data": [{"object": "embedding", "embedding": [0.5, 0.6], "index": 0}],
+                    "model": "jina-embeddings-v3",
+                    "usage": {"prompt_tokens": 3, "total_tokens": 3}
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        let config = JinaConfig::new()
+            .with_api_host(server.url())
+            .with_api_key("test-key")
+            .logging_enabled(false);
+        let backend = JinaBackend::new(config).unwrap();
+        let res = backend
+            .embeddings_request(&JinaEmbeddingsRequest {
+                model: "jina-embeddings-v3".to_string(),
+                input: vec!["hello".to_string()],
+                task: Some(JinaTask::RetrievalQuery),
+                truncate: Some(true),
+                dimensions: None,
+            })
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(res.data.len(), 1);
+        assert_eq!(res.data[0].embedding, vec![0.5, 0.6]);
+        assert_eq!(res.usage.total_tokens, 3);
+    }
+}
@@ -1,7 +1,13 @@
 pub mod anthropic;
+pub mod cassette;
 pub mod client;
+pub mod cohere;
 pub mod config;
 pub mod error;
 pub mod generic_openai;
+pub mod http;
+pub mod jina;
 pub mod openai;
 pub mod perplexity;
+pub mod rate_limit;
+pub mod voyage;
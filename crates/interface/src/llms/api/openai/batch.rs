@@ -0,0 +1,105 @@
+use super::{completion::OpenAICompletionRequest, OpenAIBackend};
+use crate::llms::api::error::ClientError;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// The endpoint of the batched requests.
+pub const OPENAI_BATCH_ENDPOINT: &str = "/v1/chat/completions";
+/// The time frame within which the batch should be processed.
+pub const OPENAI_BATCH_COMPLETION_WINDOW: &str = "24h";
+
+/// A line of the JSONL input file of a batch.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OpenAIBatchRequestLine {
+    pub custom_id: String,
+    pub method: String,
+    pub url: String,
+    pub body: OpenAICompletionRequest,
+}
+
+impl OpenAIBatchRequestLine {
+    pub fn new(custom_id: String, body: OpenAICompletionRequest) -> Self {
+        Self {
+            custom_id,
+            method: "POST".to_string(),
+            url: OPENAI_BATCH_ENDPOINT.to_string(),
+            body,
+        }
+    }
+}
+
+/// An uploaded file.
+#[derive(Debug, Deserialize, Clone)]
+pub struct OpenAIFile {
+    pub id: String,
+}
+
+/// A batch object, returned when a batch is created and retrieved.
+#[derive(Debug, Deserialize, Clone)]
+pub struct OpenAIBatch {
+    pub id: String,
+    /// One of `validating`, `failed`, `in_progress`, `finalizing`, `completed`, `expired`,
+    /// `cancelling` or `cancelled`.
+    pub status: String,
+    /// The file with the results of the successful requests.
+    pub output_file_id: Option<String>,
+    /// The file with the results of the failed requests.
+    pub error_file_id: Option<String>,
+}
+
+/// A line of the JSONL output and error files of a batch.
+#[derive(Debug, Deserialize, Clone)]
+pub struct OpenAIBatchResultLine {
+    pub custom_id: String,
+    pub response: Option<OpenAIBatchResponse>,
+    pub error: Option<OpenAIBatchError>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OpenAIBatchResponse {
+    pub status_code: u16,
+    pub body: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OpenAIBatchError {
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl OpenAIBackend {
+    /// Uploads the JSONL input file and creates a batch from it.
+    pub(crate) async fn batch_create(&self, jsonl: String) -> Result<OpenAIBatch, ClientError> {
+        let file: OpenAIFile = self
+            .client
+            .post_form("/files", || {
+                let part = reqwest::multipart::Part::bytes(jsonl.clone().into_bytes())
+                    .file_name("batch.jsonl");
+                reqwest::multipart::Form::new()
+                    .text("purpose", "batch")
+                    .part("file", part)
+            })
+            .await?;
+        self.client
+            .post(
+                "/batches",
+                json!({
+                    "input_file_id": file.id,
+                    "endpoint": OPENAI_BATCH_ENDPOINT,
+                    "completion_window": OPENAI_BATCH_COMPLETION_WINDOW,
+                }),
+            )
+            .await
+    }
+
+    pub(crate) async fn batch_retrieve(&self, batch_id: &str) -> Result<OpenAIBatch, ClientError> {
+        self.client.get(&format!("/batches/{batch_id}")).await
+    }
+
+    pub(crate) async fn batch_file_content(&self, file_id: &str) -> Result<Bytes, ClientError> {
+        self.client
+            .get_bytes(&format!("/files/{file_id}/content"))
+            .await
+    }
+}
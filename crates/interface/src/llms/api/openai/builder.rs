@@ -1,4 +1,7 @@
-use super::{OpenAIBackend, OpenAIConfig};
+use super::{
+    responses::{OpenAIBuiltInTool, OpenAIResponsesBackend},
+    OpenAIBackend, OpenAIConfig,
+};
 use crate::llms::{
     api::config::{ApiConfig, LLMApiConfigTrait},
     LLMBackend,
@@ -10,6 +13,10 @@ use std::sync::Arc;
 pub struct OpenAIBackendBuilder {
     pub config: OpenAIConfig,
     pub model: ApiLLMModel,
+    /// Sends the requests to the Responses API instead of the chat completions.
+    pub responses_api: bool,
+    /// The built-in tools of the Responses API sent with every request.
+    pub built_in_tools: Vec<OpenAIBuiltInTool>,
 }
 
 impl Default for OpenAIBackendBuilder {
@@ -17,17 +24,37 @@ impl Default for OpenAIBackendBuilder {
         Self {
             config: Default::default(),
             model: ApiLLMModel::gpt_4_o_mini(),
+            responses_api: false,
+            built_in_tools: Vec::new(),
         }
     }
 }
 
 impl OpenAIBackendBuilder {
     pub fn init(self) -> crate::Result<Arc<LLMBackend>> {
+        if self.responses_api {
+            return Ok(Arc::new(LLMBackend::OpenAIResponses(
+                OpenAIResponsesBackend::new(self.config, self.model, self.built_in_tools)?,
+            )));
+        }
         Ok(Arc::new(LLMBackend::OpenAI(OpenAIBackend::new(
             self.config,
             self.model,
         )?)))
     }
+
+    /// Sends the requests to the Responses API instead of the chat completions.
+    pub fn responses_api(mut self) -> Self {
+        self.responses_api = true;
+        self
+    }
+
+    /// Adds a built-in tool, e.g. the web search, and sends the requests to the Responses API.
+    pub fn built_in_tool(mut self, tool: OpenAIBuiltInTool) -> Self {
+        self.responses_api = true;
+        self.built_in_tools.push(tool);
+        self
+    }
 }
 
 impl LLMApiConfigTrait for OpenAIBackendBuilder {
@@ -1,4 +1,4 @@
 mod req;
 mod res;
-pub use req::OpenAICompletionRequest;
+pub use req::{CompletionRequestMessage, OpenAICompletionRequest};
 pub use res::OpenAICompletionResponse;
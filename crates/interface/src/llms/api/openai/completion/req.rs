@@ -1,4 +1,6 @@
-use crate::requests::{completion::*, stop_sequence::StopSequences};
+use crate::requests::{
+    completion::*, req_components::ReasoningEffort, stop_sequence::StopSequences,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -28,12 +30,26 @@ pub struct OpenAICompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_logprobs: Option<u8>,
 
+    /// How many chat completion choices to generate for each input message, default: 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u8>,
+
     /// The maximum number of [tokens](https://platform.openai.com/tokenizer) that can be generated in the chat completion.
     ///
     /// The total length of input tokens and generated tokens is limited by the model's context length. [Example Python code](https://cookbook.openai.com/examples/how_to_count_tokens_with_tiktoken) for counting tokens.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u64>,
 
+    /// An upper bound for the number of tokens that can be generated for a completion, including visible output tokens and reasoning tokens.
+    ///
+    /// Replaces `max_tokens` for reasoning models.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u64>,
+
+    /// Constrains the effort on reasoning for reasoning models, default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+
     /// min: 0.0, max: 2.0, default: None
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
@@ -64,7 +80,15 @@ pub struct OpenAICompletionRequest {
 
     /// The tool choice for the request, default: None
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tool_choice: Option<String>,
+    pub tool_choice: Option<OpenAIToolChoice>,
+
+    /// Whether to enable parallel function calling during tool use, default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+
+    /// An object specifying the format that the model must output, default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
 }
 
 #[derive(Clone, Serialize, Debug, Deserialize)]
@@ -73,30 +97,89 @@ pub struct OpenAIToolDefinition {
     pub function: ToolDefinition,
 }
 
+/// Controls which (if any) tool is called by the model.
+///
+/// `"none"`, `"auto"` and `"required"` select a mode, while a named function forces the model
+/// to call that tool.
+#[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum OpenAIToolChoice {
+    Mode(String),
+    Named {
+        r#type: String,
+        function: OpenAIFunctionName,
+    },
+}
+
+#[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+pub struct OpenAIFunctionName {
+    pub name: String,
+}
+
+impl From<&ToolChoice> for OpenAIToolChoice {
+    fn from(tool_choice: &ToolChoice) -> Self {
+        match tool_choice {
+            ToolChoice::Auto => Self::Mode("auto".to_string()),
+            ToolChoice::Any => Self::Mode("required".to_string()),
+            ToolChoice::None => Self::Mode("none".to_string()),
+            ToolChoice::Tool { name } => Self::Named {
+                r#type: "function".to_string(),
+                function: OpenAIFunctionName { name: name.clone() },
+            },
+        }
+    }
+}
+
 impl OpenAICompletionRequest {
     pub fn new(req: &CompletionRequest) -> crate::Result<Self, CompletionError> {
+        let capabilities = &req.backend.api_model().capabilities;
         let mut messages = Vec::new();
         match &req.prompt.get_built_prompt_messages() {
             Ok(prompt_message) => {
                 for m in prompt_message {
-                    messages.push(CompletionRequestMessage::new(m)?);
+                    let mut message = CompletionRequestMessage::new(m)?;
+                    if message.role == "system" && !capabilities.system_messages {
+                        message.role = "user".to_string();
+                    }
+                    messages.push(message);
                 }
             }
             Err(e) => return Err(CompletionError::RequestBuilderError(e.to_string())),
         }
+        // Sampling parameters are rejected by reasoning models.
+        let sampling = capabilities.sampling;
 
         Ok(OpenAICompletionRequest {
             messages,
             model: req.backend.model_id().to_owned(),
-            logit_bias: req.logit_bias.as_ref().and_then(|lb| lb.get_openai()),
-            frequency_penalty: req.config.frequency_penalty,
-            logprobs: None,
-            top_logprobs: None,
-            max_tokens: req.config.actual_request_tokens,
-            presence_penalty: Some(req.config.presence_penalty),
+            logit_bias: req
+                .logit_bias
+                .as_ref()
+                .filter(|_| sampling)
+                .and_then(|lb| lb.get_openai()),
+            frequency_penalty: req.config.frequency_penalty.filter(|_| sampling),
+            logprobs: (sampling && req.config.logprobs).then_some(true),
+            top_logprobs: req
+                .config
+                .top_logprobs
+                .filter(|_| sampling && req.config.logprobs),
+            n: (req.config.n_choices > 1).then_some(req.config.n_choices),
+            max_tokens: req
+                .config
+                .actual_request_tokens
+                .filter(|_| !capabilities.reasoning),
+            max_completion_tokens: req
+                .config
+                .actual_request_tokens
+                .filter(|_| capabilities.reasoning),
+            reasoning_effort: req
+                .config
+                .reasoning_effort
+                .filter(|_| capabilities.reasoning),
+            presence_penalty: sampling.then_some(req.config.presence_penalty),
             stop: Stop::new(&req.stop_sequences)?,
-            temperature: Some(req.config.temperature),
-            top_p: req.config.top_p,
+            temperature: sampling.then_some(req.config.temperature),
+            top_p: req.config.top_p.filter(|_| sampling),
             tools: if !req.tools.is_empty() {
                 Some(
                     req.tools
@@ -111,7 +194,13 @@ impl OpenAICompletionRequest {
                 None
             },
             tool_choice: if !req.tools.is_empty() {
-                Some("auto".to_string())
+                Some(OpenAIToolChoice::from(&req.tool_choice))
+            } else {
+                None
+            },
+            parallel_tool_calls: req.parallel_tool_calls.filter(|_| !req.tools.is_empty()),
+            response_format: if !req.response_format.is_text() {
+                Some(req.response_format.clone())
             } else {
                 None
             },
@@ -167,3 +256,21 @@ impl Stop {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_choice() {
+        let json =
+            |choice: &ToolChoice| serde_json::to_value(OpenAIToolChoice::from(choice)).unwrap();
+        assert_eq!(json(&ToolChoice::Auto), serde_json::json!("auto"));
+        assert_eq!(json(&ToolChoice::Any), serde_json::json!("required"));
+        assert_eq!(json(&ToolChoice::None), serde_json::json!("none"));
+        assert_eq!(
+            json(&ToolChoice::tool("extractor")),
+            serde_json::json!({"type": "function", "function": {"name": "extractor"}})
+        );
+    }
+}
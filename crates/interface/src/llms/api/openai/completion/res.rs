@@ -7,11 +7,36 @@ impl CompletionResponse {
         req: &CompletionRequest,
         res: OpenAICompletionResponse,
     ) -> Result<Self, CompletionError> {
-        let choice = if res.choices.is_empty() {
-            return Err(CompletionError::ResponseContentEmpty);
-        } else {
-            &res.choices[0]
-        };
+        let mut choices = res
+            .choices
+            .iter()
+            .map(CompletionChoice::new_from_openai)
+            .collect::<Result<Vec<_>, _>>()?;
+        choices.sort_by_key(|choice| choice.index);
+        let choice = choices
+            .first()
+            .ok_or(CompletionError::ResponseContentEmpty)?;
+        Ok(Self {
+            id: res.id.to_owned(),
+            response_id: None,
+            index: None,
+            content: choice.content.to_owned(),
+            reasoning: choice.reasoning.clone(),
+            thinking_blocks: Vec::new(),
+            finish_reason: choice.finish_reason.clone(),
+            completion_probabilities: choice.completion_probabilities.clone(),
+            truncated: false,
+            generation_settings: GenerationSettings::new_from_openai(req, &res),
+            timing_usage: TimingUsage::new_from_generic(req.start_time),
+            token_usage: TokenUsage::new_from_generic(&res).with_cost(req.backend.api_model()),
+            tool_calls: choice.tool_calls.clone(),
+            choices,
+        })
+    }
+}
+
+impl CompletionChoice {
+    fn new_from_openai(choice: &ChatChoice) -> Result<Self, CompletionError> {
         let finish_reason = match choice.finish_reason {
             Some(FinishReason::Stop) => CompletionFinishReason::Eos,
             Some(FinishReason::Length) => CompletionFinishReason::StopLimit,
@@ -29,15 +54,35 @@ impl CompletionResponse {
             None => CompletionFinishReason::Eos,
         };
         Ok(Self {
-            id: res.id.to_owned(),
-            index: None,
-            content: choice.message.content.as_ref().unwrap().to_owned(),
+            index: choice.index,
+            content: choice.message.content.clone().unwrap_or_default(),
+            reasoning: choice
+                .message
+                .reasoning_content
+                .clone()
+                .filter(|r| !r.is_empty()),
             finish_reason,
-            completion_probabilities: None,
-            truncated: false,
-            generation_settings: GenerationSettings::new_from_openai(req, &res),
-            timing_usage: TimingUsage::new_from_generic(req.start_time),
-            token_usage: TokenUsage::new_from_generic(&res),
+            completion_probabilities: choice
+                .logprobs
+                .as_ref()
+                .and_then(|logprobs| logprobs.content.as_ref())
+                .map(|tokens| {
+                    tokens
+                        .iter()
+                        .map(|token| InferenceProbabilities {
+                            content: Some(token.token.clone()),
+                            prob: Some(token.logprob),
+                            top_probs: token
+                                .top_logprobs
+                                .iter()
+                                .map(|top| TopProbabilities {
+                                    token: top.token.clone(),
+                                    prob: top.logprob,
+                                })
+                                .collect(),
+                        })
+                        .collect()
+                }),
             tool_calls: choice.message.tool_calls.clone(),
         })
     }
@@ -87,6 +132,9 @@ pub struct CompletionUsage {
 pub struct ChatCompletionResponseMessage {
     /// The contents of the message.
     pub content: Option<String>,
+    /// The reasoning of the model before the answer, returned by reasoning models such as DeepSeek.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
     /// The role of the author of this message.
     pub role: Role,
     /// The tool calls.
@@ -142,3 +190,50 @@ pub enum Role {
     Tool,
     Function,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choices_with_logprobs() {
+        let res: OpenAICompletionResponse = serde_json::from_value(serde_json::json!({
+            "id": "chatcmpl-1",
+            "created": 0,
+            "model": "gpt-4o-mini",
+            "choices": [
+                {
+                    "index": 1,
+                    "message": {"role": "assistant", "content": "no", "reasoning_content": "maybe"},
+                    "finish_reason": "stop",
+                    "logprobs": null
+                },
+                {
+                    "index": 0,
+                    "message": {"role": "assistant", "content": "yes"},
+                    "finish_reason": "stop",
+                    "logprobs": {"content": [{
+                        "token": "yes",
+                        "logprob": 0.0,
+                        "bytes": null,
+                        "top_logprobs": [{"token": "no", "logprob": -2.0, "bytes": null}]
+                    }]}
+                }
+            ]
+        }))
+        .unwrap();
+        let mut choices = res
+            .choices
+            .iter()
+            .map(CompletionChoice::new_from_openai)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        choices.sort_by_key(|choice| choice.index);
+        assert_eq!(choices[0].content, "yes");
+        assert_eq!(choices[0].confidence(), Some(1.0));
+        let probabilities = choices[0].completion_probabilities.as_ref().unwrap();
+        assert_eq!(probabilities[0].top_probs[0].token, "no");
+        assert_eq!(choices[1].confidence(), None);
+        assert_eq!(choices[1].reasoning.as_deref(), Some("maybe"));
+    }
+}
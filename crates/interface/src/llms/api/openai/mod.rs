@@ -1,9 +1,13 @@
+pub mod batch;
 pub mod builder;
 pub mod completion;
+pub mod responses;
 
 use super::{
     client::ApiClient,
     config::{ApiConfig, ApiConfigTrait},
+    http::HttpConfig,
+    rate_limit::RateLimiter,
 };
 use crate::requests::{
     completion::{
@@ -17,6 +21,7 @@ use completion::OpenAICompletionRequest;
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 use secrecy::{ExposeSecret, SecretString};
 use serde_json::json;
+use std::sync::Arc;
 
 /// Default v1 API base url
 pub const OPENAI_API_HOST: &str = "api.openai.com/v1";
@@ -35,7 +40,7 @@ impl OpenAIBackend {
         config.logging_config.load_logger()?;
         config.api_config.api_key = Some(config.api_config.load_api_key()?);
         Ok(Self {
-            client: ApiClient::new(config),
+            client: ApiClient::new(config)?,
             model,
         })
     }
@@ -46,7 +51,12 @@ impl OpenAIBackend {
     ) -> crate::Result<CompletionResponse, CompletionError> {
         match self
             .client
-            .post("/chat/completions", OpenAICompletionRequest::new(request)?)
+            .post_with_tokens(
+                "/chat/completions",
+                OpenAICompletionRequest::new(request)?,
+                request.estimated_tokens(),
+                request.cancellation_token.as_ref(),
+            )
             .await
         {
             Err(e) => Err(CompletionError::ClientError(e)),
@@ -60,12 +70,13 @@ impl OpenAIBackend {
     ) -> crate::Result<EmbeddingsResponse, EmbeddingsError> {
         match self
             .client
-            .post(
+            .post_with_cancellation(
                 "/embeddings",
                 json!({
                     "input": request.input,
                     "model": request.model,
                 }),
+                request.cancellation_token.as_ref(),
             )
             .await
         {
@@ -91,6 +102,8 @@ impl Default for OpenAIConfig {
                 port: None,
                 api_key: None,
                 api_key_env_var: "OPENAI_API_KEY".to_string(),
+                rate_limiter: None,
+                http: Default::default(),
             },
             logging_config: LoggingConfig {
                 logger_name: "openai".to_string(),
@@ -166,4 +179,12 @@ impl ApiConfigTrait for OpenAIConfig {
     fn api_key(&self) -> &Option<SecretString> {
         &self.api_config.api_key
     }
+
+    fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.api_config.rate_limiter.as_ref()
+    }
+
+    fn http_config(&self) -> &HttpConfig {
+        &self.api_config.http
+    }
 }
@@ -0,0 +1,278 @@
+//! The OpenAI Responses API, with built-in tools, reasoning items and server side conversation
+//! state continued with `previous_response_id`.
+
+mod req;
+mod res;
+pub use req::{OpenAIBuiltInTool, OpenAIResponsesRequest};
+pub use res::OpenAIResponsesResponse;
+
+use super::OpenAIConfig;
+use crate::llms::api::client::ApiClient;
+use crate::requests::{
+    completion::{
+        error::CompletionError, request::CompletionRequest, response::CompletionResponse,
+    },
+    embeddings::{EmbeddingsError, EmbeddingsRequest, EmbeddingsResponse},
+};
+use alith_models::api_model::ApiLLMModel;
+use serde_json::json;
+
+pub struct OpenAIResponsesBackend {
+    pub(crate) client: ApiClient<OpenAIConfig>,
+    pub model: ApiLLMModel,
+    /// The built-in tools sent with every request, next to the function tools of the request.
+    pub built_in_tools: Vec<OpenAIBuiltInTool>,
+}
+
+impl OpenAIResponsesBackend {
+    pub fn new(
+        mut config: OpenAIConfig,
+        model: ApiLLMModel,
+        built_in_tools: Vec<OpenAIBuiltInTool>,
+    ) -> crate::Result<Self> {
+        config.logging_config.load_logger()?;
+        config.api_config.api_key = Some(config.api_config.load_api_key()?);
+        Ok(Self {
+            client: ApiClient::new(config)?,
+            model,
+            built_in_tools,
+        })
+    }
+
+    pub(crate) async fn completion_request(
+        &self,
+        request: &CompletionRequest,
+    ) -> crate::Result<CompletionResponse, CompletionError> {
+        match self
+            .client
+            .post_with_tokens(
+                "/responses",
+                OpenAIResponsesRequest::new(request, &self.built_in_tools)?,
+                request.estimated_tokens(),
+                request.cancellation_token.as_ref(),
+            )
+            .await
+        {
+            Err(e) => Err(CompletionError::ClientError(e)),
+            Ok(res) => Ok(CompletionResponse::new_from_openai_responses(request, res)?),
+        }
+    }
+
+    pub(crate) async fn embeddings_request(
+        &self,
+        request: &EmbeddingsRequest,
+    ) -> crate::Result<EmbeddingsResponse, EmbeddingsError> {
+        self.client
+            .post_with_cancellation(
+                "/embeddings",
+                json!({
+                    "input": request.input,
+                    "model": request.model,
+                }),
+                request.cancellation_token.as_ref(),
+            )
+            .await
+            .map_err(EmbeddingsError::ClientError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llms::LLMBackend;
+    use crate::requests::completion::{CompletionError, CompletionFinishReason, ToolDefinition};
+    use crate::requests::logit_bias::LogitBias;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_responses_continuation() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/responses")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "model": "o4-mini",
+                "input": [{"role": "user", "content": "And in Paris?"}],
+                "instructions": "Be brief.",
+                "previous_response_id": "resp_1",
+                "max_output_tokens": 100,
+                "tools": [
+                    {"type": "function", "name": "weather", "description": "The weather",
+                     "parameters": {"type": "object"}},
+                    {"type": "web_search_preview"}
+                ],
+                "tool_choice": "auto"
+            })))
+            .with_body(
+                serde_json::json!({
+                    "id": "resp_2",
+                    "model": "o4-mini",
+                    "status": "completed",
+                    "error": null,
+                    "incomplete_details": null,
+                    "output": [
+                        {"type": "reasoning", "id": "rs_1", "summary": [
+                            {"type": "summary_text", "text": "Look it up"}
+                        ]},
+                        {"type": "web_search_call", "id": "ws_1", "status": "completed"},
+                        {"type": "message", "id": "msg_1", "role": "assistant", "content": [
+                            {"type": "output_text", "text": "Sunny.", "annotations": []}
+                        ]},
+                        {"type": "function_call", "id": "fc_1", "call_id": "call_1",
+                         "name": "weather", "arguments": "{\"city\":\"Paris\"}"}
+                    ],
+                    "usage": {"input_tokens": 20, "output_tokens": 5, "total_tokens": 25}
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let mut config = OpenAIConfig::new();
+        config.api_config.host = server.url();
+        config.api_config.api_key = Some("test-key".to_string().into());
+        config.logging_config.logging_enabled = false;
+        let backend = Arc::new(LLMBackend::OpenAIResponses(
+            OpenAIResponsesBackend::new(
+                config,
+                ApiLLMModel::o4_mini(),
+                vec![OpenAIBuiltInTool::WebSearchPreview],
+            )
+            .unwrap(),
+        ));
+
+        let mut request = CompletionRequest::new(Arc::clone(&backend));
+        request
+            .prompt
+            .add_system_message()
+            .unwrap()
+            .set_content("Be brief.");
+        request
+            .prompt
+            .add_user_message()
+            .unwrap()
+            .set_content("Weather in Rome?");
+        request
+            .prompt
+            .add_assistant_message()
+            .unwrap()
+            .set_content("Rainy.");
+        request
+            .prompt
+            .add_user_message()
+            .unwrap()
+            .set_content("And in Paris?");
+        request.config.requested_response_tokens = Some(100);
+        request.previous_response_id = Some("resp_1".to_string());
+        request.tools.push(ToolDefinition {
+            name: "weather".to_string(),
+            description: "The weather".to_string(),
+            parameters: serde_json::json!({"type": "object"}),
+        });
+
+        let response = request.request().await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(response.response_id.as_deref(), Some("resp_2"));
+        assert_eq!(response.content, "Sunny.");
+        assert_eq!(response.reasoning.as_deref(), Some("Look it up"));
+        assert_eq!(response.finish_reason, CompletionFinishReason::ToolsCall);
+        let tool_calls = response.tool_calls.unwrap();
+        assert_eq!(tool_calls[0].id, "call_1");
+        assert_eq!(tool_calls[0].function.arguments, r#"{"city":"Paris"}"#);
+        assert_eq!(response.token_usage.total_tokens, 25);
+    }
+
+    #[tokio::test]
+    async fn test_responses_logit_bias_unsupported() {
+        let mut config = OpenAIConfig::new();
+        config.api_config.api_key = Some("test-key".to_string().into());
+        config.logging_config.logging_enabled = false;
+        let backend = Arc::new(LLMBackend::OpenAIResponses(
+            OpenAIResponsesBackend::new(config, ApiLLMModel::o4_mini(), Vec::new()).unwrap(),
+        ));
+        let mut request = CompletionRequest::new(backend);
+        request
+            .prompt
+            .add_user_message()
+            .unwrap()
+            .set_content("Hello");
+        let mut logit_bias = LogitBias::new();
+        logit_bias.add_token_id(42, -100.0);
+        request.logit_bias = Some(logit_bias);
+        assert!(matches!(
+            request.request().await,
+            Err(CompletionError::RequestBuilderError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_responses_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let response = |status: &str, error: serde_json::Value, incomplete: serde_json::Value| {
+            serde_json::json!({
+                "id": "resp_1",
+                "model": "o4-mini",
+                "status": status,
+                "error": error,
+                "incomplete_details": incomplete,
+                "output": [],
+                "usage": null
+            })
+            .to_string()
+        };
+        let failed = server
+            .mock("POST", "/responses")
+            .with_body(response(
+                "failed",
+                serde_json::json!({"code": "server_error", "message": "The server failed."}),
+                serde_json::Value::Null,
+            ))
+            .expect(1)
+            .create_async()
+            .await;
+        let incomplete = server
+            .mock("POST", "/responses")
+            .with_body(response(
+                "incomplete",
+                serde_json::Value::Null,
+                serde_json::json!({"reason": "content_filter"}),
+            ))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut config = OpenAIConfig::new();
+        config.api_config.host = server.url();
+        config.api_config.api_key = Some("test-key".to_string().into());
+        config.logging_config.logging_enabled = false;
+        let backend = Arc::new(LLMBackend::OpenAIResponses(
+            OpenAIResponsesBackend::new(config, ApiLLMModel::o4_mini(), Vec::new()).unwrap(),
+        ));
+        let mut request = CompletionRequest::new(backend);
+        request
+            .prompt
+            .add_user_message()
+            .unwrap()
+            .set_content("Hello");
+
+        let Err(err) = request.request().await else {
+            panic!("expected the failed response to be an error");
+        };
+        assert!(err.is_transient());
+        let CompletionError::ResponseError { code, message } = err else {
+            panic!("expected a response error, got {err:?}");
+        };
+        assert_eq!(code.as_deref(), Some("server_error"));
+        assert_eq!(message, "The server failed.");
+
+        let Err(err) = request.request().await else {
+            panic!("expected the incomplete response to be an error");
+        };
+        assert!(!err.is_transient());
+        assert!(matches!(
+            err,
+            CompletionError::ResponseError { code: Some(code), .. } if code == "content_filter"
+        ));
+        failed.assert_async().await;
+        incomplete.assert_async().await;
+    }
+}
@@ -0,0 +1,205 @@
+use crate::llms::api::openai::completion::CompletionRequestMessage;
+use crate::requests::{completion::*, req_components::ReasoningEffort};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Default, Debug, Deserialize)]
+pub struct OpenAIResponsesRequest {
+    /// ID of the model to use.
+    pub model: String,
+
+    /// The messages of the conversation. When continuing a response, only the messages from
+    /// the last user message are sent, the previous ones are kept by the API.
+    pub input: Vec<ResponsesInputMessage>,
+
+    /// The system messages, sent apart from the input because they are not carried over by
+    /// `previous_response_id`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+
+    /// The ID of the previous response, to continue its conversation, default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_response_id: Option<String>,
+
+    /// An upper bound for the number of tokens that can be generated, including the reasoning
+    /// tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
+
+    /// min: 0.0, max: 2.0, default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// min: 0.0, max: 1.0, default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+
+    /// The reasoning configuration of reasoning models, default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<ResponsesReasoning>,
+
+    /// The function and built-in tools for the request, default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<ResponsesTool>>,
+
+    /// The tool choice for the request, default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ResponsesToolChoice>,
+
+    /// Whether to enable parallel function calling during tool use, default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+
+    /// The format of the text output, default: None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<ResponsesText>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResponsesInputMessage {
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResponsesReasoning {
+    pub effort: ReasoningEffort,
+}
+
+/// A tool hosted by OpenAI, run by the API while generating the response.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OpenAIBuiltInTool {
+    /// Searches the web for the latest information.
+    WebSearchPreview,
+    /// Searches the files of the vector stores.
+    FileSearch { vector_store_ids: Vec<String> },
+    /// Runs Python code in a container, e.g. `{"type": "auto"}`.
+    CodeInterpreter { container: serde_json::Value },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ResponsesTool {
+    Function(ResponsesFunctionTool),
+    BuiltIn(OpenAIBuiltInTool),
+}
+
+/// A function tool, flattened unlike the ones of the chat completions.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResponsesFunctionTool {
+    pub r#type: String,
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+#[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum ResponsesToolChoice {
+    Mode(String),
+    Named { r#type: String, name: String },
+}
+
+impl From<&ToolChoice> for ResponsesToolChoice {
+    fn from(tool_choice: &ToolChoice) -> Self {
+        match tool_choice {
+            ToolChoice::Auto => Self::Mode("auto".to_string()),
+            ToolChoice::Any => Self::Mode("required".to_string()),
+            ToolChoice::None => Self::Mode("none".to_string()),
+            ToolChoice::Tool { name } => Self::Named {
+                r#type: "function".to_string(),
+                name: name.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResponsesText {
+    pub format: serde_json::Value,
+}
+
+impl ResponsesText {
+    /// The text format of the structured output, flattened unlike the chat completions one.
+    fn new(response_format: &ResponseFormat) -> Option<Self> {
+        let format = match response_format {
+            ResponseFormat::Text => return None,
+            ResponseFormat::JsonObject => serde_json::json!({ "type": "json_object" }),
+            ResponseFormat::JsonSchema { json_schema } => {
+                let mut format = serde_json::to_value(json_schema).ok()?;
+                format["type"] = "json_schema".into();
+                format
+            }
+        };
+        Some(Self { format })
+    }
+}
+
+impl OpenAIResponsesRequest {
+    pub fn new(
+        req: &CompletionRequest,
+        built_in_tools: &[OpenAIBuiltInTool],
+    ) -> crate::Result<Self, CompletionError> {
+        let capabilities = &req.backend.api_model().capabilities;
+        let prompt_messages = req
+            .prompt
+            .get_built_prompt_messages()
+            .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?;
+        let mut instructions: Vec<String> = Vec::new();
+        let mut input = Vec::new();
+        for m in &prompt_messages {
+            let message = CompletionRequestMessage::new(m)?;
+            match message.role.as_str() {
+                "system" => instructions.push(message.content),
+                _ => input.push(ResponsesInputMessage {
+                    role: message.role,
+                    content: message.content,
+                }),
+            }
+        }
+        // The previous response already holds the conversation up to the last user turn
+        if req.previous_response_id.is_some() {
+            if let Some(last_user) = input.iter().rposition(|m| m.role == "user") {
+                input.drain(..last_user);
+            }
+        }
+        let sampling = capabilities.sampling;
+
+        let mut tools: Vec<ResponsesTool> = req
+            .tools
+            .iter()
+            .map(|tool| {
+                ResponsesTool::Function(ResponsesFunctionTool {
+                    r#type: "function".to_string(),
+                    name: tool.name.clone(),
+                    description: tool.description.clone(),
+                    parameters: tool.parameters.clone(),
+                })
+            })
+            .collect();
+        tools.extend(built_in_tools.iter().cloned().map(ResponsesTool::BuiltIn));
+
+        Ok(OpenAIResponsesRequest {
+            model: req.backend.model_id().to_owned(),
+            input,
+            instructions: (!instructions.is_empty()).then(|| instructions.join("\n\n")),
+            previous_response_id: req.previous_response_id.clone(),
+            max_output_tokens: req.config.actual_request_tokens,
+            temperature: sampling.then_some(req.config.temperature),
+            top_p: req.config.top_p.filter(|_| sampling),
+            reasoning: req
+                .config
+                .reasoning_effort
+                .filter(|_| capabilities.reasoning)
+                .map(|effort| ResponsesReasoning { effort }),
+            tool_choice: if !req.tools.is_empty() {
+                Some(ResponsesToolChoice::from(&req.tool_choice))
+            } else {
+                None
+            },
+            parallel_tool_calls: req.parallel_tool_calls.filter(|_| !tools.is_empty()),
+            tools: (!tools.is_empty()).then_some(tools),
+            text: ResponsesText::new(&req.response_format),
+        })
+    }
+}
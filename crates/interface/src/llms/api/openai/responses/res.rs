@@ -0,0 +1,167 @@
+use crate::requests::completion::tool::{Function, ToolCall};
+use crate::requests::completion::*;
+use serde::{Deserialize, Serialize};
+
+impl CompletionResponse {
+    pub fn new_from_openai_responses(
+        req: &CompletionRequest,
+        res: OpenAIResponsesResponse,
+    ) -> Result<Self, CompletionError> {
+        if let Some(error) = &res.error {
+            return Err(CompletionError::ResponseError {
+                code: error.code.clone(),
+                message: error.message.clone(),
+            });
+        }
+        let mut content = String::new();
+        let mut reasoning: Option<String> = None;
+        let mut tool_calls = Vec::new();
+        for item in &res.output {
+            match item {
+                ResponsesOutputItem::Message { content: parts } => {
+                    for part in parts {
+                        match part {
+                            ResponsesOutputContent::OutputText { text } => content.push_str(text),
+                            ResponsesOutputContent::Refusal { refusal } => {
+                                content.push_str(refusal)
+                            }
+                            ResponsesOutputContent::Other => {}
+                        }
+                    }
+                }
+                ResponsesOutputItem::FunctionCall {
+                    call_id,
+                    name,
+                    arguments,
+                } => tool_calls.push(ToolCall {
+                    id: call_id.to_owned(),
+                    r#type: "function".to_string(),
+                    function: Function {
+                        name: name.to_owned(),
+                        arguments: arguments.to_owned(),
+                    },
+                }),
+                ResponsesOutputItem::Reasoning { summary } => {
+                    for part in summary {
+                        let reasoning = reasoning.get_or_insert_with(String::new);
+                        if !reasoning.is_empty() {
+                            reasoning.push('\n');
+                        }
+                        reasoning.push_str(&part.text);
+                    }
+                }
+                ResponsesOutputItem::Other => {}
+            }
+        }
+
+        let finish_reason = match res.incomplete_details.as_ref().map(|d| d.reason.as_str()) {
+            Some("max_output_tokens") => CompletionFinishReason::StopLimit,
+            Some(reason) => {
+                return Err(CompletionError::ResponseError {
+                    code: Some(reason.to_owned()),
+                    message: "Incomplete response".to_owned(),
+                })
+            }
+            None if !tool_calls.is_empty() => CompletionFinishReason::ToolsCall,
+            None => CompletionFinishReason::Eos,
+        };
+        if content.is_empty() && tool_calls.is_empty() {
+            return Err(CompletionError::ResponseContentEmpty);
+        }
+
+        let tool_calls = (!tool_calls.is_empty()).then_some(tool_calls);
+        Ok(Self {
+            id: res.id.to_owned(),
+            response_id: Some(res.id.to_owned()),
+            index: None,
+            content: content.clone(),
+            reasoning: reasoning.clone(),
+            thinking_blocks: Vec::new(),
+            finish_reason: finish_reason.clone(),
+            completion_probabilities: None,
+            truncated: false,
+            generation_settings: GenerationSettings::new_from_openai_responses(req, &res),
+            timing_usage: TimingUsage::new_from_generic(req.start_time),
+            token_usage: TokenUsage::new_from_openai_responses(&res)
+                .with_cost(req.backend.api_model()),
+            tool_calls: tool_calls.clone(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                content,
+                reasoning,
+                finish_reason,
+                completion_probabilities: None,
+                tool_calls,
+            }],
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct OpenAIResponsesResponse {
+    pub id: String,
+    pub model: String,
+    /// One of `completed`, `failed`, `in_progress` or `incomplete`.
+    pub status: String,
+    pub output: Vec<ResponsesOutputItem>,
+    pub error: Option<ResponsesError>,
+    pub incomplete_details: Option<ResponsesIncompleteDetails>,
+    pub usage: Option<ResponsesUsage>,
+}
+
+/// An item of the output, the ones run by the API such as the web search calls are skipped.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponsesOutputItem {
+    Message {
+        content: Vec<ResponsesOutputContent>,
+    },
+    FunctionCall {
+        call_id: String,
+        name: String,
+        arguments: String,
+    },
+    Reasoning {
+        #[serde(default)]
+        summary: Vec<ResponsesReasoningSummary>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponsesOutputContent {
+    OutputText {
+        text: String,
+    },
+    Refusal {
+        refusal: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ResponsesReasoningSummary {
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ResponsesError {
+    pub code: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ResponsesIncompleteDetails {
+    /// Either `max_output_tokens` or `content_filter`.
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ResponsesUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub total_tokens: u32,
+}
@@ -0,0 +1,288 @@
+use reqwest::header::HeaderMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// A client side limiter for the requests and tokens sent per minute.
+///
+/// Both limits are token buckets refilled continuously over a minute. The limiter is held in an
+/// `Arc` by the backend config, so every clone of an `LLMClient` built from it shares the same
+/// budget. When the provider reports that a limit was reached, the limiter pauses all of its
+/// users until the limit resets.
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests_per_minute: Option<u32>,
+    tokens_per_minute: Option<u32>,
+    state: Mutex<RateLimiterState>,
+}
+
+#[derive(Debug)]
+struct RateLimiterState {
+    requests: f64,
+    tokens: f64,
+    last_refill: Instant,
+    paused_until: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: Option<u32>, tokens_per_minute: Option<u32>) -> Self {
+        Self {
+            requests_per_minute,
+            tokens_per_minute,
+            state: Mutex::new(RateLimiterState {
+                requests: requests_per_minute.unwrap_or_default() as f64,
+                tokens: tokens_per_minute.unwrap_or_default() as f64,
+                last_refill: Instant::now(),
+                paused_until: None,
+            }),
+        }
+    }
+
+    pub fn requests_per_minute(&self) -> Option<u32> {
+        self.requests_per_minute
+    }
+
+    pub fn tokens_per_minute(&self) -> Option<u32> {
+        self.tokens_per_minute
+    }
+
+    /// Waits until one request using `tokens` tokens fits in the limits, then takes it from the budget.
+    ///
+    /// Requests larger than the tokens per minute limit wait for a full bucket instead of forever.
+    pub async fn acquire(&self, tokens: u64) {
+        loop {
+            match self.try_acquire(tokens) {
+                Ok(()) => return,
+                Err(wait) => {
+                    crate::trace!("Rate limiter waiting {:?}", wait);
+                    tokio::time::sleep(wait).await
+                }
+            }
+        }
+    }
+
+    /// Blocks every user of the limiter for the given duration.
+    pub fn pause_for(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state
+            .paused_until
+            .is_none_or(|paused_until| paused_until < until)
+        {
+            state.paused_until = Some(until);
+        }
+    }
+
+    fn try_acquire(&self, tokens: u64) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(paused_until) = state.paused_until {
+            if paused_until > now {
+                return Err(paused_until - now);
+            }
+            state.paused_until = None;
+        }
+
+        let elapsed_minutes = (now - state.last_refill).as_secs_f64() / 60.0;
+        state.last_refill = now;
+        if let Some(rpm) = self.requests_per_minute {
+            state.requests = (state.requests + elapsed_minutes * rpm as f64).min(rpm as f64);
+        }
+        if let Some(tpm) = self.tokens_per_minute {
+            state.tokens = (state.tokens + elapsed_minutes * tpm as f64).min(tpm as f64);
+        }
+
+        let mut wait_minutes: f64 = 0.0;
+        if let Some(rpm) = self.requests_per_minute.filter(|rpm| *rpm > 0) {
+            if state.requests < 1.0 {
+                wait_minutes = wait_minutes.max((1.0 - state.requests) / rpm as f64);
+            }
+        }
+        let tokens = match self.tokens_per_minute.filter(|tpm| *tpm > 0) {
+            Some(tpm) => {
+                let tokens = (tokens as f64).min(tpm as f64);
+                if state.tokens < tokens {
+                    wait_minutes = wait_minutes.max((tokens - state.tokens) / tpm as f64);
+                }
+                tokens
+            }
+            None => 0.0,
+        };
+        if wait_minutes > 0.0 {
+            return Err(Duration::from_secs_f64(wait_minutes * 60.0));
+        }
+
+        state.requests -= 1.0;
+        state.tokens -= tokens;
+        Ok(())
+    }
+}
+
+/// The delay requested by a rate limited or unavailable response.
+///
+/// Reads `retry-after-ms` and `retry-after` first, then falls back to the reset time of the
+/// exhausted `x-ratelimit-*` (OpenAI) or `anthropic-ratelimit-*` limits.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(ms) = header_str(headers, "retry-after-ms").and_then(|v| v.parse::<f64>().ok()) {
+        return Some(Duration::from_secs_f64(ms.max(0.0) / 1000.0));
+    }
+    if let Some(value) = header_str(headers, "retry-after") {
+        if let Ok(secs) = value.parse::<f64>() {
+            return Some(Duration::from_secs_f64(secs.max(0.0)));
+        }
+        if let Ok(date) = httpdate::parse_http_date(value) {
+            return Some(
+                date.duration_since(SystemTime::now())
+                    .unwrap_or(Duration::ZERO),
+            );
+        }
+    }
+    rate_limit_reset(headers, true)
+}
+
+/// The time until the provider resets a limit with no remaining budget.
+///
+/// With `assume_exhausted`, limits that don't report their remaining budget count as exhausted.
+pub fn rate_limit_reset(headers: &HeaderMap, assume_exhausted: bool) -> Option<Duration> {
+    let exhausted = |remaining: &str| match header_str(headers, remaining) {
+        Some(value) => value.trim() == "0",
+        None => assume_exhausted,
+    };
+    let mut reset: Option<Duration> = None;
+    for limit in ["requests", "tokens"] {
+        if exhausted(&format!("x-ratelimit-remaining-{limit}")) {
+            if let Some(duration) = header_str(headers, &format!("x-ratelimit-reset-{limit}"))
+                .and_then(parse_reset_duration)
+            {
+                reset = reset.max(Some(duration));
+            }
+        }
+    }
+    for limit in ["requests", "tokens", "input-tokens", "output-tokens"] {
+        if exhausted(&format!("anthropic-ratelimit-{limit}-remaining")) {
+            if let Some(duration) =
+                header_str(headers, &format!("anthropic-ratelimit-{limit}-reset"))
+                    .and_then(parse_rfc3339)
+                    .map(|reset| reset.duration_since(SystemTime::now()).unwrap_or_default())
+            {
+                reset = reset.max(Some(duration));
+            }
+        }
+    }
+    reset
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// Parses OpenAI reset durations such as `1s`, `6m0s`, `20ms` or `1h2m3.5s`.
+fn parse_reset_duration(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        total += number
+            * match &rest[..unit_len] {
+                "ms" => 0.001,
+                "s" | "" => 1.0,
+                "m" => 60.0,
+                "h" => 3600.0,
+                _ => return None,
+            };
+        rest = &rest[unit_len..];
+    }
+    Some(Duration::from_secs_f64(total))
+}
+
+/// Parses RFC 3339 timestamps such as `2024-06-05T12:34:56Z` or `2024-06-05T12:34:56.5+02:00`.
+fn parse_rfc3339(value: &str) -> Option<SystemTime> {
+    let value = value.trim();
+    let (date, time) = value.split_once(['T', 't', ' '])?;
+    let mut date = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+
+    let offset_start = time.find(['Z', 'z', '+', '-'])?;
+    let (clock, offset) = time.split_at(offset_start);
+    let offset_secs = match offset {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let (hours, minutes) = offset[1..].split_once(':')?;
+            sign * (hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60)
+        }
+    };
+    let mut clock = clock.splitn(3, ':');
+    let hours = clock.next()?.parse::<i64>().ok()?;
+    let minutes = clock.next()?.parse::<i64>().ok()?;
+    let seconds = clock.next()?.parse::<f64>().ok()?;
+
+    // Days from the civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    let secs = days * 86400 + hours * 3600 + minutes * 60 - offset_secs;
+    let secs = secs as f64 + seconds;
+    if secs < 0.0 {
+        return None;
+    }
+    Some(UNIX_EPOCH + Duration::from_secs_f64(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_retry_after_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-ratelimit-remaining-requests",
+            HeaderValue::from_static("0"),
+        );
+        headers.insert(
+            "x-ratelimit-reset-requests",
+            HeaderValue::from_static("1m30.5s"),
+        );
+        headers.insert(
+            "x-ratelimit-remaining-tokens",
+            HeaderValue::from_static("100"),
+        );
+        headers.insert("x-ratelimit-reset-tokens", HeaderValue::from_static("6m0s"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs_f64(90.5)));
+
+        headers.insert("retry-after", HeaderValue::from_static("2"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(2)));
+
+        assert_eq!(
+            parse_rfc3339("1970-01-02T00:00:01.5+01:00"),
+            Some(UNIX_EPOCH + Duration::from_secs_f64(86401.5 - 3600.0))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_budget() {
+        let limiter = RateLimiter::new(Some(3), Some(1000));
+        limiter.acquire(400).await;
+        limiter.acquire(400).await;
+        // Out of tokens but not requests.
+        assert!(limiter.try_acquire(400).is_err());
+        assert!(limiter.try_acquire(0).is_ok());
+        // Out of requests.
+        assert!(limiter.try_acquire(0).is_err());
+    }
+}
@@ -0,0 +1,256 @@
+use super::{
+    client::ApiClient,
+    config::{ApiConfig, ApiConfigTrait, LLMApiConfigTrait},
+    http::HttpConfig,
+    rate_limit::RateLimiter,
+};
+use crate::requests::embeddings::{EmbeddingsData, EmbeddingsError, EmbeddingsResponse, Usage};
+use alith_devices::logging::{LoggingConfig, LoggingConfigTrait};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Default v1 API base url
+pub const VOYAGE_API_HOST: &str = "api.voyageai.com/v1";
+/// The maximum number of texts accepted by a single embeddings request.
+pub const VOYAGE_MAX_DOCUMENTS: usize = 128;
+
+pub struct VoyageBackend {
+    pub(crate) client: ApiClient<VoyageConfig>,
+}
+
+impl VoyageBackend {
+    pub fn new(mut config: VoyageConfig) -> crate::Result<Self> {
+        config.logging_config.load_logger()?;
+        config.api_config.api_key = Some(config.api_config.load_api_key()?);
+        Ok(Self {
+            client: ApiClient::new(config)?,
+        })
+    }
+
+    pub async fn embeddings_request(
+        &self,
+        request: &VoyageEmbeddingsRequest,
+    ) -> crate::Result<EmbeddingsResponse, EmbeddingsError> {
+        match self
+            .client
+            .post::<_, VoyageEmbeddingsResponse>("/embeddings", request)
+            .await
+        {
+            Ok(res) => Ok(res.into()),
+            Err(e) => Err(EmbeddingsError::ClientError(e)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VoyageEmbeddingsRequest {
+    /// A list of texts to embed, at most [VOYAGE_MAX_DOCUMENTS] items.
+    pub input: Vec<String>,
+    /// Name of the model, e.g. `voyage-3` or `voyage-3-lite`.
+    pub model: String,
+    /// Type of the input text. When set, Voyage prepends a retrieval specific prompt to the input.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_type: Option<VoyageInputType>,
+    /// Whether to truncate the input texts to fit within the context length. Defaults to `true` server side.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncation: Option<bool>,
+    /// The number of dimensions for resulting output embeddings, only supported by some models.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_dimension: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VoyageInputType {
+    Query,
+    Document,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VoyageEmbeddingsResponse {
+    pub object: String,
+    pub data: Vec<VoyageEmbeddingsData>,
+    pub model: String,
+    pub usage: VoyageUsage,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VoyageEmbeddingsData {
+    pub object: String,
+    pub embedding: Vec<f64>,
+    pub index: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VoyageUsage {
+    pub total_tokens: usize,
+}
+
+impl From<VoyageEmbeddingsResponse> for EmbeddingsResponse {
+    fn from(res: VoyageEmbeddingsResponse) -> Self {
+        EmbeddingsResponse {
+            object: res.object,
+            data: res
+                .data
+                .into_iter()
+                .map(|d| EmbeddingsData {
+                    object: d.object,
+                    embedding: d.embedding,
+                    index: d.index,
+                })
+                .collect(),
+            model: res.model,
+            usage: Usage {
+                prompt_tokens: res.usage.total_tokens,
+                total_tokens: res.usage.total_tokens,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VoyageConfig {
+    pub api_config: ApiConfig,
+    pub logging_config: LoggingConfig,
+}
+
+impl Default for VoyageConfig {
+    fn default() -> Self {
+        Self {
+            api_config: ApiConfig {
+                host: VOYAGE_API_HOST.to_string(),
+                port: None,
+                api_key: None,
+                api_key_env_var: "VOYAGE_API_KEY".to_string(),
+                rate_limiter: None,
+                http: Default::default(),
+            },
+            logging_config: LoggingConfig {
+                logger_name: "voyage".to_string(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl VoyageConfig {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl ApiConfigTrait for VoyageConfig {
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(api_key) = self.api_key() {
+            if let Ok(header_value) =
+                HeaderValue::from_str(&format!("Bearer {}", api_key.expose_secret()))
+            {
+                headers.insert(AUTHORIZATION, header_value);
+            } else {
+                crate::error!("Failed to create header value from authorization value");
+            }
+        }
+
+        headers
+    }
+
+    fn url(&self, path: &str) -> String {
+        if self.api_config.host.starts_with("http") {
+            if let Some(port) = &self.api_config.port {
+                format!("{}:{}{}", self.api_config.host, port, path)
+            } else {
+                format!("{}{}", self.api_config.host, path)
+            }
+        } else {
+            format!("https://{}{}", self.api_config.host, path)
+        }
+    }
+
+    fn api_key(&self) -> &Option<SecretString> {
+        &self.api_config.api_key
+    }
+
+    fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.api_config.rate_limiter.as_ref()
+    }
+
+    fn http_config(&self) -> &HttpConfig {
+        &self.api_config.http
+    }
+}
+
+impl LLMApiConfigTrait for VoyageConfig {
+    fn api_base_config_mut(&mut self) -> &mut ApiConfig {
+        &mut self.api_config
+    }
+
+    fn api_config(&self) -> &ApiConfig {
+        &self.api_config
+    }
+}
+
+impl LoggingConfigTrait for VoyageConfig {
+    fn logging_config_mut(&mut self) -> &mut LoggingConfig {
+        &mut self.logging_config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_voyage_embeddings_request() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/embeddings")
+            .match_header("authorization", "Bearer test-key")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "input": ["hello", "world"],
+                "model": "voyage-3",
+                "input_type": "document",
+                "output_dimension": 2,
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "object": "list",
+                    "data": [
+                        {"object": "embedding", "embedding": [0.1, 0.2], "index": 0},
+                        {"object": "embedding", "embedding": [0.3, 0.4], "index": 1}
+                    ],
+                    "model": "voyage-3",
+                    "usage": {"total_tokens": 4}
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        let config = VoyageConfig::new()
+            .with_api_host(server.url())
+            .with_api_key("test-key")
+            .logging_enabled(false);
+        let backend = VoyageBackend::new(config).unwrap();
+        let res = backend
+            .embeddings_request(&VoyageEmbeddingsRequest {
+                input: vec!["hello".to_string(), "world".to_string()],
+                model: "voyage-3".to_string(),
+                input_type: Some(VoyageInputType::Document),
+                truncation: None,
+                output_dimension: Some(2),
+            })
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(res.model, "voyage-3");
+        assert_eq!(res.data.len(), 2);
+        assert_eq!(res.data[1].embedding, vec![0.3, 0.4]);
+        assert_eq!(res.usage.prompt_tokens, 4);
+        assert_eq!(res.usage.total_tokens, 4);
+    }
+}
@@ -1,21 +1,24 @@
 use crate::requests::{
     completion::{
         error::CompletionError, request::CompletionRequest, response::CompletionResponse,
+        response_format::ResponseFormat,
     },
     embeddings::{EmbeddingsError, EmbeddingsRequest, EmbeddingsResponse},
     logit_bias::LogitBias,
 };
-use alith_models::tokenizer::Tokenizer;
+use alith_models::{api_model::ApiLLMModel, tokenizer::Tokenizer};
 use alith_prompt::{LLMPrompt, PromptTokenizer};
 pub mod api;
 pub mod local;
 use api::anthropic::AnthropicBackend;
 use api::generic_openai::GenericApiBackend;
-use api::openai::OpenAIBackend;
+use api::openai::{responses::OpenAIResponsesBackend, OpenAIBackend};
 use std::sync::Arc;
 
 pub enum LLMBackend {
     OpenAI(OpenAIBackend),
+    /// The OpenAI Responses API.
+    OpenAIResponses(OpenAIResponsesBackend),
     Anthropic(AnthropicBackend),
     GenericApi(GenericApiBackend),
 }
@@ -27,6 +30,7 @@ impl LLMBackend {
     ) -> crate::Result<CompletionResponse, CompletionError> {
         match self {
             LLMBackend::OpenAI(b) => b.completion_request(request).await,
+            LLMBackend::OpenAIResponses(b) => b.completion_request(request).await,
             LLMBackend::Anthropic(b) => b.completion_request(request).await,
             LLMBackend::GenericApi(b) => b.completion_request(request).await,
         }
@@ -38,8 +42,12 @@ impl LLMBackend {
     ) -> crate::Result<EmbeddingsResponse, EmbeddingsError> {
         match self {
             LLMBackend::OpenAI(b) => b.embeddings_request(request).await,
+            LLMBackend::OpenAIResponses(b) => b.embeddings_request(request).await,
             LLMBackend::GenericApi(b) => b.embeddings_request(request).await,
-            _ => unimplemented!(),
+            LLMBackend::Anthropic(_) => Err(EmbeddingsError::UnsupportedBackend(
+                "Anthropic does not provide an embeddings API, use Voyage embeddings instead"
+                    .to_string(),
+            )),
         }
     }
 
@@ -70,6 +78,11 @@ impl LLMBackend {
                 Some(b.model.tokens_per_message),
                 b.model.tokens_per_name,
             ),
+            LLMBackend::OpenAIResponses(b) => LLMPrompt::new_api_prompt(
+                self.prompt_tokenizer(),
+                Some(b.model.tokens_per_message),
+                b.model.tokens_per_name,
+            ),
             LLMBackend::Anthropic(b) => LLMPrompt::new_api_prompt(
                 self.prompt_tokenizer(),
                 Some(b.model.tokens_per_message),
@@ -86,14 +99,55 @@ impl LLMBackend {
     pub fn get_total_prompt_tokens(&self, prompt: &LLMPrompt) -> crate::Result<u64> {
         match self {
             LLMBackend::OpenAI(_) => prompt.api_prompt()?.get_total_prompt_tokens(),
+            LLMBackend::OpenAIResponses(_) => prompt.api_prompt()?.get_total_prompt_tokens(),
             LLMBackend::Anthropic(_) => prompt.api_prompt()?.get_total_prompt_tokens(),
             LLMBackend::GenericApi(_) => prompt.api_prompt()?.get_total_prompt_tokens(),
         }
     }
 
+    /// Whether the backend can natively produce output in the given format.
+    pub fn supports_response_format(&self, response_format: &ResponseFormat) -> bool {
+        match (self, response_format) {
+            (_, ResponseFormat::Text) => true,
+            (LLMBackend::OpenAI(_), ResponseFormat::JsonObject) => true,
+            (LLMBackend::OpenAI(b), ResponseFormat::JsonSchema { .. }) => {
+                b.model.supports_json_schema()
+            }
+            (LLMBackend::OpenAIResponses(_), ResponseFormat::JsonObject) => true,
+            (LLMBackend::OpenAIResponses(b), ResponseFormat::JsonSchema { .. }) => {
+                b.model.supports_json_schema()
+            }
+            (LLMBackend::GenericApi(_), ResponseFormat::JsonObject) => true,
+            (LLMBackend::GenericApi(_), ResponseFormat::JsonSchema { .. }) => false,
+            (LLMBackend::Anthropic(_), _) => false,
+        }
+    }
+
+    /// Whether the backend can return more than one choice, and token log probabilities.
+    ///
+    /// Many OpenAI compatible servers silently ignore `n`, so generic APIs are assumed not to.
+    pub fn supports_n_choices(&self) -> bool {
+        match self {
+            LLMBackend::OpenAI(_) => true,
+            LLMBackend::OpenAIResponses(_)
+            | LLMBackend::Anthropic(_)
+            | LLMBackend::GenericApi(_) => false,
+        }
+    }
+
+    pub fn api_model(&self) -> &ApiLLMModel {
+        match self {
+            LLMBackend::OpenAI(b) => &b.model,
+            LLMBackend::OpenAIResponses(b) => &b.model,
+            LLMBackend::Anthropic(b) => &b.model,
+            LLMBackend::GenericApi(b) => &b.model,
+        }
+    }
+
     pub fn model_id(&self) -> &str {
         match self {
             LLMBackend::OpenAI(b) => &b.model.model_base.model_id,
+            LLMBackend::OpenAIResponses(b) => &b.model.model_base.model_id,
             LLMBackend::Anthropic(b) => &b.model.model_base.model_id,
             LLMBackend::GenericApi(b) => &b.model.model_base.model_id,
         }
@@ -102,6 +156,7 @@ impl LLMBackend {
     pub fn model_ctx_size(&self) -> u64 {
         match self {
             LLMBackend::OpenAI(b) => b.model.model_base.model_ctx_size,
+            LLMBackend::OpenAIResponses(b) => b.model.model_base.model_ctx_size,
             LLMBackend::Anthropic(b) => b.model.model_base.model_ctx_size,
             LLMBackend::GenericApi(b) => b.model.model_base.model_ctx_size,
         }
@@ -110,6 +165,7 @@ impl LLMBackend {
     pub fn inference_ctx_size(&self) -> u64 {
         match self {
             LLMBackend::OpenAI(b) => b.model.model_base.inference_ctx_size,
+            LLMBackend::OpenAIResponses(b) => b.model.model_base.inference_ctx_size,
             LLMBackend::Anthropic(b) => b.model.model_base.inference_ctx_size,
             LLMBackend::GenericApi(b) => b.model.model_base.inference_ctx_size,
         }
@@ -118,6 +174,7 @@ impl LLMBackend {
     pub fn tokenizer(&self) -> &Arc<Tokenizer> {
         match self {
             LLMBackend::OpenAI(b) => &b.model.model_base.tokenizer,
+            LLMBackend::OpenAIResponses(b) => &b.model.model_base.tokenizer,
             LLMBackend::Anthropic(b) => &b.model.model_base.tokenizer,
             LLMBackend::GenericApi(b) => &b.model.model_base.tokenizer,
         }
@@ -128,6 +185,9 @@ impl LLMBackend {
             LLMBackend::OpenAI(b) => {
                 Arc::clone(&b.model.model_base.tokenizer) as Arc<dyn PromptTokenizer>
             }
+            LLMBackend::OpenAIResponses(b) => {
+                Arc::clone(&b.model.model_base.tokenizer) as Arc<dyn PromptTokenizer>
+            }
             LLMBackend::Anthropic(b) => {
                 Arc::clone(&b.model.model_base.tokenizer) as Arc<dyn PromptTokenizer>
             }
@@ -141,6 +201,9 @@ impl LLMBackend {
         if let Some(logit_bias) = logit_bias {
             match self {
                 LLMBackend::OpenAI(_) => logit_bias.build_openai(self.tokenizer())?,
+                LLMBackend::OpenAIResponses(_) => {
+                    crate::bail!("The OpenAI Responses API does not support logit bias")
+                }
                 LLMBackend::Anthropic(_) => unreachable!("Anthropic does not support logit bias"),
                 LLMBackend::GenericApi(_) => logit_bias.build_openai(self.tokenizer())?,
             };
@@ -155,6 +218,15 @@ impl LLMBackend {
         }
     }
 
+    pub fn openai_responses(
+        &self,
+    ) -> crate::Result<&api::openai::responses::OpenAIResponsesBackend> {
+        match self {
+            LLMBackend::OpenAIResponses(b) => Ok(b),
+            _ => crate::bail!("Backend is not openai_responses"),
+        }
+    }
+
     pub fn anthropic(&self) -> crate::Result<&api::anthropic::AnthropicBackend> {
         match self {
             LLMBackend::Anthropic(b) => Ok(b),
@@ -172,6 +244,7 @@ impl LLMBackend {
     pub fn shutdown(&self) {
         match self {
             LLMBackend::OpenAI(_) => (),
+            LLMBackend::OpenAIResponses(_) => (),
             LLMBackend::Anthropic(_) => (),
             LLMBackend::GenericApi(_) => (),
         }
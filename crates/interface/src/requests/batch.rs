@@ -0,0 +1,623 @@
+use crate::llms::{
+    api::{
+        anthropic::{
+            batch::{
+                AnthropicBatch, AnthropicBatchRequest, AnthropicBatchResult,
+                AnthropicBatchResultLine,
+            },
+            completion::{AnthropicCompletionRequest, AnthropicCompletionResponse},
+        },
+        error::{ApiError, ClientError},
+        openai::{
+            batch::{OpenAIBatch, OpenAIBatchRequestLine, OpenAIBatchResultLine},
+            completion::{OpenAICompletionRequest, OpenAICompletionResponse},
+        },
+    },
+    LLMBackend,
+};
+use crate::requests::completion::{CompletionError, CompletionRequest, CompletionResponse};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The results of a batch keyed by custom id, with the error of every failed request.
+pub type BatchResults = HashMap<String, Result<CompletionResponse, CompletionError>>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BatchError {
+    #[error("CompletionError: {0}")]
+    CompletionError(#[from] CompletionError),
+    #[error("ClientError: {0}")]
+    ClientError(#[from] ClientError),
+    #[error("UnsupportedBackend: {0}")]
+    UnsupportedBackend(String),
+    #[error("DuplicateCustomId: {0}")]
+    DuplicateCustomId(String),
+    #[error("NotSubmitted: The batch has not been submitted")]
+    NotSubmitted,
+    #[error("AlreadySubmitted: Requests can't be added to a submitted batch")]
+    AlreadySubmitted,
+    #[error("NotFinished: The batch is {0:?}")]
+    NotFinished(BatchStatus),
+    #[error("IoError: {0}")]
+    IoError(#[from] std::io::Error),
+    /// Json serialization or deserialization errors
+    #[error("JsonError: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+/// The status of a batch, common to the providers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    /// The batch has not been submitted yet.
+    #[default]
+    Pending,
+    Validating,
+    InProgress,
+    Finalizing,
+    Completed,
+    Failed,
+    Expired,
+    Cancelling,
+    Cancelled,
+}
+
+impl BatchStatus {
+    /// Whether the provider stopped processing the batch, its results can be fetched.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            BatchStatus::Completed
+                | BatchStatus::Failed
+                | BatchStatus::Expired
+                | BatchStatus::Cancelled
+        )
+    }
+
+    fn from_openai(batch: &OpenAIBatch) -> Self {
+        match batch.status.as_str() {
+            "validating" => BatchStatus::Validating,
+            "in_progress" => BatchStatus::InProgress,
+            "finalizing" => BatchStatus::Finalizing,
+            "completed" => BatchStatus::Completed,
+            "failed" => BatchStatus::Failed,
+            "expired" => BatchStatus::Expired,
+            "cancelling" => BatchStatus::Cancelling,
+            "cancelled" => BatchStatus::Cancelled,
+            status => {
+                crate::warn!("Unknown batch status: {}", status);
+                BatchStatus::InProgress
+            }
+        }
+    }
+
+    fn from_anthropic(batch: &AnthropicBatch) -> Self {
+        match batch.processing_status.as_str() {
+            "ended" => BatchStatus::Completed,
+            "canceling" => BatchStatus::Cancelling,
+            _ => BatchStatus::InProgress,
+        }
+    }
+}
+
+/// The state of a batch job. It is saved to the state file of the job, so a restarted process
+/// can resume polling and fetch the results of a submitted batch.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BatchState {
+    /// The provider id of the batch, `None` before it is submitted.
+    pub batch_id: Option<String>,
+    pub status: BatchStatus,
+    /// The custom ids of the requests, in submission order.
+    pub custom_ids: Vec<String>,
+    /// The OpenAI file with the results of the successful requests.
+    pub output_file_id: Option<String>,
+    /// The OpenAI file with the results of the failed requests.
+    pub error_file_id: Option<String>,
+}
+
+/// A bulk job sent to the batch API of the provider, processed asynchronously at a discount.
+///
+/// Requests are keyed by a custom id, which keys their responses in the results. With a
+/// state file, the job is saved once submitted and on every poll, and [`BatchJob::resume`]
+/// continues it after a crash. Responses of a resumed job are built with the default
+/// generation settings of the backend, as the original requests are not saved.
+///
+/// # Example
+///
+/// ```no_run
+/// # use alith_interface::requests::batch::BatchJob;
+/// # use alith_interface::requests::completion::CompletionRequest;
+/// # async fn run(backend: std::sync::Arc<alith_interface::llms::LLMBackend>) -> Result<(), Box<dyn std::error::Error>> {
+/// let mut job = BatchJob::new(backend.clone()).state_file("batch.json");
+/// for (id, text) in [("doc-1", "first document"), ("doc-2", "second document")] {
+///     let mut request = CompletionRequest::new(backend.clone());
+///     request.prompt.add_user_message()?.set_content(text);
+///     job.add_request(id, request)?;
+/// }
+/// job.submit().await?;
+/// job.wait(std::time::Duration::from_secs(60)).await?;
+/// for (id, response) in job.results().await? {
+///     println!("{id}: {}", response?.content);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct BatchJob {
+    backend: Arc<LLMBackend>,
+    requests: HashMap<String, CompletionRequest>,
+    state_file: Option<PathBuf>,
+    pub state: BatchState,
+}
+
+impl BatchJob {
+    pub fn new(backend: Arc<LLMBackend>) -> Self {
+        Self {
+            backend,
+            requests: HashMap::new(),
+            state_file: None,
+            state: BatchState::default(),
+        }
+    }
+
+    /// Resumes the job saved in the state file.
+    pub fn resume<P: Into<PathBuf>>(backend: Arc<LLMBackend>, path: P) -> Result<Self, BatchError> {
+        let path = path.into();
+        let state = serde_json::from_slice(&std::fs::read(&path)?)?;
+        Ok(Self {
+            backend,
+            requests: HashMap::new(),
+            state_file: Some(path),
+            state,
+        })
+    }
+
+    /// Saves the state of the job to the file once it is submitted and on every poll.
+    pub fn state_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.state_file = Some(path.into());
+        self
+    }
+
+    /// Adds a request to the batch. The custom id must be unique within the batch.
+    pub fn add_request<S: Into<String>>(
+        &mut self,
+        custom_id: S,
+        mut request: CompletionRequest,
+    ) -> Result<&mut Self, BatchError> {
+        if self.state.batch_id.is_some() {
+            return Err(BatchError::AlreadySubmitted);
+        }
+        let custom_id = custom_id.into();
+        if self.requests.contains_key(&custom_id) {
+            return Err(BatchError::DuplicateCustomId(custom_id));
+        }
+        request.prepare_request()?;
+        self.state.custom_ids.push(custom_id.clone());
+        self.requests.insert(custom_id, request);
+        Ok(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.custom_ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.custom_ids.is_empty()
+    }
+
+    /// The requests of the batch in the JSONL format of the provider, one request per line.
+    pub fn to_jsonl(&self) -> Result<String, BatchError> {
+        match self.backend.as_ref() {
+            LLMBackend::OpenAI(_) => to_jsonl(&self.openai_requests()?),
+            LLMBackend::Anthropic(_) => to_jsonl(&self.anthropic_requests()?),
+            LLMBackend::OpenAIResponses(_) | LLMBackend::GenericApi(_) => {
+                Err(unsupported_backend())
+            }
+        }
+    }
+
+    /// Submits the batch to the provider.
+    pub async fn submit(&mut self) -> Result<&BatchState, BatchError> {
+        if self.state.batch_id.is_some() {
+            return Err(BatchError::AlreadySubmitted);
+        }
+        match self.backend.as_ref() {
+            LLMBackend::OpenAI(b) => {
+                let batch = b.batch_create(to_jsonl(&self.openai_requests()?)?).await?;
+                self.update_from_openai(&batch);
+            }
+            LLMBackend::Anthropic(b) => {
+                let batch = b.batch_create(self.anthropic_requests()?).await?;
+                self.update_from_anthropic(&batch);
+            }
+            LLMBackend::OpenAIResponses(_) | LLMBackend::GenericApi(_) => {
+                return Err(unsupported_backend())
+            }
+        }
+        self.save()?;
+        Ok(&self.state)
+    }
+
+    /// Refreshes the status of the submitted batch.
+    pub async fn poll(&mut self) -> Result<BatchStatus, BatchError> {
+        let batch_id = self
+            .state
+            .batch_id
+            .clone()
+            .ok_or(BatchError::NotSubmitted)?;
+        match self.backend.as_ref() {
+            LLMBackend::OpenAI(b) => {
+                let batch = b.batch_retrieve(&batch_id).await?;
+                self.update_from_openai(&batch);
+            }
+            LLMBackend::Anthropic(b) => {
+                let batch = b.batch_retrieve(&batch_id).await?;
+                self.update_from_anthropic(&batch);
+            }
+            LLMBackend::OpenAIResponses(_) | LLMBackend::GenericApi(_) => {
+                return Err(unsupported_backend())
+            }
+        }
+        self.save()?;
+        Ok(self.state.status)
+    }
+
+    /// Polls the batch every `interval` until the provider stops processing it.
+    pub async fn wait(&mut self, interval: Duration) -> Result<BatchStatus, BatchError> {
+        loop {
+            let status = self.poll().await?;
+            if status.is_finished() {
+                return Ok(status);
+            }
+            crate::info!("Batch {:?} is {:?}", self.state.batch_id, status);
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Fetches the results of the finished batch, keyed by custom id.
+    pub async fn results(&self) -> Result<BatchResults, BatchError> {
+        let batch_id = self
+            .state
+            .batch_id
+            .as_deref()
+            .ok_or(BatchError::NotSubmitted)?;
+        if !self.state.status.is_finished() {
+            return Err(BatchError::NotFinished(self.state.status));
+        }
+        let mut results = BatchResults::new();
+        match self.backend.as_ref() {
+            LLMBackend::OpenAI(b) => {
+                for file_id in [&self.state.output_file_id, &self.state.error_file_id]
+                    .into_iter()
+                    .flatten()
+                {
+                    let content = b.batch_file_content(file_id).await?;
+                    for line in parse_jsonl::<OpenAIBatchResultLine>(&content)? {
+                        let result = self.response_from_openai(&line);
+                        results.insert(line.custom_id, result);
+                    }
+                }
+            }
+            LLMBackend::Anthropic(b) => {
+                let content = b.batch_results(batch_id).await?;
+                for line in parse_jsonl::<AnthropicBatchResultLine>(&content)? {
+                    let result = self.response_from_anthropic(&line);
+                    results.insert(line.custom_id, result);
+                }
+            }
+            LLMBackend::OpenAIResponses(_) | LLMBackend::GenericApi(_) => {
+                return Err(unsupported_backend())
+            }
+        }
+        Ok(results)
+    }
+
+    fn openai_requests(&self) -> Result<Vec<OpenAIBatchRequestLine>, BatchError> {
+        self.state
+            .custom_ids
+            .iter()
+            .map(|custom_id| {
+                Ok(OpenAIBatchRequestLine::new(
+                    custom_id.clone(),
+                    OpenAICompletionRequest::new(&self.requests[custom_id])?,
+                ))
+            })
+            .collect()
+    }
+
+    fn anthropic_requests(&self) -> Result<Vec<AnthropicBatchRequest>, BatchError> {
+        self.state
+            .custom_ids
+            .iter()
+            .map(|custom_id| {
+                Ok(AnthropicBatchRequest {
+                    custom_id: custom_id.clone(),
+                    params: AnthropicCompletionRequest::new(&self.requests[custom_id])?,
+                })
+            })
+            .collect()
+    }
+
+    fn update_from_openai(&mut self, batch: &OpenAIBatch) {
+        self.state.batch_id = Some(batch.id.clone());
+        self.state.status = BatchStatus::from_openai(batch);
+        self.state.output_file_id = batch.output_file_id.clone();
+        self.state.error_file_id = batch.error_file_id.clone();
+    }
+
+    fn update_from_anthropic(&mut self, batch: &AnthropicBatch) {
+        self.state.batch_id = Some(batch.id.clone());
+        self.state.status = BatchStatus::from_anthropic(batch);
+    }
+
+    fn save(&self) -> Result<(), BatchError> {
+        if let Some(path) = &self.state_file {
+            std::fs::write(path, serde_json::to_vec_pretty(&self.state)?)?;
+        }
+        Ok(())
+    }
+
+    /// The request of a custom id, or a default one for a resumed job.
+    fn request(&self, custom_id: &str) -> CompletionRequest {
+        self.requests
+            .get(custom_id)
+            .cloned()
+            .unwrap_or_else(|| CompletionRequest::new(Arc::clone(&self.backend)))
+    }
+
+    fn response_from_openai(
+        &self,
+        line: &OpenAIBatchResultLine,
+    ) -> Result<CompletionResponse, CompletionError> {
+        match (&line.response, &line.error) {
+            (Some(response), _) if response.status_code == 200 => {
+                let res: OpenAICompletionResponse = serde_json::from_value(response.body.clone())?;
+                CompletionResponse::new_from_openai(&self.request(&line.custom_id), res)
+            }
+            (Some(response), _) => {
+                let mut error: ApiError = serde_json::from_value(response.body["error"].clone())?;
+                error.status = Some(response.status_code);
+                Err(ClientError::ApiError(error).into())
+            }
+            (None, Some(error)) => Err(batch_error(&error.message, error.code.clone()).into()),
+            (None, None) => Err(CompletionError::ResponseContentEmpty),
+        }
+    }
+
+    fn response_from_anthropic(
+        &self,
+        line: &AnthropicBatchResultLine,
+    ) -> Result<CompletionResponse, CompletionError> {
+        match &line.result {
+            AnthropicBatchResult::Succeeded { message } => {
+                let res: AnthropicCompletionResponse = serde_json::from_value(message.clone())?;
+                CompletionResponse::new_from_anthropic(&self.request(&line.custom_id), res)
+            }
+            AnthropicBatchResult::Errored { error } => {
+                Err(ClientError::ApiError(error.error.clone()).into())
+            }
+            AnthropicBatchResult::Canceled => {
+                Err(batch_error("The request was canceled", Some("canceled".to_string())).into())
+            }
+            AnthropicBatchResult::Expired => {
+                Err(batch_error("The request expired", Some("expired".to_string())).into())
+            }
+        }
+    }
+}
+
+fn unsupported_backend() -> BatchError {
+    BatchError::UnsupportedBackend("Only OpenAI and Anthropic provide a batch API".to_string())
+}
+
+fn to_jsonl<T: Serialize>(lines: &[T]) -> Result<String, BatchError> {
+    let mut jsonl = String::new();
+    for line in lines {
+        jsonl.push_str(&serde_json::to_string(line)?);
+        jsonl.push('\n');
+    }
+    Ok(jsonl)
+}
+
+fn batch_error(message: &str, code: Option<String>) -> ClientError {
+    ClientError::ApiError(ApiError {
+        message: message.to_string(),
+        r#type: Some("batch_error".to_string()),
+        param: None,
+        code,
+        status: None,
+    })
+}
+
+fn parse_jsonl<T: DeserializeOwned>(content: &[u8]) -> Result<Vec<T>, serde_json::Error> {
+    String::from_utf8_lossy(content)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llms::api::{anthropic::AnthropicBackend, openai::OpenAIBackend};
+    use crate::llms::api::{anthropic::AnthropicConfig, openai::OpenAIConfig};
+    use alith_models::api_model::ApiLLMModel;
+
+    fn request(backend: &Arc<LLMBackend>, content: &str) -> CompletionRequest {
+        let request = CompletionRequest::new(Arc::clone(backend));
+        request
+            .prompt
+            .add_user_message()
+            .unwrap()
+            .set_content(content);
+        request
+    }
+
+    #[tokio::test]
+    async fn test_openai_batch_job() {
+        let mut server = mockito::Server::new_async().await;
+        let upload = server
+            .mock("POST", "/files")
+            .match_body(mockito::Matcher::Regex(
+                r#""custom_id":"doc-2""#.to_string(),
+            ))
+            .with_body(r#"{"id": "file-in"}"#)
+            .create_async()
+            .await;
+        let create = server
+            .mock("POST", "/batches")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "input_file_id": "file-in",
+                "endpoint": "/v1/chat/completions",
+            })))
+            .with_body(r#"{"id": "batch-1", "status": "validating"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/batches/batch-1")
+            .with_body(
+                r#"{"id": "batch-1", "status": "completed",
+                    "output_file_id": "file-out", "error_file_id": "file-err"}"#,
+            )
+            .create_async()
+            .await;
+        let completion = serde_json::json!({
+            "id": "chatcmpl-1",
+            "created": 0,
+            "model": "gpt-4o-mini",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "positive"},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 10, "completion_tokens": 1, "total_tokens": 11}
+        });
+        server
+            .mock("GET", "/files/file-out/content")
+            .with_body(format!(
+                "{}\n",
+                serde_json::json!({
+                    "custom_id": "doc-1",
+                    "response": {"status_code": 200, "body": completion},
+                    "error": null
+                })
+            ))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/files/file-err/content")
+            .with_body(format!(
+                "{}\n",
+                serde_json::json!({
+                    "custom_id": "doc-2",
+                    "response": {"status_code": 400, "body": {"error": {
+                        "message": "Invalid request", "type": "invalid_request_error"
+                    }}},
+                    "error": null
+                })
+            ))
+            .create_async()
+            .await;
+
+        let mut config = OpenAIConfig::new();
+        config.api_config.host = server.url();
+        config.api_config.api_key = Some("test-key".to_string().into());
+        config.logging_config.logging_enabled = false;
+        let backend = Arc::new(LLMBackend::OpenAI(
+            OpenAIBackend::new(config, ApiLLMModel::gpt_4_o_mini()).unwrap(),
+        ));
+
+        let state_file = std::env::temp_dir().join("alith_openai_batch_job.json");
+        let mut job = BatchJob::new(Arc::clone(&backend)).state_file(&state_file);
+        job.add_request("doc-1", request(&backend, "I love it"))
+            .unwrap()
+            .add_request("doc-2", request(&backend, "I hate it"))
+            .unwrap();
+        assert!(matches!(
+            job.add_request("doc-1", request(&backend, "again")),
+            Err(BatchError::DuplicateCustomId(_))
+        ));
+        assert_eq!(job.to_jsonl().unwrap().lines().count(), 2);
+        job.submit().await.unwrap();
+        upload.assert_async().await;
+        create.assert_async().await;
+
+        // A restarted process resumes the submitted job from its state file.
+        let mut job = BatchJob::resume(Arc::clone(&backend), &state_file).unwrap();
+        assert_eq!(job.state.batch_id.as_deref(), Some("batch-1"));
+        assert_eq!(
+            job.wait(Duration::from_millis(1)).await.unwrap(),
+            BatchStatus::Completed
+        );
+        let results = job.results().await.unwrap();
+        assert_eq!(results["doc-1"].as_ref().unwrap().content, "positive");
+        match &results["doc-2"] {
+            Err(CompletionError::ClientError(ClientError::ApiError(e))) => {
+                assert_eq!(e.status, Some(400))
+            }
+            res => panic!("unexpected result: {:?}", res.as_ref().map(|r| &r.content)),
+        }
+        std::fs::remove_file(state_file).ok();
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_batch_job() {
+        let mut server = mockito::Server::new_async().await;
+        let create = server
+            .mock("POST", "/messages/batches")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "requests": [{"custom_id": "doc-1", "params": {"model": "claude-3-haiku-20240307"}}]
+            })))
+            .with_body(r#"{"id": "msgbatch-1", "processing_status": "in_progress"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/messages/batches/msgbatch-1")
+            .with_body(r#"{"id": "msgbatch-1", "processing_status": "ended"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/messages/batches/msgbatch-1/results")
+            .with_body(format!(
+                "{}\n{}\n",
+                serde_json::json!({"custom_id": "doc-1", "result": {"type": "succeeded", "message": {
+                    "id": "msg-1",
+                    "type": "message",
+                    "role": "assistant",
+                    "model": "claude-3-haiku-20240307",
+                    "content": [{"type": "text", "text": "positive"}],
+                    "stop_reason": "end_turn",
+                    "stop_sequence": null,
+                    "usage": {"input_tokens": 10, "output_tokens": 1}
+                }}}),
+                serde_json::json!({"custom_id": "doc-2", "result": {"type": "expired"}}),
+            ))
+            .create_async()
+            .await;
+
+        let mut config = AnthropicConfig::new();
+        config.api_config.host = server.url();
+        config.api_config.api_key = Some("test-key".to_string().into());
+        config.logging_config.logging_enabled = false;
+        let backend = Arc::new(LLMBackend::Anthropic(
+            AnthropicBackend::new(config, ApiLLMModel::claude_3_haiku()).unwrap(),
+        ));
+
+        let mut job = BatchJob::new(Arc::clone(&backend));
+        job.add_request("doc-1", request(&backend, "I love it"))
+            .unwrap()
+            .add_request("doc-2", request(&backend, "I hate it"))
+            .unwrap();
+        assert!(matches!(job.results().await, Err(BatchError::NotSubmitted)));
+        job.submit().await.unwrap();
+        create.assert_async().await;
+        assert_eq!(job.poll().await.unwrap(), BatchStatus::Completed);
+
+        let results = job.results().await.unwrap();
+        assert_eq!(results["doc-1"].as_ref().unwrap().content, "positive");
+        assert!(results["doc-2"].is_err());
+    }
+}
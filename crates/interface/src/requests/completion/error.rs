@@ -11,6 +11,17 @@ pub enum CompletionError {
     RequestTokenLimitError(#[from] alith_prompt::RequestTokenLimitError),
     #[error("StopReasonUnsupported: {0}")]
     StopReasonUnsupported(String),
+    /// The provider reported a failed or incomplete response, with its error code or reason.
+    #[error("ResponseError: {message} ({})", code.as_deref().unwrap_or("no code"))]
+    ResponseError {
+        code: Option<String>,
+        message: String,
+    },
+    /// The request was cancelled with its cancellation token.
+    ///
+    /// The usage is the one of the responses received before, e.g. discarded retries.
+    #[error("Cancelled")]
+    Cancelled { usage: super::TokenUsage },
     #[error("ExceededRetryCount")]
     ExceededRetryCount {
         message: String,
@@ -33,3 +44,28 @@ pub enum CompletionError {
     #[error("JsonError: {0}")]
     JsonError(#[from] serde_json::Error),
 }
+
+impl CompletionError {
+    /// Whether the request failed because of the provider, e.g. a rate limit, a server error
+    /// or a timeout, so it may succeed when retried later or with another provider.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            CompletionError::ClientError(e) => e.is_transient(),
+            CompletionError::ResponseError { code, .. } => {
+                matches!(
+                    code.as_deref(),
+                    Some("server_error" | "rate_limit_exceeded")
+                )
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the quota of the account at the provider is spent.
+    pub fn is_quota_exhausted(&self) -> bool {
+        match self {
+            CompletionError::ClientError(e) => e.is_quota_exhausted(),
+            _ => false,
+        }
+    }
+}
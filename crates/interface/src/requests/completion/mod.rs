@@ -1,10 +1,14 @@
 pub mod error;
 pub mod request;
 pub mod response;
+pub mod response_format;
 pub mod tool;
 
-pub use super::res_components::{GenerationSettings, TimingUsage, TokenUsage};
+pub use super::res_components::{
+    GenerationSettings, InferenceProbabilities, TimingUsage, TokenUsage, TopProbabilities,
+};
 pub use error::CompletionError;
 pub use request::CompletionRequest;
-pub use response::{CompletionFinishReason, CompletionResponse};
+pub use response::{CompletionChoice, CompletionFinishReason, CompletionResponse, ThinkingBlock};
+pub use response_format::{JsonSchemaFormat, ResponseFormat};
 pub use tool::{ToolChoice, ToolDefinition};
@@ -1,6 +1,9 @@
-use super::{error::CompletionError, response::CompletionResponse, ToolChoice, ToolDefinition};
+use super::{
+    error::CompletionError, response::CompletionResponse, ResponseFormat, ThinkingBlock,
+    TokenUsage, ToolChoice, ToolDefinition,
+};
 use crate::{
-    llms::LLMBackend,
+    llms::{api::error::ClientError, LLMBackend},
     requests::{
         completion::response::CompletionFinishReason, logit_bias::LogitBias,
         req_components::RequestConfig, stop_sequence::StopSequences,
@@ -8,6 +11,7 @@ use crate::{
 };
 use alith_prompt::LLMPrompt;
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
 pub struct CompletionRequest {
     pub start_time: std::time::Instant,
@@ -20,6 +24,18 @@ pub struct CompletionRequest {
     pub llm_interface_errors: Vec<CompletionError>,
     pub tools: Vec<ToolDefinition>,
     pub tool_choice: ToolChoice,
+    /// Whether the model may call several tools in one response, `None` for the provider default.
+    pub parallel_tool_calls: Option<bool>,
+    pub response_format: ResponseFormat,
+    /// Aborts the request and its retries with [CompletionError::Cancelled] once cancelled.
+    pub cancellation_token: Option<CancellationToken>,
+    /// Continues the conversation of a previous response on stateful APIs, e.g. the OpenAI
+    /// Responses API, so only the messages after the last assistant message are sent.
+    pub previous_response_id: Option<String>,
+    /// The [`CompletionResponse::thinking_blocks`] of the assistant messages of the prompt, one
+    /// entry per assistant message in order. Anthropic requires them back with the assistant
+    /// messages of a tool use loop while extended thinking is enabled.
+    pub assistant_thinking: Vec<Vec<ThinkingBlock>>,
 }
 
 impl Clone for CompletionRequest {
@@ -34,7 +50,12 @@ impl Clone for CompletionRequest {
             backend: Arc::clone(&self.backend),
             llm_interface_errors: Vec::new(),
             tools: Vec::new(),
-            tool_choice: ToolChoice::Auto,
+            tool_choice: self.tool_choice.clone(),
+            parallel_tool_calls: self.parallel_tool_calls,
+            response_format: self.response_format.clone(),
+            cancellation_token: self.cancellation_token.clone(),
+            previous_response_id: self.previous_response_id.clone(),
+            assistant_thinking: self.assistant_thinking.clone(),
         }
     }
 }
@@ -52,6 +73,11 @@ impl CompletionRequest {
             llm_interface_errors: Vec::new(),
             tools: Vec::new(),
             tool_choice: ToolChoice::default(),
+            parallel_tool_calls: None,
+            response_format: ResponseFormat::default(),
+            cancellation_token: None,
+            previous_response_id: None,
+            assistant_thinking: Vec::new(),
         }
     }
 
@@ -60,11 +86,29 @@ impl CompletionRequest {
         self.stop_sequences.sequences.clear();
         self.grammar_string = None;
         self.logit_bias = None;
+        self.assistant_thinking.clear();
+        self.response_format = ResponseFormat::default();
     }
 
-    pub async fn request(&mut self) -> crate::Result<CompletionResponse, CompletionError> {
-        self.llm_interface_errors.clear();
-        self.start_time = std::time::Instant::now();
+    /// The prompt tokens plus the maximum response tokens, as counted by provider rate limits.
+    pub(crate) fn estimated_tokens(&self) -> u64 {
+        self.backend
+            .get_total_prompt_tokens(&self.prompt)
+            .unwrap_or_default()
+            + self.config.actual_request_tokens.unwrap_or_default()
+    }
+
+    /// Sets the structured output format of the response.
+    ///
+    /// The API backends send the format itself. Local backends are constrained with the
+    /// equivalent grammar instead, set in [CompletionRequest::grammar_string] from
+    /// [ResponseFormat::gbnf_grammar].
+    pub fn set_response_format(&mut self, response_format: ResponseFormat) {
+        self.response_format = response_format;
+    }
+
+    /// Builds the logit bias and sets the response token limit, returning the prompt tokens.
+    pub(crate) fn prepare_request(&mut self) -> crate::Result<u64, CompletionError> {
         self.backend
             .build_logit_bias(&mut self.logit_bias)
             .map_err(|e| CompletionError::RequestBuilderError(e.to_string()))?;
@@ -77,10 +121,24 @@ impl CompletionRequest {
         self.config
             .set_max_tokens_for_request(total_prompt_tokens)
             .map_err(CompletionError::RequestTokenLimitError)?;
+        Ok(total_prompt_tokens)
+    }
+
+    pub async fn request(&mut self) -> crate::Result<CompletionResponse, CompletionError> {
+        self.llm_interface_errors.clear();
+        self.start_time = std::time::Instant::now();
+        let total_prompt_tokens = self.prepare_request()?;
 
         let mut retry_count: u8 = 0;
+        // The usage of the responses discarded by retries
+        let mut partial_usage = TokenUsage::default();
 
         loop {
+            if self.is_cancelled() {
+                return Err(CompletionError::Cancelled {
+                    usage: partial_usage,
+                });
+            }
             if retry_count >= self.config.retry_after_fail_n_times {
                 let llm_interface_error = CompletionError::ExceededRetryCount {
                     message: format!("Request failed after {retry_count} attempts."),
@@ -92,12 +150,18 @@ impl CompletionRequest {
             }
             tracing::info!("{}", self);
             match self.backend.completion_request(self).await {
+                Err(CompletionError::ClientError(ClientError::Cancelled)) => {
+                    return Err(CompletionError::Cancelled {
+                        usage: partial_usage,
+                    });
+                }
                 Err(e) => {
                     tracing::warn!(?e);
                     retry_count += 1;
                     match e {
                         CompletionError::RequestBuilderError { .. }
                         | CompletionError::StopReasonUnsupported { .. }
+                        | CompletionError::ResponseError { .. }
                         | CompletionError::ClientError { .. } => {
                             return Err(e);
                         }
@@ -128,6 +192,7 @@ impl CompletionRequest {
                             };
                             tracing::warn!(?llm_interface_error);
                             self.llm_interface_errors.push(llm_interface_error);
+                            partial_usage.accumulate(&res.token_usage);
                             retry_count += 1;
                             if self.config.increase_limit_on_fail {
                                 self.config
@@ -144,6 +209,7 @@ impl CompletionRequest {
                                 let llm_interface_error = CompletionError::StopLimitRetry;
                                 tracing::warn!(?llm_interface_error);
                                 self.llm_interface_errors.push(llm_interface_error);
+                                partial_usage.accumulate(&res.token_usage);
                                 self.config
                                     .increase_token_limit(total_prompt_tokens, None)?;
                                 retry_count += 1;
@@ -160,6 +226,13 @@ impl CompletionRequest {
         }
     }
 
+    /// Whether the cancellation token of the request was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation_token
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+    }
+
     pub fn set_base_req_stop_sequences(
         &mut self,
         stop_word_done: &Option<String>,
@@ -190,6 +263,7 @@ impl std::fmt::Display for CompletionRequest {
         writeln!(f, "  prompt: {}", self.prompt)?;
         writeln!(f, "  stop_sequences: {:?}", self.stop_sequences.to_vec())?;
         writeln!(f, "  grammar_string: {:?}", self.grammar_string)?;
+        writeln!(f, "  response_format: {:?}", self.response_format)?;
         write!(f, "  config: {}", self.config)?;
         write!(f, "  tools: {:?}", self.tools)
     }
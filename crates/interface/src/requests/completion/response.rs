@@ -8,10 +8,22 @@ use super::tool::ToolCall;
 pub struct CompletionResponse {
     /// A unique identifier for the chat completion.
     pub id: String,
+    /// The id continuing the conversation of a stateful API, see
+    /// [`CompletionRequest::previous_response_id`].
+    ///
+    /// [`CompletionRequest::previous_response_id`]: super::CompletionRequest::previous_response_id
+    pub response_id: Option<String>,
     /// If batched, the index of the choice in the list of choices.
     pub index: Option<u32>,
     /// The generated completion.
     pub content: String,
+    /// The reasoning of the model before the completion, when the model returns it.
+    pub reasoning: Option<String>,
+    /// The signed thinking blocks of the response, which must be sent back with the assistant
+    /// message in the following turns, see [`CompletionRequest::assistant_thinking`].
+    ///
+    /// [`CompletionRequest::assistant_thinking`]: super::CompletionRequest::assistant_thinking
+    pub thinking_blocks: Vec<ThinkingBlock>,
     pub finish_reason: CompletionFinishReason,
     pub completion_probabilities: Option<Vec<InferenceProbabilities>>,
     /// True if the context size was exceeded during generation, i.e. the number of tokens provided in the prompt (tokens_evaluated) plus tokens generated (tokens predicted) exceeded the context size (n_ctx)
@@ -20,6 +32,63 @@ pub struct CompletionResponse {
     pub timing_usage: TimingUsage,
     pub token_usage: TokenUsage,
     pub tool_calls: Option<Vec<ToolCall>>,
+    /// Every choice of the response when more than one was requested, the first one included.
+    pub choices: Vec<CompletionChoice>,
+}
+
+impl CompletionResponse {
+    /// The mean probability of the generated tokens, when log probabilities were requested.
+    pub fn confidence(&self) -> Option<f32> {
+        self.completion_probabilities
+            .as_deref()
+            .and_then(mean_token_probability)
+    }
+}
+
+/// A thinking block of an extended thinking response, signed by the provider so it can be
+/// verified when it is sent back.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ThinkingBlock {
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    /// Thinking encrypted by the safety systems of the provider.
+    RedactedThinking {
+        data: String,
+    },
+}
+
+/// One of the choices of a completion requested with [`RequestConfig::n_choices`] above `1`.
+///
+/// [`RequestConfig::n_choices`]: crate::requests::req_components::RequestConfig::n_choices
+pub struct CompletionChoice {
+    /// The index of the choice in the list of choices.
+    pub index: u32,
+    pub content: String,
+    /// The thinking of the model before the content, when the model returns it.
+    pub reasoning: Option<String>,
+    pub finish_reason: CompletionFinishReason,
+    pub completion_probabilities: Option<Vec<InferenceProbabilities>>,
+    pub tool_calls: Option<Vec<ToolCall>>,
+}
+
+impl CompletionChoice {
+    /// The mean probability of the generated tokens, when log probabilities were requested.
+    pub fn confidence(&self) -> Option<f32> {
+        self.completion_probabilities
+            .as_deref()
+            .and_then(mean_token_probability)
+    }
+}
+
+fn mean_token_probability(probabilities: &[InferenceProbabilities]) -> Option<f32> {
+    let logprobs: Vec<f32> = probabilities.iter().filter_map(|p| p.prob).collect();
+    if logprobs.is_empty() {
+        return None;
+    }
+    Some(logprobs.iter().map(|logprob| logprob.exp()).sum::<f32>() / logprobs.len() as f32)
 }
 
 impl std::fmt::Display for CompletionResponse {
@@ -27,6 +96,9 @@ impl std::fmt::Display for CompletionResponse {
         writeln!(f)?;
         writeln!(f, "CompletionResponse:")?;
         writeln!(f, "    content: {:?}", self.content)?;
+        if let Some(reasoning) = &self.reasoning {
+            writeln!(f, "    reasoning: {:?}", reasoning)?;
+        }
         writeln!(f, "    finish_reason: {}", self.finish_reason)?;
         write!(f, "    generation_settings: {}", self.generation_settings)?;
         write!(f, "    timing_usage: {}", self.timing_usage)?;
@@ -35,7 +107,7 @@ impl std::fmt::Display for CompletionResponse {
     }
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CompletionFinishReason {
     /// The completion finished because the model generated the EOS token.
     Eos,
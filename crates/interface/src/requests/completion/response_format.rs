@@ -0,0 +1,340 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// The format the model is required to produce its output in.
+///
+/// Serializes to the OpenAI `response_format` object, also accepted by the OpenAI compatible
+/// endpoints of Gemini and Ollama. Local backends take the equivalent GBNF grammar, see
+/// [ResponseFormat::gbnf_grammar].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Free form text.
+    #[default]
+    Text,
+    /// Any syntactically valid JSON object.
+    JsonObject,
+    /// JSON that conforms to the given schema.
+    JsonSchema { json_schema: JsonSchemaFormat },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    /// The name of the response format. Must be a-z, A-Z, 0-9, or contain underscores and dashes.
+    pub name: String,
+    /// A description of what the response format is for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The JSON schema the output must conform to.
+    pub schema: Value,
+    /// Whether to enable strict schema adherence. Strict mode requires every object to set
+    /// `additionalProperties: false` and list all of its properties as required.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+impl ResponseFormat {
+    pub fn json_schema<S: Into<String>>(name: S, schema: Value, strict: bool) -> Self {
+        ResponseFormat::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name: name.into(),
+                description: None,
+                schema,
+                strict: Some(strict),
+            },
+        }
+    }
+
+    #[inline]
+    pub fn is_text(&self) -> bool {
+        matches!(self, ResponseFormat::Text)
+    }
+
+    /// A GBNF grammar constraining local backends to the requested format.
+    pub fn gbnf_grammar(&self) -> Option<String> {
+        match self {
+            ResponseFormat::Text => None,
+            ResponseFormat::JsonObject => {
+                let mut converter = GbnfConverter::new(&Value::Null);
+                converter
+                    .rules
+                    .insert("root".to_string(), "object".to_string());
+                Some(converter.format())
+            }
+            ResponseFormat::JsonSchema { json_schema } => {
+                let mut converter = GbnfConverter::new(&json_schema.schema);
+                let root = converter.visit(&json_schema.schema, "root");
+                if root != "root" {
+                    converter.rules.insert("root".to_string(), root);
+                }
+                Some(converter.format())
+            }
+        }
+    }
+}
+
+fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let path = reference.strip_prefix("#/")?;
+    path.split('/').try_fold(root, |value, segment| {
+        value.get(segment.replace("~1", "/").replace("~0", "~"))
+    })
+}
+
+const GBNF_PRIMITIVES: [(&str, &str); 9] = [
+    ("ws", r#"[ \t\n]*"#),
+    (
+        "char",
+        r#"[^"\\\x7F\x00-\x1F] | "\\" (["\\/bfnrt] | "u" [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F])"#,
+    ),
+    ("string", r#""\"" char* "\"" ws"#),
+    (
+        "number",
+        r#""-"? ([0-9] | [1-9] [0-9]*) ("." [0-9]+)? ([eE] [-+]? [0-9]+)? ws"#,
+    ),
+    ("integer", r#""-"? ([0-9] | [1-9] [0-9]*) ws"#),
+    ("boolean", r#"("true" | "false") ws"#),
+    ("null", r#""null" ws"#),
+    (
+        "value",
+        r#"object | array | string | number | boolean | null"#,
+    ),
+    (
+        "object",
+        r#""{" ws (string ":" ws value ("," ws string ":" ws value)*)? "}" ws"#,
+    ),
+];
+
+const GBNF_ARRAY: (&str, &str) = ("array", r#""[" ws (value ("," ws value)*)? "]" ws"#);
+
+/// Converts a JSON schema into a GBNF grammar, the grammar format used by llama.cpp.
+struct GbnfConverter<'a> {
+    root: &'a Value,
+    rules: BTreeMap<String, String>,
+    refs: BTreeMap<String, String>,
+}
+
+impl<'a> GbnfConverter<'a> {
+    fn new(root: &'a Value) -> Self {
+        let mut rules = BTreeMap::new();
+        for (name, rule) in GBNF_PRIMITIVES.iter().chain([GBNF_ARRAY].iter()) {
+            rules.insert(name.to_string(), rule.to_string());
+        }
+        Self {
+            root,
+            rules,
+            refs: BTreeMap::new(),
+        }
+    }
+
+    fn format(&self) -> String {
+        let mut grammar = format!("root ::= {}\n", self.rules["root"]);
+        for (name, rule) in self.rules.iter().filter(|(name, _)| *name != "root") {
+            grammar.push_str(&format!("{name} ::= {rule}\n"));
+        }
+        grammar
+    }
+
+    /// Returns the rule body for the schema, adding any rules it needs under `name`.
+    fn visit(&mut self, schema: &Value, name: &str) -> String {
+        let body = self.rule_body(schema, name);
+        self.rules.insert(name.to_string(), body);
+        name.to_string()
+    }
+
+    fn rule_body(&mut self, schema: &Value, name: &str) -> String {
+        let Value::Object(map) = schema else {
+            return "value".to_string();
+        };
+        if let Some(Value::String(reference)) = map.get("$ref") {
+            return self.visit_ref(reference);
+        }
+        if let Some(value) = map.get("const") {
+            return format!("{} ws", gbnf_literal(&value.to_string()));
+        }
+        if let Some(Value::Array(values)) = map.get("enum") {
+            let alts = values
+                .iter()
+                .map(|v| gbnf_literal(&v.to_string()))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            return format!("({alts}) ws");
+        }
+        for key in ["anyOf", "oneOf"] {
+            if let Some(Value::Array(schemas)) = map.get(key) {
+                return self.alternatives(schemas.iter(), name);
+            }
+        }
+        if let Some(Value::Array(schemas)) = map.get("allOf") {
+            if let [schema] = schemas.as_slice() {
+                return self.rule_body(schema, name);
+            }
+        }
+        match map.get("type") {
+            Some(Value::String(t)) => self.typed(t, map, name),
+            Some(Value::Array(types)) => {
+                let typed = types
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .enumerate()
+                    .map(|(i, t)| {
+                        let body = self.typed(t, map, &format!("{name}-{i}"));
+                        format!("({body})")
+                    })
+                    .collect::<Vec<_>>();
+                typed.join(" | ")
+            }
+            _ if map.contains_key("properties") => self.typed("object", map, name),
+            _ => "value".to_string(),
+        }
+    }
+
+    fn alternatives<'s>(&mut self, schemas: impl Iterator<Item = &'s Value>, name: &str) -> String {
+        schemas
+            .enumerate()
+            .map(|(i, schema)| self.visit(schema, &format!("{name}-{i}")))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    fn visit_ref(&mut self, reference: &str) -> String {
+        if let Some(rule) = self.refs.get(reference) {
+            return rule.clone();
+        }
+        let rule = format!(
+            "ref-{}",
+            rule_name(reference.rsplit('/').next().unwrap_or(reference))
+        );
+        self.refs.insert(reference.to_string(), rule.clone());
+        match resolve_ref(self.root, reference) {
+            Some(schema) => {
+                let schema = schema.clone();
+                self.visit(&schema, &rule)
+            }
+            None => {
+                self.rules.insert(rule.clone(), "value".to_string());
+                rule
+            }
+        }
+    }
+
+    fn typed(&mut self, t: &str, map: &Map<String, Value>, name: &str) -> String {
+        match t {
+            "string" | "number" | "integer" | "boolean" | "null" => t.to_string(),
+            "array" => match map.get("items") {
+                Some(items) => {
+                    let item = self.visit(items, &format!("{name}-item"));
+                    format!(r#""[" ws ({item} ("," ws {item})*)? "]" ws"#)
+                }
+                None => "array".to_string(),
+            },
+            "object" => {
+                let Some(Value::Object(properties)) = map.get("properties") else {
+                    return "object".to_string();
+                };
+                let required: Vec<&str> = match map.get("required") {
+                    Some(Value::Array(required)) => {
+                        required.iter().filter_map(Value::as_str).collect()
+                    }
+                    _ => Vec::new(),
+                };
+                let mut required_kvs = Vec::new();
+                let mut optional_kvs = Vec::new();
+                for (key, schema) in properties {
+                    let value = self.visit(schema, &format!("{name}-{}", rule_name(key)));
+                    let kv = format!(
+                        r#"{} ws ":" ws {value}"#,
+                        gbnf_literal(&Value::String(key.clone()).to_string())
+                    );
+                    if required.contains(&key.as_str()) {
+                        required_kvs.push(kv);
+                    } else {
+                        optional_kvs.push(kv);
+                    }
+                }
+                let mut body = r#""{" ws "#.to_string();
+                body.push_str(&required_kvs.join(r#" "," ws "#));
+                if required_kvs.is_empty() {
+                    body.push_str(&optional_tail(&optional_kvs, false));
+                } else {
+                    body.push_str(&optional_tail(&optional_kvs, true));
+                }
+                body.push_str(r#" "}" ws"#);
+                body
+            }
+            _ => "value".to_string(),
+        }
+    }
+}
+
+/// Each optional property may be present or absent, but the properties stay in schema order.
+fn optional_tail(kvs: &[String], leading_comma: bool) -> String {
+    if kvs.is_empty() {
+        return String::new();
+    }
+    if leading_comma {
+        return kvs
+            .iter()
+            .map(|kv| format!(r#" ("," ws {kv})?"#))
+            .collect::<String>();
+    }
+    let alts = (0..kvs.len())
+        .map(|i| format!("{}{}", kvs[i], optional_tail(&kvs[i + 1..], true)))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    format!(" ({alts})?")
+}
+
+fn gbnf_literal(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn rule_name(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_openai_serialization() {
+        let format = ResponseFormat::json_schema("person", json!({"type": "object"}), true);
+        assert_eq!(
+            serde_json::to_value(&format).unwrap(),
+            json!({
+                "type": "json_schema",
+                "json_schema": {"name": "person", "schema": {"type": "object"}, "strict": true}
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(ResponseFormat::JsonObject).unwrap(),
+            json!({"type": "json_object"})
+        );
+    }
+
+    #[test]
+    fn test_gbnf_grammar() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "age": {"type": "integer"},
+                "tags": {"type": "array", "items": {"$ref": "#/definitions/Tag"}},
+            },
+            "required": ["name", "age"],
+            "definitions": {"Tag": {"enum": ["a", "b"]}},
+        });
+        let grammar = ResponseFormat::json_schema("person", schema, false)
+            .gbnf_grammar()
+            .unwrap();
+        assert!(grammar.starts_with("root ::= \"{\" ws "));
+        assert!(grammar.contains(r#""\"age\"" ws ":" ws root-age "," ws "\"name\"" ws ":" ws root-name ("," ws "\"tags\"" ws ":" ws root-tags)? "}" ws"#));
+        assert!(grammar.contains(r#"ref-Tag ::= ("\"a\"" | "\"b\"") ws"#));
+        assert!(grammar.contains("root-tags-item ::= ref-Tag\n"));
+        assert_eq!(ResponseFormat::Text.gbnf_grammar(), None);
+    }
+}
@@ -7,15 +7,27 @@ pub struct ToolDefinition {
     pub parameters: serde_json::Value,
 }
 
+/// How the model may use the tools of a request.
 #[derive(Clone, Debug, Deserialize, Serialize, Default, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ToolChoice {
+    /// The model decides whether to call tools.
     #[default]
     Auto,
+    /// The model must call at least one tool.
     Any,
-    Tool {
-        name: String,
-    },
+    /// The model must call the named tool.
+    Tool { name: String },
+    /// The model must not call tools.
+    None,
+}
+
+impl ToolChoice {
+    /// Forces the model to call the named tool.
+    #[inline]
+    pub fn tool<S: Into<String>>(name: S) -> Self {
+        Self::Tool { name: name.into() }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Default, PartialEq)]
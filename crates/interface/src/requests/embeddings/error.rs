@@ -7,6 +7,11 @@ pub enum EmbeddingsError {
     ClientError(#[from] crate::llms::api::error::ClientError),
     #[error("LocalClientError: {0}")]
     LocalClientError(String),
+    #[error("UnsupportedBackend: {0}")]
+    UnsupportedBackend(String),
+    /// The request was cancelled with its cancellation token.
+    #[error("Cancelled")]
+    Cancelled,
     #[error("ExceededRetryCount")]
     ExceededRetryCount {
         message: String,
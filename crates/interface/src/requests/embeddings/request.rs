@@ -1,11 +1,15 @@
+use crate::llms::api::error::ClientError;
 use crate::{llms::LLMBackend, requests::req_components::RequestConfig};
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
 use super::{response::EmbeddingsResponse, EmbeddingsError};
 
 pub struct EmbeddingsRequest {
     pub model: String,
     pub input: Vec<String>,
+    /// Aborts the request and its retries with [EmbeddingsError::Cancelled] once cancelled.
+    pub cancellation_token: Option<CancellationToken>,
     backend: Arc<LLMBackend>,
     config: RequestConfig,
     llm_interface_errors: Vec<EmbeddingsError>,
@@ -16,6 +20,7 @@ impl Clone for EmbeddingsRequest {
         Self {
             model: self.model.clone(),
             input: self.input.clone(),
+            cancellation_token: self.cancellation_token.clone(),
             backend: self.backend.clone(),
             config: self.config.clone(),
             llm_interface_errors: Vec::new(),
@@ -28,6 +33,7 @@ impl EmbeddingsRequest {
         EmbeddingsRequest {
             model: String::new(),
             input: Vec::new(),
+            cancellation_token: None,
             backend: Arc::clone(&backend),
             config: RequestConfig::new(backend.model_ctx_size(), backend.inference_ctx_size()),
             llm_interface_errors: Vec::new(),
@@ -42,6 +48,13 @@ impl EmbeddingsRequest {
         self.llm_interface_errors.clear();
         let mut retry_count: u8 = 0;
         loop {
+            if self
+                .cancellation_token
+                .as_ref()
+                .is_some_and(|token| token.is_cancelled())
+            {
+                return Err(EmbeddingsError::Cancelled);
+            }
             if retry_count >= self.config.retry_after_fail_n_times {
                 let llm_interface_error = EmbeddingsError::ExceededRetryCount {
                     message: format!("Request failed after {retry_count} attempts."),
@@ -53,12 +66,16 @@ impl EmbeddingsRequest {
             }
             tracing::info!("{}", self);
             match self.backend.embeddings_request(self).await {
+                Err(EmbeddingsError::ClientError(ClientError::Cancelled)) => {
+                    return Err(EmbeddingsError::Cancelled);
+                }
                 Err(e) => {
                     tracing::warn!(?e);
                     retry_count += 1;
                     match e {
                         EmbeddingsError::RequestBuilderError { .. }
-                        | EmbeddingsError::ClientError { .. } => {
+                        | EmbeddingsError::ClientError { .. }
+                        | EmbeddingsError::UnsupportedBackend { .. } => {
                             return Err(e);
                         }
 
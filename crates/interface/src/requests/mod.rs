@@ -1,3 +1,4 @@
+pub mod batch;
 pub mod completion;
 pub mod embeddings;
 // pub mod constraints;
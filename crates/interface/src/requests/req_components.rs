@@ -143,10 +143,67 @@ pub struct RequestConfig {
     /// When set to `true`, the system will cache the prompt and reuse it for the next request.
     /// This can potentially improve performance for repeated or similar queries.
     ///
-    /// Supported LLMs
+    /// For Anthropic models, cache breakpoints are set on the tools, the system prompt and the
+    /// long messages of the request, and cached tokens are reported in
+    /// [`crate::requests::completion::TokenUsage::tokens_cached`].
+    ///
+    /// Supported LLMs: anthropic
     ///
     /// Defaults to `false`.
     pub cache_prompt: bool,
+    /// Return the log probability of each generated token.
+    ///
+    /// The probabilities are available in [`crate::requests::completion::CompletionResponse::completion_probabilities`].
+    ///
+    /// Supported LLMs: openai
+    ///
+    /// Defaults to `false`.
+    pub logprobs: bool,
+    /// The number of most likely tokens to return with their log probability at each position.
+    ///
+    /// Accepts values between 0 and 20, and requires [`RequestConfig::logprobs`].
+    ///
+    /// Supported LLMs: openai
+    ///
+    /// Defaults to `None`.
+    pub top_logprobs: Option<u8>,
+    /// The number of choices to generate for the request, the `n` parameter of the API.
+    ///
+    /// Every choice is returned in [`crate::requests::completion::CompletionResponse::choices`].
+    /// Input tokens are billed once, output tokens for every choice.
+    ///
+    /// Supported LLMs: openai
+    ///
+    /// Defaults to `1`.
+    pub n_choices: u8,
+    /// The number of tokens the model may spend thinking before it responds.
+    ///
+    /// The thinking is returned in [`crate::requests::completion::CompletionResponse::reasoning`].
+    /// The budget is added to the response tokens, which are reduced to keep the total within
+    /// the maximum output tokens of the model. It must be at least 1024, below the maximum
+    /// output tokens, and fixes the temperature to its default.
+    ///
+    /// Supported LLMs: anthropic
+    ///
+    /// Defaults to `None` (no extended thinking).
+    pub thinking_budget_tokens: Option<u64>,
+    /// How much effort a reasoning model spends thinking before it responds.
+    ///
+    /// Ignored by models without reasoning support.
+    ///
+    /// Supported LLMs: openai
+    ///
+    /// Defaults to `None` (the model default).
+    pub reasoning_effort: Option<ReasoningEffort>,
+}
+
+/// The `reasoning_effort` of a reasoning model request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
 }
 
 impl RequestConfig {
@@ -164,6 +221,11 @@ impl RequestConfig {
             retry_after_fail_n_times: 3,
             increase_limit_on_fail: false,
             cache_prompt: false,
+            logprobs: false,
+            top_logprobs: None,
+            n_choices: 1,
+            thinking_budget_tokens: None,
+            reasoning_effort: None,
         }
     }
 
@@ -305,6 +367,37 @@ pub trait RequestConfigTrait {
         self.config().cache_prompt = cache_prompt;
         self
     }
+
+    /// Sets the value of [RequestConfig::logprobs].
+    fn logprobs(&mut self, logprobs: bool) -> &mut Self {
+        self.config().logprobs = logprobs;
+        self
+    }
+
+    /// Sets the value of [RequestConfig::top_logprobs], and enables [RequestConfig::logprobs].
+    fn top_logprobs(&mut self, top_logprobs: u8) -> &mut Self {
+        self.config().logprobs = true;
+        self.config().top_logprobs = Some(top_logprobs.min(20));
+        self
+    }
+
+    /// Sets the value of [RequestConfig::thinking_budget_tokens].
+    fn thinking_budget_tokens(&mut self, thinking_budget_tokens: u64) -> &mut Self {
+        self.config().thinking_budget_tokens = Some(thinking_budget_tokens);
+        self
+    }
+
+    /// Sets the value of [RequestConfig::reasoning_effort].
+    fn reasoning_effort(&mut self, reasoning_effort: ReasoningEffort) -> &mut Self {
+        self.config().reasoning_effort = Some(reasoning_effort);
+        self
+    }
+
+    /// Sets the value of [RequestConfig::n_choices].
+    fn n_choices(&mut self, n_choices: u8) -> &mut Self {
+        self.config().n_choices = n_choices.max(1);
+        self
+    }
 }
 
 impl std::fmt::Display for RequestConfig {
@@ -336,6 +429,15 @@ impl std::fmt::Display for RequestConfig {
             "    increase_limit_on_fail: {:?}",
             self.increase_limit_on_fail
         )?;
-        writeln!(f, "    cache_prompt: {:?}", self.cache_prompt)
+        writeln!(f, "    cache_prompt: {:?}", self.cache_prompt)?;
+        writeln!(f, "    logprobs: {:?}", self.logprobs)?;
+        writeln!(f, "    top_logprobs: {:?}", self.top_logprobs)?;
+        writeln!(f, "    n_choices: {:?}", self.n_choices)?;
+        writeln!(
+            f,
+            "    thinking_budget_tokens: {:?}",
+            self.thinking_budget_tokens
+        )?;
+        writeln!(f, "    reasoning_effort: {:?}", self.reasoning_effort)
     }
 }
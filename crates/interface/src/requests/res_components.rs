@@ -1,19 +1,22 @@
 use super::completion::request::CompletionRequest;
 use crate::llms::api::{
     anthropic::completion::AnthropicCompletionResponse,
-    openai::completion::OpenAICompletionResponse,
+    openai::{completion::OpenAICompletionResponse, responses::OpenAIResponsesResponse},
 };
+use alith_models::api_model::ApiLLMModel;
 
 /// The log probability of the completion.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InferenceProbabilities {
     /// The token selected by the model.
     pub content: Option<String>,
+    /// The log probability of the selected token.
+    pub prob: Option<f32>,
     /// An array of length n_probs.
     pub top_probs: Vec<TopProbabilities>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TopProbabilities {
     /// The token.
     pub token: String,
@@ -49,7 +52,7 @@ impl GenerationSettings {
             presence_penalty: req.config.presence_penalty,
             temperature: req.config.temperature,
             top_p: req.config.top_p,
-            n_choices: 1,
+            n_choices: req.config.n_choices,
             n_predict: req.config.actual_request_tokens.map(|x| x as i32),
             n_ctx: req.config.inference_ctx_size,
             logit_bias: None,
@@ -63,6 +66,25 @@ impl GenerationSettings {
         }
     }
 
+    pub fn new_from_openai_responses(
+        req: &CompletionRequest,
+        res: &OpenAIResponsesResponse,
+    ) -> Self {
+        Self {
+            model: res.model.to_owned(),
+            frequency_penalty: req.config.frequency_penalty,
+            presence_penalty: req.config.presence_penalty,
+            temperature: req.config.temperature,
+            top_p: req.config.top_p,
+            n_choices: 1,
+            n_predict: req.config.actual_request_tokens.map(|x| x as i32),
+            n_ctx: req.config.inference_ctx_size,
+            logit_bias: None,
+            grammar: None,
+            stop_sequences: Vec::new(),
+        }
+    }
+
     pub fn new_from_anthropic(req: &CompletionRequest, res: &AnthropicCompletionResponse) -> Self {
         Self {
             model: res.model.to_string(),
@@ -161,10 +183,18 @@ impl std::fmt::Display for TimingUsage {
     }
 }
 
+/// The price of cached prompt tokens relative to the input token price (Anthropic pricing).
+pub const CACHE_READ_COST_FACTOR: f32 = 0.1;
+/// The price of prompt tokens written to the cache relative to the input token price.
+pub const CACHE_WRITE_COST_FACTOR: f32 = 1.25;
+
 /// Token statistics for the completion request.
+#[derive(Debug, Clone, Default)]
 pub struct TokenUsage {
     /// Number of tokens from the prompt which could be re-used from previous completion (n_past)
     pub tokens_cached: Option<u32>,
+    /// Number of tokens from the prompt written to the provider cache.
+    pub tokens_cache_written: Option<u32>,
     /// Number of tokens evaluated in total from the prompt. Same as tokens_evaluated.
     pub prompt_tokens: u32,
     /// Number of tokens in the generated completion. Same as predicted_n.
@@ -182,6 +212,7 @@ impl TokenUsage {
         if let Some(usage) = &res.usage {
             Self {
                 tokens_cached: None,
+                tokens_cache_written: None,
                 prompt_tokens: usage.prompt_tokens,
                 completion_tokens: usage.completion_tokens,
                 total_tokens: usage.total_tokens,
@@ -191,6 +222,7 @@ impl TokenUsage {
         } else {
             Self {
                 tokens_cached: None,
+                tokens_cache_written: None,
                 prompt_tokens: 0,
                 completion_tokens: 0,
                 total_tokens: 0,
@@ -200,12 +232,67 @@ impl TokenUsage {
         }
     }
 
-    pub fn new_from_anthropic(res: &AnthropicCompletionResponse) -> Self {
+    pub fn new_from_openai_responses(res: &OpenAIResponsesResponse) -> Self {
+        let usage = res.usage.as_ref();
         Self {
             tokens_cached: None,
-            prompt_tokens: res.usage.input_tokens,
+            tokens_cache_written: None,
+            prompt_tokens: usage.map(|u| u.input_tokens).unwrap_or_default(),
+            completion_tokens: usage.map(|u| u.output_tokens).unwrap_or_default(),
+            total_tokens: usage.map(|u| u.total_tokens).unwrap_or_default(),
+            dollar_cost: None,
+            cents_cost: None,
+        }
+    }
+
+    /// Fills in the dollar and cents cost of the request from the model pricing.
+    ///
+    /// Cached prompt tokens are billed at [CACHE_READ_COST_FACTOR] and tokens written to the
+    /// cache at [CACHE_WRITE_COST_FACTOR] of the input token price.
+    pub fn with_cost(mut self, model: &ApiLLMModel) -> Self {
+        let cached = self.tokens_cached.unwrap_or_default();
+        let written = self.tokens_cache_written.unwrap_or_default();
+        let uncached = self.prompt_tokens.saturating_sub(cached + written);
+        self.dollar_cost = model
+            .request_cost(uncached, self.completion_tokens)
+            .map(|cost| {
+                cost + model.cost_per_m_in_tokens
+                    * (cached as f32 * CACHE_READ_COST_FACTOR
+                        + written as f32 * CACHE_WRITE_COST_FACTOR)
+                    / 1_000_000.0
+            });
+        self.cents_cost = self.dollar_cost.map(|dollars| dollars * 100.0);
+        self
+    }
+
+    /// Adds the usage of another request, e.g. a discarded retry.
+    pub fn accumulate(&mut self, other: &TokenUsage) {
+        fn add<T: std::ops::Add<Output = T>>(a: Option<T>, b: Option<T>) -> Option<T> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            }
+        }
+        self.tokens_cached = add(self.tokens_cached, other.tokens_cached);
+        self.tokens_cache_written = add(self.tokens_cache_written, other.tokens_cache_written);
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+        self.dollar_cost = add(self.dollar_cost, other.dollar_cost);
+        self.cents_cost = add(self.cents_cost, other.cents_cost);
+    }
+
+    pub fn new_from_anthropic(res: &AnthropicCompletionResponse) -> Self {
+        let cached = res.usage.cache_read_input_tokens.unwrap_or_default();
+        let written = res.usage.cache_creation_input_tokens.unwrap_or_default();
+        // Anthropic doesn't count the cached tokens in the input tokens
+        let prompt_tokens = res.usage.input_tokens + cached + written;
+        Self {
+            tokens_cached: Some(cached),
+            tokens_cache_written: Some(written),
+            prompt_tokens,
             completion_tokens: res.usage.output_tokens,
-            total_tokens: res.usage.input_tokens + res.usage.output_tokens,
+            total_tokens: prompt_tokens + res.usage.output_tokens,
             dollar_cost: None,
             cents_cost: None,
         }
@@ -216,6 +303,11 @@ impl std::fmt::Display for TokenUsage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f)?;
         writeln!(f, "    tokens_cached: {:?}", self.tokens_cached)?;
+        writeln!(
+            f,
+            "    tokens_cache_written: {:?}",
+            self.tokens_cache_written
+        )?;
         writeln!(f, "    prompt_tokens: {:?}", self.prompt_tokens)?;
         writeln!(f, "    completion_tokens: {:?}", self.completion_tokens)?;
         writeln!(f, "    total_tokens: {:?}", self.total_tokens)?;
@@ -1,4 +1,4 @@
-use super::ApiLLMModel;
+use super::{ApiLLMModel, ApiModelCapabilities};
 use crate::{tokenizer::Tokenizer, LLMModelBase};
 use std::sync::Arc;
 
@@ -22,6 +22,7 @@ impl ApiLLMModel {
     pub fn claude_3_opus() -> ApiLLMModel {
         let model_id = "claude-3-opus-20240229".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -33,12 +34,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 75.00,
             tokens_per_message: 3,
             tokens_per_name: None,
+            capabilities,
         }
     }
 
     pub fn claude_3_sonnet() -> ApiLLMModel {
         let model_id = "claude-3-sonnet-20240229".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -50,12 +53,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 15.00,
             tokens_per_message: 3,
             tokens_per_name: None,
+            capabilities,
         }
     }
 
     pub fn claude_3_haiku() -> ApiLLMModel {
         let model_id = "claude-3-haiku-20240307".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -67,12 +72,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 1.25,
             tokens_per_message: 3,
             tokens_per_name: None,
+            capabilities,
         }
     }
 
     pub fn claude_3_5_sonnet() -> ApiLLMModel {
         let model_id = "claude-3-5-sonnet-20240620".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -84,12 +91,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 15.00,
             tokens_per_message: 3,
             tokens_per_name: None,
+            capabilities,
         }
     }
 
     pub fn claude_3_7_sonnet() -> ApiLLMModel {
         let model_id = "claude-3-7-sonnet-20250219".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -101,12 +110,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 15.00,
             tokens_per_message: 3,
             tokens_per_name: None,
+            capabilities,
         }
     }
 
     pub fn claude<S: ToString>(model_id: S) -> ApiLLMModel {
         let model_id = model_id.to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -118,6 +129,7 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 15.00,
             tokens_per_message: 3,
             tokens_per_name: None,
+            capabilities,
         }
     }
 }
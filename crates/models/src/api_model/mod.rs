@@ -1,8 +1,10 @@
 use super::LLMModelBase;
+use serde::{Deserialize, Serialize};
 
 pub mod anthropic;
 pub mod openai;
 pub mod perplexity;
+pub mod registry;
 
 #[derive(Clone)]
 pub struct ApiLLMModel {
@@ -11,6 +13,78 @@ pub struct ApiLLMModel {
     pub cost_per_m_out_tokens: f32,
     pub tokens_per_message: u32,
     pub tokens_per_name: Option<i32>,
+    /// The request parameters the model accepts.
+    pub capabilities: ApiModelCapabilities,
+}
+
+/// The features of an API model, which decide the parameters sent in its requests.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiModelCapabilities {
+    /// A reasoning model, limited with `max_completion_tokens` instead of `max_tokens` and
+    /// accepting a `reasoning_effort`.
+    pub reasoning: bool,
+    /// Accepts the sampling parameters: `temperature`, `top_p`, penalties and log probabilities.
+    pub sampling: bool,
+    /// Accepts system messages, otherwise they are sent as user messages.
+    pub system_messages: bool,
+    /// Supports `json_schema` structured outputs.
+    pub json_schema: bool,
+    /// Supports tool calls.
+    pub tools: bool,
+    /// Accepts images in the messages.
+    pub vision: bool,
+    /// Supports streamed responses.
+    pub streaming: bool,
+}
+
+impl Default for ApiModelCapabilities {
+    fn default() -> Self {
+        Self {
+            reasoning: false,
+            sampling: true,
+            system_messages: true,
+            json_schema: false,
+            tools: true,
+            vision: false,
+            streaming: true,
+        }
+    }
+}
+
+impl ApiModelCapabilities {
+    /// The capabilities of a model, inferred from its id.
+    pub fn from_model_id(model_id: &str) -> Self {
+        let is_reasoning = ["o1", "o3", "o4"]
+            .iter()
+            .any(|prefix| model_id.starts_with(prefix));
+        let is_legacy_reasoning =
+            model_id.starts_with("o1-mini") || model_id.starts_with("o1-preview");
+        Self {
+            reasoning: is_reasoning,
+            sampling: !is_reasoning,
+            system_messages: !is_legacy_reasoning,
+            json_schema: !is_legacy_reasoning
+                && ["gpt-4o", "gpt-4.1", "gpt-4.5", "o1", "o3", "o4"]
+                    .iter()
+                    .any(|prefix| model_id.starts_with(prefix)),
+            tools: !is_legacy_reasoning,
+            vision: !is_legacy_reasoning
+                && [
+                    "gpt-4o",
+                    "gpt-4-turbo",
+                    "gpt-4.1",
+                    "gpt-4.5",
+                    "o1",
+                    "o3",
+                    "o4",
+                    "claude-3",
+                ]
+                .iter()
+                .any(|prefix| model_id.starts_with(prefix)),
+            streaming: true,
+        }
+    }
 }
 
 impl Default for ApiLLMModel {
@@ -18,3 +92,23 @@ impl Default for ApiLLMModel {
         Self::gpt_4()
     }
 }
+
+impl ApiLLMModel {
+    /// The dollar cost of a request, `None` when the model pricing is unknown.
+    pub fn request_cost(&self, input_tokens: u32, output_tokens: u32) -> Option<f32> {
+        if self.cost_per_m_in_tokens == 0.0 && self.cost_per_m_out_tokens == 0.0 {
+            return None;
+        }
+        Some(
+            (input_tokens as f32 * self.cost_per_m_in_tokens
+                + output_tokens as f32 * self.cost_per_m_out_tokens)
+                / 1_000_000.0,
+        )
+    }
+
+    /// Whether the model supports `json_schema` structured outputs.
+    #[inline]
+    pub fn supports_json_schema(&self) -> bool {
+        self.capabilities.json_schema
+    }
+}
@@ -1,23 +1,36 @@
-use super::ApiLLMModel;
+use super::{ApiLLMModel, ApiModelCapabilities};
 use crate::{tokenizer::Tokenizer, LLMModelBase};
 use std::sync::Arc;
 
 impl ApiLLMModel {
     pub fn openai_model_from_model_id(model_id: &str) -> ApiLLMModel {
-        match model_id {
+        Self::try_openai_model_from_model_id(model_id)
+            .unwrap_or_else(|| panic!("Model ID ({model_id}) not found for ApiLLMModel"))
+    }
+
+    /// The preset of an OpenAI model, `None` when the model id is unknown.
+    pub fn try_openai_model_from_model_id(model_id: &str) -> Option<ApiLLMModel> {
+        let model = match model_id {
             "gpt-4" => Self::gpt_4(),
             "gpt-4-32k" => Self::gpt_4_32k(),
             "gpt-4-turbo" => Self::gpt_4_turbo(),
             "gpt-4o" => Self::gpt_4_o(),
             "gpt-3.5-turbo" => Self::gpt_3_5_turbo(),
             "gpt-4o-mini" => Self::gpt_3_5_turbo(),
-            _ => panic!("Model ID ({model_id}) not found for ApiLLMModel"),
-        }
+            "o1-mini" => Self::o1_mini(),
+            "o1-preview" => Self::o1_preview(),
+            "o1" => Self::o1(),
+            "o3-mini" => Self::o3_mini(),
+            "o4-mini" => Self::o4_mini(),
+            _ => return None,
+        };
+        Some(model)
     }
 
     pub fn gpt_4() -> ApiLLMModel {
         let model_id = "gpt-4".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -29,12 +42,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 60.00,
             tokens_per_message: 3,
             tokens_per_name: Some(1),
+            capabilities,
         }
     }
 
     pub fn gpt_4_32k() -> ApiLLMModel {
         let model_id = "gpt-4-32k".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -46,12 +61,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 120.00,
             tokens_per_message: 3,
             tokens_per_name: Some(1),
+            capabilities,
         }
     }
 
     pub fn gpt_4_turbo() -> ApiLLMModel {
         let model_id = "gpt-4-turbo".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -63,12 +80,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 30.00,
             tokens_per_message: 3,
             tokens_per_name: Some(1),
+            capabilities,
         }
     }
 
     pub fn gpt_3_5_turbo() -> ApiLLMModel {
         let model_id = "gpt-3.5-turbo".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -80,12 +99,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 1.50,
             tokens_per_message: 4,
             tokens_per_name: Some(-1),
+            capabilities,
         }
     }
 
     pub fn gpt_4_o_mini() -> ApiLLMModel {
         let model_id = "gpt-4o-mini".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -97,12 +118,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 0.60,
             tokens_per_message: 3,
             tokens_per_name: Some(1),
+            capabilities,
         }
     }
 
     pub fn gpt_4_o() -> ApiLLMModel {
         let model_id = "gpt-4o".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -114,12 +137,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 15.00,
             tokens_per_message: 3,
             tokens_per_name: Some(1),
+            capabilities,
         }
     }
 
     pub fn o1_mini() -> ApiLLMModel {
         let model_id = "o1-mini".to_string();
         let tokenizer = model_tokenizer("gpt-4o-mini");
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -131,12 +156,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 12.00,
             tokens_per_message: 4,
             tokens_per_name: Some(-1),
+            capabilities,
         }
     }
 
     pub fn o1_preview() -> ApiLLMModel {
         let model_id = "o1-preview".to_string();
         let tokenizer = model_tokenizer("gpt-4o-mini");
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -148,6 +175,64 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 60.00,
             tokens_per_message: 4,
             tokens_per_name: Some(-1),
+            capabilities,
+        }
+    }
+
+    pub fn o1() -> ApiLLMModel {
+        let model_id = "o1".to_string();
+        let tokenizer = model_tokenizer("gpt-4o");
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
+        ApiLLMModel {
+            model_base: LLMModelBase {
+                model_id,
+                model_ctx_size: 200000,
+                inference_ctx_size: 100000,
+                tokenizer,
+            },
+            cost_per_m_in_tokens: 15.00,
+            cost_per_m_out_tokens: 60.00,
+            tokens_per_message: 4,
+            tokens_per_name: Some(-1),
+            capabilities,
+        }
+    }
+
+    pub fn o3_mini() -> ApiLLMModel {
+        let model_id = "o3-mini".to_string();
+        let tokenizer = model_tokenizer("gpt-4o");
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
+        ApiLLMModel {
+            model_base: LLMModelBase {
+                model_id,
+                model_ctx_size: 200000,
+                inference_ctx_size: 100000,
+                tokenizer,
+            },
+            cost_per_m_in_tokens: 1.10,
+            cost_per_m_out_tokens: 4.40,
+            tokens_per_message: 4,
+            tokens_per_name: Some(-1),
+            capabilities,
+        }
+    }
+
+    pub fn o4_mini() -> ApiLLMModel {
+        let model_id = "o4-mini".to_string();
+        let tokenizer = model_tokenizer("gpt-4o");
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
+        ApiLLMModel {
+            model_base: LLMModelBase {
+                model_id,
+                model_ctx_size: 200000,
+                inference_ctx_size: 100000,
+                tokenizer,
+            },
+            cost_per_m_in_tokens: 1.10,
+            cost_per_m_out_tokens: 4.40,
+            tokens_per_message: 4,
+            tokens_per_name: Some(-1),
+            capabilities,
         }
     }
 }
@@ -1,29 +1,30 @@
-use super::ApiLLMModel;
+use super::{ApiLLMModel, ApiModelCapabilities};
 use crate::{tokenizer::Tokenizer, LLMModelBase};
 use std::sync::Arc;
 
 impl ApiLLMModel {
     pub fn perplexity_model_from_model_id(model_id: &str) -> ApiLLMModel {
-        if model_id.starts_with("llama-3.1-sonar-small") {
-            Self::sonar_small()
-        } else if model_id.starts_with("llama-3.1-sonar-large") {
-            Self::sonar_large()
-        } else if model_id.starts_with("llama-3.1-sonar-huge") {
-            Self::sonar_huge()
-        } else if model_id.contains("sonar-small") {
-            Self::sonar_small()
+        Self::try_perplexity_model_from_model_id(model_id)
+            .unwrap_or_else(|| panic!("Model ID ({model_id}) not found for ApiLLMModel"))
+    }
+
+    /// The preset of a Perplexity model, `None` when the model id is unknown.
+    pub fn try_perplexity_model_from_model_id(model_id: &str) -> Option<ApiLLMModel> {
+        if model_id.contains("sonar-small") {
+            Some(Self::sonar_small())
         } else if model_id.contains("sonar-large") {
-            Self::sonar_large()
+            Some(Self::sonar_large())
         } else if model_id.contains("sonar-huge") {
-            Self::sonar_huge()
+            Some(Self::sonar_huge())
         } else {
-            panic!("Model ID ({model_id}) not found for ApiLLMModel")
+            None
         }
     }
 
     pub fn sonar_small() -> ApiLLMModel {
         let model_id = "llama-3.1-sonar-small-128k-online".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -35,12 +36,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 0.1,
             tokens_per_message: 3,
             tokens_per_name: None,
+            capabilities,
         }
     }
 
     pub fn sonar_large() -> ApiLLMModel {
         let model_id = "llama-3.1-sonar-large-128k-online".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -52,12 +55,14 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 0.5,
             tokens_per_message: 3,
             tokens_per_name: None,
+            capabilities,
         }
     }
 
     pub fn sonar_huge() -> ApiLLMModel {
         let model_id = "llama-3.1-sonar-huge-128k-online".to_string();
         let tokenizer = model_tokenizer(&model_id);
+        let capabilities = ApiModelCapabilities::from_model_id(&model_id);
         ApiLLMModel {
             model_base: LLMModelBase {
                 model_id,
@@ -69,6 +74,7 @@ impl ApiLLMModel {
             cost_per_m_out_tokens: 2.5,
             tokens_per_message: 3,
             tokens_per_name: None,
+            capabilities,
         }
     }
 }
@@ -0,0 +1,299 @@
+//! A registry of API models, loaded from TOML or JSON, with the built-in presets as defaults.
+//!
+//! ```toml
+//! [models."deepseek-chat"]
+//! provider = "openai_compatible"
+//! base_url = "https://api.deepseek.com"
+//! api_key_env = "DEEPSEEK_API_KEY"
+//! context_size = 64000
+//! max_output = 8192
+//! tokenizer = { tiktoken = "gpt-4o" }
+//! pricing = { input = 0.27, output = 1.10 }
+//! capabilities = { tools = true, json_schema = false }
+//! ```
+
+use super::{ApiLLMModel, ApiModelCapabilities};
+use crate::{tokenizer::Tokenizer, LLMModelBase};
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The environment variable with the path of the registry file loaded by
+/// [`ModelRegistry::from_env`].
+pub const MODEL_REGISTRY_ENV_VAR: &str = "ALITH_MODEL_REGISTRY";
+
+/// The API serving a model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelProvider {
+    #[serde(rename = "openai")]
+    OpenAI,
+    /// The OpenAI Responses API.
+    #[serde(rename = "openai_responses")]
+    OpenAIResponses,
+    Anthropic,
+    Perplexity,
+    /// Any API compatible with the OpenAI chat completions, requires a `base_url`.
+    #[serde(rename = "openai_compatible")]
+    OpenAICompatible,
+}
+
+impl ModelProvider {
+    /// The provider of a built-in preset, inferred from the model id.
+    pub fn from_model_id(model_id: &str) -> Option<Self> {
+        if ["gpt", "o1", "o3", "o4"]
+            .iter()
+            .any(|prefix| model_id.starts_with(prefix))
+        {
+            Some(Self::OpenAI)
+        } else if model_id.starts_with("claude") {
+            Some(Self::Anthropic)
+        } else if model_id.starts_with("llama") || model_id.starts_with("sonar") {
+            Some(Self::Perplexity)
+        } else {
+            None
+        }
+    }
+}
+
+/// The tokenizer used to count the tokens of a model.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenizerChoice {
+    /// A tiktoken encoding, selected by an OpenAI model id.
+    Tiktoken(String),
+    /// The `tokenizer.json` of a Hugging Face repo.
+    HuggingFace(String),
+    /// A local `tokenizer.json` file.
+    File(PathBuf),
+}
+
+impl Default for TokenizerChoice {
+    fn default() -> Self {
+        Self::Tiktoken("gpt-4".to_string())
+    }
+}
+
+impl TokenizerChoice {
+    pub fn load(&self) -> Result<Tokenizer> {
+        match self {
+            Self::Tiktoken(model_id) => Tokenizer::new_tiktoken(model_id),
+            Self::HuggingFace(repo_id) => {
+                let hf_token = dotenvy::var("HUGGING_FACE_TOKEN").ok();
+                Tokenizer::new_from_hf_repo(hf_token.as_deref(), repo_id.as_str())
+            }
+            Self::File(path) => Tokenizer::new_from_tokenizer_json(path),
+        }
+    }
+}
+
+/// The dollar cost per million tokens.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelPricing {
+    pub input: f32,
+    pub output: f32,
+}
+
+/// The description of a model in the registry.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModelEntry {
+    pub provider: ModelProvider,
+    /// Overrides the host of the provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// The environment variable with the API key, overriding the one of the provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    /// The context size of the model, in tokens.
+    pub context_size: u64,
+    /// The maximum output of a request, in tokens.
+    pub max_output: u64,
+    #[serde(default)]
+    pub pricing: ModelPricing,
+    #[serde(default)]
+    pub tokenizer: TokenizerChoice,
+    #[serde(default)]
+    pub capabilities: ApiModelCapabilities,
+}
+
+impl ModelEntry {
+    /// Builds the model with the given id, loading its tokenizer.
+    pub fn to_model(&self, model_id: &str) -> Result<ApiLLMModel> {
+        Ok(ApiLLMModel {
+            model_base: LLMModelBase {
+                model_id: model_id.to_string(),
+                model_ctx_size: self.context_size,
+                inference_ctx_size: self.max_output,
+                tokenizer: Arc::new(self.tokenizer.load()?),
+            },
+            cost_per_m_in_tokens: self.pricing.input,
+            cost_per_m_out_tokens: self.pricing.output,
+            tokens_per_message: 3,
+            tokens_per_name: None,
+            capabilities: self.capabilities.clone(),
+        })
+    }
+}
+
+/// A model resolved by the registry, with the API serving it.
+#[derive(Clone)]
+pub struct ResolvedModel {
+    pub provider: ModelProvider,
+    pub base_url: Option<String>,
+    pub api_key_env: Option<String>,
+    pub model: ApiLLMModel,
+}
+
+/// The models known by name. The configured entries take precedence over the built-in presets.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelRegistry {
+    #[serde(default)]
+    pub models: HashMap<String, ModelEntry>,
+}
+
+impl ModelRegistry {
+    /// A registry without entries, resolving the built-in presets only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Loads a `.toml` or `.json` registry file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("json") => Self::from_json(&content),
+            _ => bail!("unsupported model registry file {}", path.display()),
+        }
+    }
+
+    /// Loads the registry file set in [`MODEL_REGISTRY_ENV_VAR`], if any.
+    pub fn from_env() -> Result<Self> {
+        match dotenvy::var(MODEL_REGISTRY_ENV_VAR) {
+            Ok(path) => Self::from_file(path),
+            Err(_) => Ok(Self::new()),
+        }
+    }
+
+    pub fn register<S: Into<String>>(&mut self, model_id: S, entry: ModelEntry) -> &mut Self {
+        self.models.insert(model_id.into(), entry);
+        self
+    }
+
+    /// Adds the entries of another registry, replacing the ones with the same id.
+    pub fn merge(&mut self, other: ModelRegistry) -> &mut Self {
+        self.models.extend(other.models);
+        self
+    }
+
+    /// The entry of a model: an exact match, else the longest id prefixing the model id, so
+    /// that `claude-3-opus` describes `claude-3-opus-20240229`.
+    pub fn get(&self, model_id: &str) -> Option<&ModelEntry> {
+        self.models.get(model_id).or_else(|| {
+            self.models
+                .iter()
+                .filter(|(id, _)| model_id.starts_with(id.as_str()))
+                .max_by_key(|(id, _)| id.len())
+                .map(|(_, entry)| entry)
+        })
+    }
+
+    /// Resolves a model from the registry entries, falling back to the built-in presets.
+    pub fn resolve(&self, model_id: &str) -> Result<ResolvedModel> {
+        if let Some(entry) = self.get(model_id) {
+            if entry.provider == ModelProvider::OpenAICompatible && entry.base_url.is_none() {
+                bail!("model {model_id} of an OpenAI compatible provider requires a base_url");
+            }
+            return Ok(ResolvedModel {
+                provider: entry.provider,
+                base_url: entry.base_url.clone(),
+                api_key_env: entry.api_key_env.clone(),
+                model: entry.to_model(model_id)?,
+            });
+        }
+        let provider = ModelProvider::from_model_id(model_id)
+            .ok_or_else(|| anyhow!("unknown model {model_id}"))?;
+        let model = match provider {
+            ModelProvider::OpenAI | ModelProvider::OpenAIResponses => {
+                ApiLLMModel::try_openai_model_from_model_id(model_id)
+            }
+            ModelProvider::Anthropic => Some(ApiLLMModel::anthropic_model_from_model_id(model_id)),
+            ModelProvider::Perplexity => ApiLLMModel::try_perplexity_model_from_model_id(model_id),
+            ModelProvider::OpenAICompatible => None,
+        }
+        .ok_or_else(|| anyhow!("unknown model {model_id}"))?;
+        Ok(ResolvedModel {
+            provider,
+            base_url: None,
+            api_key_env: None,
+            model,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGISTRY_TOML: &str = r#"
+[models."deepseek-chat"]
+provider = "openai_compatible"
+base_url = "https://api.deepseek.com"
+api_key_env = "DEEPSEEK_API_KEY"
+context_size = 64000
+max_output = 8192
+pricing = { input = 0.27, output = 1.10 }
+capabilities = { vision = false, json_schema = true }
+
+[models."claude-3-opus"]
+provider = "anthropic"
+context_size = 200000
+max_output = 4096
+tokenizer = { tiktoken = "gpt-4o" }
+"#;
+
+    #[test]
+    fn test_registry_toml_and_json() {
+        let registry = ModelRegistry::from_toml(REGISTRY_TOML).unwrap();
+        let entry = registry.get("deepseek-chat").unwrap();
+        assert_eq!(entry.provider, ModelProvider::OpenAICompatible);
+        assert_eq!(entry.pricing.output, 1.10);
+        assert!(entry.capabilities.json_schema);
+        assert!(entry.capabilities.tools);
+        assert_eq!(entry.tokenizer, TokenizerChoice::default());
+
+        let json = serde_json::to_string(&registry).unwrap();
+        assert_eq!(ModelRegistry::from_json(&json).unwrap(), registry);
+    }
+
+    #[test]
+    fn test_registry_resolve() {
+        let registry = ModelRegistry::from_toml(REGISTRY_TOML).unwrap();
+
+        let resolved = registry.resolve("claude-3-opus-20240229").unwrap();
+        assert_eq!(resolved.provider, ModelProvider::Anthropic);
+        assert_eq!(resolved.model.model_base.inference_ctx_size, 4096);
+        assert_eq!(resolved.model.model_base.model_id, "claude-3-opus-20240229");
+
+        let resolved = registry.resolve("gpt-4o").unwrap();
+        assert_eq!(resolved.provider, ModelProvider::OpenAI);
+        assert_eq!(resolved.model.cost_per_m_in_tokens, 5.0);
+
+        assert!(registry.resolve("gpt-unknown").is_err());
+        assert!(registry.resolve("mistral-large").is_err());
+    }
+}
@@ -1 +1,801 @@
+//! A client of the X (Twitter) API v2: posting, replying, searching, mentions and user
+//! timelines, with media upload. The API URLs can be changed to test against a mock server.
+//! The media are only read from a configured directory, or downloaded, when allowed.
 
+use reqwest::header::{HeaderMap, AUTHORIZATION};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, Method, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+mod oauth;
+mod tools;
+
+pub use oauth::{OAuth1Credentials, TwitterAuth};
+pub use tools::*;
+
+pub const API_URL: &str = "https://api.x.com/2/";
+pub const UPLOAD_URL: &str = "https://upload.twitter.com/1.1/media/upload.json";
+/// The longest wait for a rate limit to reset before giving up.
+pub const DEFAULT_MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+/// The retries of a rate limited request before giving up.
+pub const MAX_RATE_LIMIT_RETRIES: usize = 3;
+/// The shortest wait before retrying a rate limited request, even when the limit has already
+/// reset.
+pub const MIN_RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(1);
+/// The maximum number of results of a page.
+pub const MAX_PAGE_SIZE: usize = 100;
+/// The maximum number of tweets read by a call, over all its pages.
+pub const MAX_RESULTS: usize = 500;
+/// The largest media uploaded, the limit of the images.
+pub const DEFAULT_MAX_MEDIA_SIZE: usize = 5 * 1024 * 1024;
+/// The fields of the tweets requested in addition to their id and text.
+const TWEET_FIELDS: &str = "created_at,author_id,conversation_id,in_reply_to_user_id";
+
+#[derive(Debug, thiserror::Error)]
+#[error("Twitter error")]
+pub enum TwitterError {
+    #[error("Missing credentials: {0}")]
+    MissingCredentials(String),
+    #[error("The API returned {status}: {message}")]
+    ApiError { status: u16, message: String },
+    #[error("Rate limited until {reset}")]
+    RateLimited { reset: u64 },
+    #[error("Invalid media: {0}")]
+    InvalidMedia(String),
+    #[error("Url error: {0}")]
+    UrlError(#[from] url::ParseError),
+    #[error("Request error: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// The rate limit of an endpoint, read from the `x-rate-limit-*` headers of its responses.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub limit: Option<u32>,
+    pub remaining: Option<u32>,
+    /// The Unix time when the limit resets.
+    pub reset: Option<u64>,
+}
+
+impl RateLimit {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: &str| headers.get(name)?.to_str().ok()?.parse().ok();
+        Self {
+            limit: header("x-rate-limit-limit").map(|v: u64| v as u32),
+            remaining: header("x-rate-limit-remaining").map(|v: u64| v as u32),
+            reset: header("x-rate-limit-reset"),
+        }
+    }
+
+    /// Whether no request is left before the reset.
+    #[inline]
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == Some(0)
+    }
+
+    /// The time until the limit resets.
+    pub fn wait(&self) -> Option<Duration> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        Some(Duration::from_secs(self.reset?.saturating_sub(now)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tweet {
+    pub id: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_reply_to_user_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub id: String,
+    pub name: String,
+    pub username: String,
+}
+
+/// Tweets and the token of the next page, if any.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TweetPage {
+    pub tweets: Vec<Tweet>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Data<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct Page {
+    #[serde(default)]
+    data: Vec<Tweet>,
+    #[serde(default)]
+    meta: PageMeta,
+}
+
+#[derive(Deserialize, Default)]
+struct PageMeta {
+    next_token: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TwitterClient {
+    auth: TwitterAuth,
+    client: Client,
+    api_url: Url,
+    upload_url: Url,
+    max_rate_limit_wait: Duration,
+    media_dir: Option<PathBuf>,
+    allow_media_urls: bool,
+    max_media_size: usize,
+}
+
+impl TwitterClient {
+    pub fn new(auth: TwitterAuth) -> Self {
+        Self {
+            auth,
+            client: Client::new(),
+            api_url: Url::parse(API_URL).expect("valid api url"),
+            upload_url: Url::parse(UPLOAD_URL).expect("valid upload url"),
+            max_rate_limit_wait: DEFAULT_MAX_RATE_LIMIT_WAIT,
+            media_dir: None,
+            allow_media_urls: false,
+            max_media_size: DEFAULT_MAX_MEDIA_SIZE,
+        }
+    }
+
+    /// A client authenticated with [`TwitterAuth::from_env`].
+    pub fn from_env() -> Result<Self, TwitterError> {
+        Ok(Self::new(TwitterAuth::from_env()?))
+    }
+
+    /// The base URL of the API v2, e.g. of a mock server.
+    pub fn api_url(mut self, url: impl AsRef<str>) -> Result<Self, TwitterError> {
+        let mut url = Url::parse(url.as_ref())?;
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        self.api_url = url;
+        Ok(self)
+    }
+
+    /// The URL of the media upload endpoint.
+    pub fn upload_url(mut self, url: impl AsRef<str>) -> Result<Self, TwitterError> {
+        self.upload_url = Url::parse(url.as_ref())?;
+        Ok(self)
+    }
+
+    /// The longest wait for a rate limit to reset. Beyond it, a rate limited request fails and
+    /// the pagination stops early.
+    pub fn max_rate_limit_wait(mut self, wait: Duration) -> Self {
+        self.max_rate_limit_wait = wait;
+        self
+    }
+
+    /// Allows uploading the media of a local directory, given by their paths relative to it.
+    /// No local file is read otherwise.
+    pub fn media_dir(mut self, dir: impl AsRef<Path>) -> Result<Self, TwitterError> {
+        self.media_dir = Some(std::fs::canonicalize(dir)?);
+        Ok(self)
+    }
+
+    /// Allows uploading the media downloaded from URLs, which are rejected by default.
+    pub fn allow_media_urls(mut self, allow: bool) -> Self {
+        self.allow_media_urls = allow;
+        self
+    }
+
+    /// The largest media uploaded, in bytes.
+    pub fn max_media_size(mut self, size: usize) -> Self {
+        self.max_media_size = size;
+        self
+    }
+
+    /// Posts a tweet, as a reply if `reply_to` is given, with the uploaded media.
+    pub async fn post_tweet(
+        &self,
+        text: &str,
+        reply_to: Option<&str>,
+        media_ids: &[String],
+    ) -> Result<Tweet, TwitterError> {
+        let mut body = json!({ "text": text });
+        if let Some(reply_to) = reply_to {
+            body["reply"] = json!({ "in_reply_to_tweet_id": reply_to });
+        }
+        if !media_ids.is_empty() {
+            body["media"] = json!({ "media_ids": media_ids });
+        }
+        let (tweet, _) = self
+            .send::<Data<Tweet>>(Method::POST, self.api_url.join("tweets")?, Some(body))
+            .await?;
+        Ok(tweet.data)
+    }
+
+    /// Searches the tweets of the last seven days.
+    pub async fn search_recent(
+        &self,
+        query: &str,
+        max_results: usize,
+        next_token: Option<String>,
+    ) -> Result<TweetPage, TwitterError> {
+        let mut url = self.api_url.join("tweets/search/recent")?;
+        url.query_pairs_mut().append_pair("query", query);
+        self.paginate(url, "next_token", 10, max_results, next_token)
+            .await
+    }
+
+    /// The tweets mentioning the user.
+    pub async fn mentions(
+        &self,
+        user_id: &str,
+        max_results: usize,
+        next_token: Option<String>,
+    ) -> Result<TweetPage, TwitterError> {
+        let url = self.endpoint(&["users", user_id, "mentions"])?;
+        self.paginate(url, "pagination_token", 5, max_results, next_token)
+            .await
+    }
+
+    /// The tweets of the user, most recent first.
+    pub async fn user_tweets(
+        &self,
+        user_id: &str,
+        max_results: usize,
+        next_token: Option<String>,
+    ) -> Result<TweetPage, TwitterError> {
+        let url = self.endpoint(&["users", user_id, "tweets"])?;
+        self.paginate(url, "pagination_token", 5, max_results, next_token)
+            .await
+    }
+
+    /// The authenticated user, which requires a user context.
+    pub async fn me(&self) -> Result<User, TwitterError> {
+        let (user, _) = self
+            .send::<Data<User>>(Method::GET, self.api_url.join("users/me")?, None)
+            .await?;
+        Ok(user.data)
+    }
+
+    pub async fn user_by_username(&self, username: &str) -> Result<User, TwitterError> {
+        let username = username.trim_start_matches('@');
+        let url = self.endpoint(&["users", "by", "username", username])?;
+        let (user, _) = self.send::<Data<User>>(Method::GET, url, None).await?;
+        Ok(user.data)
+    }
+
+    /// The user with the given username, or the authenticated user.
+    pub async fn user(&self, username: Option<&str>) -> Result<User, TwitterError> {
+        match username {
+            Some(username) => self.user_by_username(username).await,
+            None => self.me().await,
+        }
+    }
+
+    /// Uploads an image, returning the id to attach it to a tweet. The upload endpoint of the
+    /// API v1.1 requires OAuth 1.0a credentials.
+    pub async fn upload_media(&self, media: Vec<u8>) -> Result<String, TwitterError> {
+        #[derive(Deserialize)]
+        struct Uploaded {
+            media_id_string: String,
+        }
+
+        self.check_upload_auth()?;
+        if media.len() > self.max_media_size {
+            return Err(self.media_too_large());
+        }
+        let form = Form::new().part("media", Part::bytes(media).file_name("media"));
+        // The multipart body is not part of the OAuth 1.0a signature.
+        let response = self
+            .client
+            .post(self.upload_url.clone())
+            .header(
+                AUTHORIZATION,
+                self.auth.header("POST", &self.upload_url, &[]),
+            )
+            .multipart(form)
+            .send()
+            .await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(api_error(status, &text));
+        }
+        Ok(serde_json::from_str::<Uploaded>(&text)?.media_id_string)
+    }
+
+    /// Uploads the media read from the media directory or downloaded from URLs, when the
+    /// client allows them.
+    pub async fn upload_media_sources(
+        &self,
+        sources: &[String],
+    ) -> Result<Vec<String>, TwitterError> {
+        if !sources.is_empty() {
+            self.check_upload_auth()?;
+        }
+        let mut media_ids = Vec::with_capacity(sources.len());
+        for source in sources {
+            let media = if source.starts_with("http://") || source.starts_with("https://") {
+                self.download_media(source).await?
+            } else {
+                self.read_media(source).await?
+            };
+            media_ids.push(self.upload_media(media).await?);
+        }
+        Ok(media_ids)
+    }
+
+    fn check_upload_auth(&self) -> Result<(), TwitterError> {
+        match self.auth {
+            TwitterAuth::OAuth1(_) => Ok(()),
+            TwitterAuth::Bearer(_) => Err(TwitterError::MissingCredentials(
+                "uploading media requires OAuth 1.0a credentials".to_string(),
+            )),
+        }
+    }
+
+    fn media_too_large(&self) -> TwitterError {
+        TwitterError::InvalidMedia(format!(
+            "the media is larger than {} bytes",
+            self.max_media_size
+        ))
+    }
+
+    async fn download_media(&self, url: &str) -> Result<Vec<u8>, TwitterError> {
+        if !self.allow_media_urls {
+            return Err(TwitterError::InvalidMedia(format!(
+                "downloading the media of {url} is not allowed"
+            )));
+        }
+        let mut response = self.client.get(url).send().await?.error_for_status()?;
+        if response
+            .content_length()
+            .is_some_and(|len| len > self.max_media_size as u64)
+        {
+            return Err(self.media_too_large());
+        }
+        let mut media = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if media.len() + chunk.len() > self.max_media_size {
+                return Err(self.media_too_large());
+            }
+            media.extend_from_slice(&chunk);
+        }
+        Ok(media)
+    }
+
+    /// Reads a media of the media directory, the links being resolved first so they cannot
+    /// point out of it.
+    async fn read_media(&self, source: &str) -> Result<Vec<u8>, TwitterError> {
+        let Some(media_dir) = &self.media_dir else {
+            return Err(TwitterError::InvalidMedia(format!(
+                "reading the local media {source} is not allowed"
+            )));
+        };
+        let path = tokio::fs::canonicalize(media_dir.join(source)).await?;
+        if !path.starts_with(media_dir) {
+            return Err(TwitterError::InvalidMedia(format!(
+                "{source} is not in the media directory"
+            )));
+        }
+        if tokio::fs::metadata(&path).await?.len() > self.max_media_size as u64 {
+            return Err(self.media_too_large());
+        }
+        Ok(tokio::fs::read(path).await?)
+    }
+
+    /// The URL of an endpoint of the API v2, the segments being percent-encoded.
+    fn endpoint(&self, segments: &[&str]) -> Result<Url, TwitterError> {
+        let mut url = self.api_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    /// Reads the pages of tweets until `max_results`, at most [`MAX_RESULTS`], are read or
+    /// there is no next page. The tweets of the last page beyond `max_results` are dropped.
+    /// When the rate limit is exhausted for longer than the maximum wait, the tweets read so
+    /// far are returned with the token of the next page.
+    async fn paginate(
+        &self,
+        url: Url,
+        token_param: &str,
+        min_page_size: usize,
+        max_results: usize,
+        mut next_token: Option<String>,
+    ) -> Result<TweetPage, TwitterError> {
+        let max_results = max_results.min(MAX_RESULTS);
+        let mut tweets = Vec::new();
+        loop {
+            let page_size = max_results
+                .saturating_sub(tweets.len())
+                .clamp(min_page_size, MAX_PAGE_SIZE);
+            let mut page_url = url.clone();
+            page_url
+                .query_pairs_mut()
+                .append_pair("max_results", &page_size.to_string())
+                .append_pair("tweet.fields", TWEET_FIELDS);
+            if let Some(token) = &next_token {
+                page_url.query_pairs_mut().append_pair(token_param, token);
+            }
+            let (page, rate_limit) = self.send::<Page>(Method::GET, page_url, None).await?;
+            tweets.extend(page.data);
+            next_token = page.meta.next_token;
+            if tweets.len() >= max_results || next_token.is_none() {
+                break;
+            }
+            if rate_limit.is_exhausted()
+                && rate_limit
+                    .wait()
+                    .is_none_or(|wait| wait > self.max_rate_limit_wait)
+            {
+                break;
+            }
+        }
+        tweets.truncate(max_results);
+        Ok(TweetPage { tweets, next_token })
+    }
+
+    /// Sends a request, waiting for the rate limit to reset and retrying, up to
+    /// [`MAX_RATE_LIMIT_RETRIES`] times, if it is exceeded.
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: Url,
+        body: Option<Value>,
+    ) -> Result<(T, RateLimit), TwitterError> {
+        let mut retries = 0;
+        loop {
+            let mut request = self
+                .client
+                .request(method.clone(), url.clone())
+                .header(AUTHORIZATION, self.auth.header(method.as_str(), &url, &[]));
+            if let Some(body) = &body {
+                request = request.json(body);
+            }
+            let response = request.send().await?;
+            let rate_limit = RateLimit::from_headers(response.headers());
+            let status = response.status();
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                let wait = rate_limit
+                    .wait()
+                    .map(|wait| wait.max(MIN_RATE_LIMIT_BACKOFF));
+                match wait {
+                    Some(wait)
+                        if wait <= self.max_rate_limit_wait && retries < MAX_RATE_LIMIT_RETRIES =>
+                    {
+                        tracing::warn!("rate limited by the Twitter API, waiting {wait:?}");
+                        tokio::time::sleep(wait).await;
+                        retries += 1;
+                        continue;
+                    }
+                    _ => {
+                        return Err(TwitterError::RateLimited {
+                            reset: rate_limit.reset.unwrap_or_default(),
+                        })
+                    }
+                }
+            }
+            let text = response.text().await?;
+            if !status.is_success() {
+                return Err(api_error(status, &text));
+            }
+            return Ok((serde_json::from_str(&text)?, rate_limit));
+        }
+    }
+}
+
+/// The error of a response, from the `detail` or the first of the `errors` of the body.
+fn api_error(status: StatusCode, body: &str) -> TwitterError {
+    let value: Value = serde_json::from_str(body).unwrap_or_default();
+    let message = value["detail"]
+        .as_str()
+        .or_else(|| value["errors"][0]["message"].as_str())
+        .or_else(|| value["title"].as_str())
+        .unwrap_or(body)
+        .to_string();
+    TwitterError::ApiError {
+        status: status.as_u16(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alith_core::tool::StructureTool;
+    use mockito::{Matcher, Server};
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn client(server: &Server, auth: TwitterAuth) -> TwitterClient {
+        TwitterClient::new(auth)
+            .api_url(format!("{}/2/", server.url()))
+            .unwrap()
+            .upload_url(format!("{}/1.1/media/upload.json", server.url()))
+            .unwrap()
+    }
+
+    fn oauth1() -> TwitterAuth {
+        TwitterAuth::oauth1("key", "secret", "token", "token-secret")
+    }
+
+    #[tokio::test]
+    async fn test_search_pagination_and_rate_limit() {
+        let mut server = Server::new_async().await;
+        let first = server
+            .mock("GET", "/2/tweets/search/recent")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("query".to_string(), "alith".to_string()),
+                Matcher::UrlEncoded("max_results".to_string(), "30".to_string()),
+            ]))
+            .match_header("authorization", "Bearer token")
+            .with_body(r#"{"data":[{"id":"1","text":"a"}],"meta":{"next_token":"p2"}}"#)
+            .create_async()
+            .await;
+        let limited = server
+            .mock("GET", "/2/tweets/search/recent")
+            .match_query(Matcher::UrlEncoded(
+                "next_token".to_string(),
+                "p2".to_string(),
+            ))
+            .with_status(429)
+            .with_header("x-rate-limit-remaining", "0")
+            .with_header("x-rate-limit-reset", &now().to_string())
+            .with_body(r#"{"title":"Too Many Requests"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/2/tweets/search/recent")
+            .match_query(Matcher::UrlEncoded(
+                "next_token".to_string(),
+                "p2".to_string(),
+            ))
+            .with_header("x-rate-limit-remaining", "0")
+            .with_header("x-rate-limit-reset", "4102444800")
+            .with_body(r#"{"data":[{"id":"2","text":"b"}],"meta":{"next_token":"p3"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let client = client(&server, TwitterAuth::bearer("token"));
+
+        let page = client.search_recent("alith", 30, None).await.unwrap();
+        let ids: Vec<_> = page.tweets.iter().map(|tweet| tweet.id.as_str()).collect();
+        assert_eq!(ids, ["1", "2"]);
+        // The rate limit is exhausted for longer than the maximum wait.
+        assert_eq!(page.next_token.as_deref(), Some("p3"));
+        first.assert_async().await;
+        limited.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited() {
+        let mut server = Server::new_async().await;
+        let limited = server
+            .mock("GET", "/2/users/me")
+            .with_status(429)
+            .with_header("x-rate-limit-reset", "4102444800")
+            .expect(1)
+            .create_async()
+            .await;
+        let client = client(&server, TwitterAuth::bearer("token"));
+        let err = client.me().await.unwrap_err();
+        assert!(matches!(
+            err,
+            TwitterError::RateLimited { reset: 4102444800 }
+        ));
+        limited.assert_async().await;
+
+        // The retries stop even when the limit keeps resetting.
+        limited.remove_async().await;
+        let limited = server
+            .mock("GET", "/2/users/me")
+            .with_status(429)
+            .with_header("x-rate-limit-reset", &now().to_string())
+            .expect(MAX_RATE_LIMIT_RETRIES + 1)
+            .create_async()
+            .await;
+        let err = client.me().await.unwrap_err();
+        assert!(matches!(err, TwitterError::RateLimited { .. }));
+        limited.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_api_error() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/2/tweets")
+            .with_status(403)
+            .with_body(
+                r#"{"title":"Forbidden","detail":"You are not permitted to perform this action."}"#,
+            )
+            .create_async()
+            .await;
+        let client = client(&server, TwitterAuth::bearer("token"));
+        let err = client.post_tweet("gm", None, &[]).await.unwrap_err();
+        assert!(matches!(
+            err,
+            TwitterError::ApiError { status: 403, ref message } if message.starts_with("You are not permitted")
+        ));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_media_upload() {
+        let media_dir = std::env::temp_dir().join(format!("alith-twitter-{}", std::process::id()));
+        std::fs::create_dir_all(&media_dir).unwrap();
+        std::fs::write(media_dir.join("image.png"), b"image bytes").unwrap();
+        let mut server = Server::new_async().await;
+        let upload = server
+            .mock("POST", "/1.1/media/upload.json")
+            .match_header(
+                "authorization",
+                Matcher::Regex(
+                    r#"^OAuth oauth_consumer_key="key", .*oauth_signature=""#.to_string(),
+                ),
+            )
+            .match_body(Matcher::Regex("image bytes".to_string()))
+            .with_body(r#"{"media_id":42,"media_id_string":"42"}"#)
+            .create_async()
+            .await;
+        let tweet = server
+            .mock("POST", "/2/tweets")
+            .match_body(Matcher::PartialJson(
+                json!({ "text": "gm", "media": { "media_ids": ["42"] } }),
+            ))
+            .with_body(r#"{"data":{"id":"7","text":"gm"}}"#)
+            .create_async()
+            .await;
+
+        let client = client(&server, oauth1())
+            .media_dir(&media_dir)
+            .unwrap()
+            .max_media_size(16);
+        let posted = TwitterPostTool::new(client.clone())
+            .run_with_args(PostTweetInput {
+                text: "gm".to_string(),
+                media: vec!["image.png".to_string()],
+            })
+            .await
+            .unwrap();
+        assert_eq!(posted.id, "7");
+        upload.assert_async().await;
+        tweet.assert_async().await;
+
+        let upload = |client: TwitterClient, source: &str| {
+            let source = source.to_string();
+            async move { client.upload_media_sources(&[source]).await.unwrap_err() }
+        };
+        let outside = media_dir.join("../outside.png");
+        std::fs::write(&outside, b"secret").unwrap();
+        assert!(matches!(
+            upload(client.clone(), "../outside.png").await,
+            TwitterError::InvalidMedia(_)
+        ));
+        assert!(matches!(
+            upload(client.clone(), outside.to_str().unwrap()).await,
+            TwitterError::InvalidMedia(_)
+        ));
+        assert!(matches!(
+            upload(client.clone(), &format!("{}/image.png", server.url())).await,
+            TwitterError::InvalidMedia(_)
+        ));
+        assert!(matches!(
+            upload(client.clone().max_media_size(4), "image.png").await,
+            TwitterError::InvalidMedia(_)
+        ));
+        assert!(matches!(
+            upload(self::client(&server, oauth1()), "image.png").await,
+            TwitterError::InvalidMedia(_)
+        ));
+        assert!(matches!(
+            upload(
+                self::client(&server, TwitterAuth::bearer("token")),
+                "image.png"
+            )
+            .await,
+            TwitterError::MissingCredentials(_)
+        ));
+        std::fs::remove_file(outside).unwrap();
+        std::fs::remove_dir_all(media_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mentions_tool_oauth1() {
+        let mut server = Server::new_async().await;
+        let signed = Matcher::Regex(
+            r#"^OAuth oauth_consumer_key="key", oauth_nonce="\w+", oauth_signature=".+", oauth_signature_method="HMAC-SHA1", oauth_timestamp="\d+", oauth_token="token", oauth_version="1.0"$"#
+                .to_string(),
+        );
+        let me = server
+            .mock("GET", "/2/users/me")
+            .match_header("authorization", signed.clone())
+            .with_body(r#"{"data":{"id":"123","name":"Alith","username":"alith"}}"#)
+            .create_async()
+            .await;
+        let mentions = server
+            .mock("GET", "/2/users/123/mentions")
+            .match_query(Matcher::UrlEncoded(
+                "max_results".to_string(),
+                "5".to_string(),
+            ))
+            .match_header("authorization", signed)
+            .with_body(r#"{"data":[{"id":"1","text":"@alith gm","author_id":"9"}],"meta":{}}"#)
+            .create_async()
+            .await;
+
+        let page = TwitterMentionsTool::new(client(&server, oauth1()))
+            .run_with_args(UserTweetsInput {
+                username: None,
+                max_results: 5,
+                next_token: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.tweets.len(), 1);
+        assert_eq!(page.tweets[0].author_id.as_deref(), Some("9"));
+        assert_eq!(page.next_token, None);
+        me.assert_async().await;
+        mentions.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_timeline_tool() {
+        let mut server = Server::new_async().await;
+        let user = server
+            .mock("GET", "/2/users/by/username/alice%2F..")
+            .with_body(r#"{"data":{"id":"123","name":"Alice","username":"alice"}}"#)
+            .create_async()
+            .await;
+        let tweets = server
+            .mock("GET", "/2/users/123/tweets")
+            .match_query(Matcher::UrlEncoded(
+                "max_results".to_string(),
+                "5".to_string(),
+            ))
+            .with_body(
+                r#"{"data":[{"id":"3","text":"c"},{"id":"2","text":"b"},{"id":"1","text":"a"}],"meta":{"next_token":"p2"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let page = TwitterTimelineTool::new(client(&server, TwitterAuth::bearer("token")))
+            .run_with_args(UserTweetsInput {
+                username: Some("@alice/..".to_string()),
+                max_results: 2,
+                next_token: None,
+            })
+            .await
+            .unwrap();
+        let ids: Vec<_> = page.tweets.iter().map(|tweet| tweet.id.as_str()).collect();
+        assert_eq!(ids, ["3", "2"]);
+        assert_eq!(page.next_token.as_deref(), Some("p2"));
+        user.assert_async().await;
+        tweets.assert_async().await;
+    }
+}
@@ -0,0 +1,200 @@
+//! The authentication of the requests: an OAuth 2.0 bearer token, or OAuth 1.0a user context
+//! with HMAC-SHA1 signed requests.
+
+use super::TwitterError;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rand::{distr::Alphanumeric, Rng};
+use sha1::Sha1;
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// The characters percent-encoded by OAuth 1.0a: all but the unreserved ones.
+const OAUTH_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(Debug, Clone)]
+pub enum TwitterAuth {
+    /// An OAuth 2.0 bearer token. App-only tokens can only read, the tokens of a user can
+    /// also post.
+    Bearer(String),
+    /// OAuth 1.0a user context.
+    OAuth1(OAuth1Credentials),
+}
+
+#[derive(Debug, Clone)]
+pub struct OAuth1Credentials {
+    pub consumer_key: String,
+    pub consumer_secret: String,
+    pub access_token: String,
+    pub access_token_secret: String,
+}
+
+impl TwitterAuth {
+    #[inline]
+    pub fn bearer(token: impl ToString) -> Self {
+        Self::Bearer(token.to_string())
+    }
+
+    pub fn oauth1(
+        consumer_key: impl ToString,
+        consumer_secret: impl ToString,
+        access_token: impl ToString,
+        access_token_secret: impl ToString,
+    ) -> Self {
+        Self::OAuth1(OAuth1Credentials {
+            consumer_key: consumer_key.to_string(),
+            consumer_secret: consumer_secret.to_string(),
+            access_token: access_token.to_string(),
+            access_token_secret: access_token_secret.to_string(),
+        })
+    }
+
+    /// Reads the OAuth 1.0a credentials from `TWITTER_API_KEY`, `TWITTER_API_SECRET`,
+    /// `TWITTER_ACCESS_TOKEN` and `TWITTER_ACCESS_TOKEN_SECRET`, or else the bearer token from
+    /// `TWITTER_BEARER_TOKEN`.
+    pub fn from_env() -> Result<Self, TwitterError> {
+        let var = |name: &str| std::env::var(name).ok();
+        if let (Some(key), Some(secret), Some(token), Some(token_secret)) = (
+            var("TWITTER_API_KEY"),
+            var("TWITTER_API_SECRET"),
+            var("TWITTER_ACCESS_TOKEN"),
+            var("TWITTER_ACCESS_TOKEN_SECRET"),
+        ) {
+            return Ok(Self::oauth1(key, secret, token, token_secret));
+        }
+        var("TWITTER_BEARER_TOKEN")
+            .map(Self::Bearer)
+            .ok_or_else(|| {
+                TwitterError::MissingCredentials(
+                    "set TWITTER_BEARER_TOKEN or the OAuth 1.0a TWITTER_* variables".to_string(),
+                )
+            })
+    }
+
+    /// The `Authorization` header of a request. The form parameters of the body, if any, are
+    /// part of the OAuth 1.0a signature.
+    pub fn header(&self, method: &str, url: &Url, form: &[(&str, &str)]) -> String {
+        match self {
+            Self::Bearer(token) => format!("Bearer {token}"),
+            Self::OAuth1(credentials) => {
+                let nonce: String = rand::rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(32)
+                    .map(char::from)
+                    .collect();
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+                credentials.header(method, url, form, &nonce, timestamp)
+            }
+        }
+    }
+}
+
+impl OAuth1Credentials {
+    /// The `Authorization` header of a request signed with the given nonce and timestamp.
+    pub fn header(
+        &self,
+        method: &str,
+        url: &Url,
+        form: &[(&str, &str)],
+        nonce: &str,
+        timestamp: u64,
+    ) -> String {
+        let timestamp = timestamp.to_string();
+        let mut oauth_params = vec![
+            ("oauth_consumer_key", self.consumer_key.as_str()),
+            ("oauth_nonce", nonce),
+            ("oauth_signature_method", "HMAC-SHA1"),
+            ("oauth_timestamp", timestamp.as_str()),
+            ("oauth_token", self.access_token.as_str()),
+            ("oauth_version", "1.0"),
+        ];
+
+        let mut params: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| (encode(&k), encode(&v)))
+            .chain(
+                oauth_params
+                    .iter()
+                    .chain(form)
+                    .map(|(k, v)| (encode(k), encode(v))),
+            )
+            .collect();
+        params.sort();
+        let params = params
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let mut base_url = url.clone();
+        base_url.set_query(None);
+        base_url.set_fragment(None);
+        let base = format!(
+            "{}&{}&{}",
+            method.to_uppercase(),
+            encode(base_url.as_str()),
+            encode(&params)
+        );
+        let key = format!(
+            "{}&{}",
+            encode(&self.consumer_secret),
+            encode(&self.access_token_secret)
+        );
+        let mut mac =
+            Hmac::<Sha1>::new_from_slice(key.as_bytes()).expect("HMAC takes keys of any size");
+        mac.update(base.as_bytes());
+        let signature = STANDARD.encode(mac.finalize().into_bytes());
+
+        oauth_params.push(("oauth_signature", &signature));
+        oauth_params.sort();
+        let header = oauth_params
+            .iter()
+            .map(|(k, v)| format!("{k}=\"{}\"", encode(v)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("OAuth {header}")
+    }
+}
+
+fn encode(s: &str) -> String {
+    utf8_percent_encode(s, OAUTH_ENCODE_SET).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example of the Twitter documentation on creating a signature.
+    #[test]
+    fn test_oauth1_signature() {
+        let credentials = OAuth1Credentials {
+            consumer_key: "xvz1evFS4wEEPTGEFPHBog".to_string(),
+            consumer_secret: "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw".to_string(),
+            access_token: "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb".to_string(),
+            access_token_secret: "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE".to_string(),
+        };
+        let url =
+            Url::parse("https://api.twitter.com/1.1/statuses/update.json?include_entities=true")
+                .unwrap();
+        let header = credentials.header(
+            "post",
+            &url,
+            &[(
+                "status",
+                "Hello Ladies + Gentlemen, a signed OAuth request!",
+            )],
+            "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg",
+            1318622958,
+        );
+        assert!(header.starts_with("OAuth oauth_consumer_key=\"xvz1evFS4wEEPTGEFPHBog\", "));
+        assert!(header.contains("oauth_signature=\"hCtSmYh%2BiHYCEqBWrE7C7hYmtUk%3D\""));
+    }
+}
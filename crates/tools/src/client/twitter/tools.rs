@@ -0,0 +1,242 @@
+use super::{Tweet, TweetPage, TwitterClient, TwitterError};
+use alith_core::tool::{StructureTool, Tool, ToolError};
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The number of tweets read when not given.
+pub const DEFAULT_MAX_RESULTS: usize = 10;
+
+impl From<TwitterError> for ToolError {
+    fn from(err: TwitterError) -> Self {
+        ToolError::NormalError(Box::new(err))
+    }
+}
+
+impl TwitterClient {
+    /// All the Twitter tools sharing this client.
+    pub fn tools(&self) -> Vec<Box<dyn Tool>> {
+        vec![
+            Box::new(TwitterPostTool::new(self.clone())),
+            Box::new(TwitterReplyTool::new(self.clone())),
+            Box::new(TwitterSearchTool::new(self.clone())),
+            Box::new(TwitterMentionsTool::new(self.clone())),
+            Box::new(TwitterTimelineTool::new(self.clone())),
+        ]
+    }
+}
+
+fn default_max_results() -> usize {
+    DEFAULT_MAX_RESULTS
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PostTweetInput {
+    /// The text of the tweet.
+    pub text: String,
+    /// The images attached to the tweet, as paths in the media directory or URLs, when the
+    /// client allows them.
+    #[serde(default)]
+    pub media: Vec<String>,
+}
+
+/// Posts a tweet.
+pub struct TwitterPostTool {
+    client: TwitterClient,
+}
+
+impl TwitterPostTool {
+    pub fn new(client: TwitterClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl StructureTool for TwitterPostTool {
+    type Input = PostTweetInput;
+    type Output = Tweet;
+
+    fn name(&self) -> &str {
+        "twitter_post_tweet"
+    }
+
+    fn description(&self) -> &str {
+        "Posts a tweet, with images given as paths in the media directory or URLs, and returns it."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let media_ids = self.client.upload_media_sources(&input.media).await?;
+        Ok(self
+            .client
+            .post_tweet(&input.text, None, &media_ids)
+            .await?)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReplyInput {
+    /// The id of the tweet replied to.
+    pub tweet_id: String,
+    /// The text of the reply.
+    pub text: String,
+    /// The images attached to the reply, as paths in the media directory or URLs, when the
+    /// client allows them.
+    #[serde(default)]
+    pub media: Vec<String>,
+}
+
+/// Replies to a tweet.
+pub struct TwitterReplyTool {
+    client: TwitterClient,
+}
+
+impl TwitterReplyTool {
+    pub fn new(client: TwitterClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl StructureTool for TwitterReplyTool {
+    type Input = ReplyInput;
+    type Output = Tweet;
+
+    fn name(&self) -> &str {
+        "twitter_reply"
+    }
+
+    fn description(&self) -> &str {
+        "Replies to a tweet by its id and returns the reply."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let media_ids = self.client.upload_media_sources(&input.media).await?;
+        Ok(self
+            .client
+            .post_tweet(&input.text, Some(&input.tweet_id), &media_ids)
+            .await?)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SearchTweetsInput {
+    /// The search query, e.g. `alith -is:retweet lang:en`.
+    pub query: String,
+    /// The maximum number of tweets returned.
+    #[serde(default = "default_max_results")]
+    pub max_results: usize,
+    /// The token of the next page returned by a previous search.
+    #[serde(default)]
+    pub next_token: Option<String>,
+}
+
+/// Searches the recent tweets.
+pub struct TwitterSearchTool {
+    client: TwitterClient,
+}
+
+impl TwitterSearchTool {
+    pub fn new(client: TwitterClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl StructureTool for TwitterSearchTool {
+    type Input = SearchTweetsInput;
+    type Output = TweetPage;
+
+    fn name(&self) -> &str {
+        "twitter_search_recent"
+    }
+
+    fn description(&self) -> &str {
+        "Searches the tweets of the last seven days. Returns a next_token when more tweets can be read."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        Ok(self
+            .client
+            .search_recent(&input.query, input.max_results, input.next_token)
+            .await?)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UserTweetsInput {
+    /// The username of the user, the authenticated user when omitted.
+    #[serde(default)]
+    pub username: Option<String>,
+    /// The maximum number of tweets returned.
+    #[serde(default = "default_max_results")]
+    pub max_results: usize,
+    /// The token of the next page returned by a previous call.
+    #[serde(default)]
+    pub next_token: Option<String>,
+}
+
+/// Fetches the tweets mentioning a user.
+pub struct TwitterMentionsTool {
+    client: TwitterClient,
+}
+
+impl TwitterMentionsTool {
+    pub fn new(client: TwitterClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl StructureTool for TwitterMentionsTool {
+    type Input = UserTweetsInput;
+    type Output = TweetPage;
+
+    fn name(&self) -> &str {
+        "twitter_get_mentions"
+    }
+
+    fn description(&self) -> &str {
+        "Gets the recent tweets mentioning a user, the authenticated user by default."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let user = self.client.user(input.username.as_deref()).await?;
+        Ok(self
+            .client
+            .mentions(&user.id, input.max_results, input.next_token)
+            .await?)
+    }
+}
+
+/// Fetches the timeline of a user.
+pub struct TwitterTimelineTool {
+    client: TwitterClient,
+}
+
+impl TwitterTimelineTool {
+    pub fn new(client: TwitterClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl StructureTool for TwitterTimelineTool {
+    type Input = UserTweetsInput;
+    type Output = TweetPage;
+
+    fn name(&self) -> &str {
+        "twitter_get_user_timeline"
+    }
+
+    fn description(&self) -> &str {
+        "Gets the recent tweets posted by a user, the authenticated user by default."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let user = self.client.user(input.username.as_deref()).await?;
+        Ok(self
+            .client
+            .user_tweets(&user.id, input.max_results, input.next_token)
+            .await?)
+    }
+}
@@ -0,0 +1,185 @@
+use super::{host, send_request, Search, SearchError, SearchResult, SearchResults};
+use alith_core::tool::{StructureTool, ToolError};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+
+pub const DEFAULT_URL: &str = "https://api.bing.microsoft.com/v7.0/search";
+pub const DEFAULT_MAX_COUNT: usize = 10;
+/// The maximum number of results of a request.
+const MAX_COUNT: usize = 50;
+
+/// Searches with the Bing Web Search API.
+///
+/// Microsoft retired the Bing Search APIs on August 11, 2025, so [`DEFAULT_URL`] no longer
+/// answers: the searcher only works with an endpoint compatible with the v7 API, given with
+/// [`Searcher::url`].
+pub struct Searcher {
+    url: String,
+    api_key: String,
+    client: Client,
+    max_count: usize,
+}
+
+impl Searcher {
+    pub fn new(api_key: impl ToString) -> Self {
+        Self {
+            url: DEFAULT_URL.to_string(),
+            api_key: api_key.to_string(),
+            client: Client::new(),
+            max_count: DEFAULT_MAX_COUNT,
+        }
+    }
+
+    pub fn url(mut self, url: impl ToString) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    pub fn max_count(mut self, max_count: usize) -> Self {
+        self.max_count = max_count;
+        self
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    web_pages: Option<WebPages>,
+    #[serde(default)]
+    errors: Vec<ErrorDetail>,
+    /// The error of the Azure gateway, e.g. for an invalid key.
+    error: Option<ErrorDetail>,
+}
+
+#[derive(Deserialize)]
+struct ErrorDetail {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct WebPages {
+    #[serde(default)]
+    value: Vec<Item>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+    #[serde(default)]
+    name: String,
+    url: String,
+    #[serde(default)]
+    snippet: String,
+    date_published: Option<String>,
+    site_name: Option<String>,
+}
+
+/// Parses the web pages of the JSON response, none when it has no web pages section.
+pub fn parse_results(body: &str, max_count: usize) -> Result<SearchResults, SearchError> {
+    let response: Response = serde_json::from_str(body)?;
+    if let Some(error) = response.error.or(response.errors.into_iter().next()) {
+        return Err(SearchError::SearchError(error.message));
+    }
+    Ok(response
+        .web_pages
+        .map(|pages| pages.value)
+        .unwrap_or_default()
+        .into_iter()
+        .take(max_count)
+        .map(|item| SearchResult {
+            source: item.site_name.or_else(|| host(&item.url)),
+            title: item.name,
+            link: item.url,
+            snippet: item.snippet,
+            published: item.date_published,
+            score: None,
+        })
+        .collect())
+}
+
+#[async_trait]
+impl Search for Searcher {
+    async fn search(&self, query: &str) -> Result<SearchResults, SearchError> {
+        let mut url = Url::parse(&self.url)?;
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("count", &self.max_count.min(MAX_COUNT).to_string());
+
+        let request = self
+            .client
+            .get(url)
+            .header("Ocp-Apim-Subscription-Key", &self.api_key);
+        let body = send_request(request).await?;
+        parse_results(&body, self.max_count)
+    }
+}
+
+#[async_trait]
+impl StructureTool for Searcher {
+    type Input = String;
+    type Output = SearchResults;
+
+    fn name(&self) -> &str {
+        "BingSearch"
+    }
+
+    fn description(&self) -> &str {
+        r#"BingSearch is a tool designed to perform search queries on the Bing search engine.
+It takes a search query string as input and returns relevant search results with their source.
+This tool is ideal for scenarios where real-time information from the internet is required.
+"#
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        self.search(&input)
+            .await
+            .map_err(|err| ToolError::NormalError(Box::new(err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_results() {
+        let body = r#"{
+            "_type": "SearchResponse",
+            "webPages": {
+                "totalEstimatedMatches": 2,
+                "value": [
+                    {"name": "A", "url": "https://www.example.com/a", "snippet": "First", "datePublished": "2025-03-01T00:00:00.0000000", "siteName": "Example"},
+                    {"name": "B", "url": "https://example.org/b", "snippet": "Second"}
+                ]
+            }
+        }"#;
+        let results = parse_results(body, 10).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "A");
+        assert_eq!(results[0].source.as_deref(), Some("Example"));
+        assert_eq!(
+            results[0].published.as_deref(),
+            Some("2025-03-01T00:00:00.0000000")
+        );
+        assert_eq!(results[1].source.as_deref(), Some("example.org"));
+        assert_eq!(parse_results(body, 1).unwrap().len(), 1);
+
+        assert!(
+            parse_results(r#"{"_type": "SearchResponse", "queryContext": {}}"#, 10)
+                .unwrap()
+                .is_empty()
+        );
+        let error = r#"{"_type": "ErrorResponse", "errors": [{"code": "InvalidRequest", "message": "Parameter has invalid value.", "parameter": "count"}]}"#;
+        assert!(matches!(
+            parse_results(error, 10),
+            Err(SearchError::SearchError(message)) if message == "Parameter has invalid value."
+        ));
+        let error = r#"{"error": {"code": "401", "message": "Access denied due to invalid subscription key."}}"#;
+        assert!(matches!(
+            parse_results(error, 10),
+            Err(SearchError::SearchError(message)) if message.starts_with("Access denied")
+        ));
+    }
+}
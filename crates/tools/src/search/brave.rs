@@ -0,0 +1,185 @@
+use super::{host, send_request, Search, SearchError, SearchResult, SearchResults};
+use alith_core::tool::{StructureTool, ToolError};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+
+pub const DEFAULT_URL: &str = "https://api.search.brave.com/res/v1/web/search";
+pub const DEFAULT_MAX_COUNT: usize = 10;
+/// The maximum number of results of a request.
+const MAX_COUNT: usize = 20;
+
+/// Searches with the Brave Search API.
+pub struct Searcher {
+    url: String,
+    api_key: String,
+    client: Client,
+    max_count: usize,
+}
+
+impl Searcher {
+    pub fn new(api_key: impl ToString) -> Self {
+        Self {
+            url: DEFAULT_URL.to_string(),
+            api_key: api_key.to_string(),
+            client: Client::new(),
+            max_count: DEFAULT_MAX_COUNT,
+        }
+    }
+
+    pub fn url(mut self, url: impl ToString) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    pub fn max_count(mut self, max_count: usize) -> Self {
+        self.max_count = max_count;
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    web: Option<WebResults>,
+    error: Option<ErrorDetail>,
+}
+
+#[derive(Deserialize)]
+struct ErrorDetail {
+    code: Option<String>,
+    detail: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WebResults {
+    #[serde(default)]
+    results: Vec<Item>,
+}
+
+#[derive(Deserialize)]
+struct Item {
+    #[serde(default)]
+    title: String,
+    url: String,
+    #[serde(default)]
+    description: String,
+    page_age: Option<String>,
+    age: Option<String>,
+    profile: Option<Profile>,
+}
+
+#[derive(Deserialize)]
+struct Profile {
+    name: Option<String>,
+}
+
+/// Parses the web results of the JSON response, none when it has no web section.
+pub fn parse_results(body: &str, max_count: usize) -> Result<SearchResults, SearchError> {
+    let response: Response = serde_json::from_str(body)?;
+    if let Some(error) = response.error {
+        return Err(SearchError::SearchError(
+            error
+                .detail
+                .or(error.code)
+                .unwrap_or_else(|| body.to_string()),
+        ));
+    }
+    Ok(response
+        .web
+        .map(|web| web.results)
+        .unwrap_or_default()
+        .into_iter()
+        .take(max_count)
+        .map(|item| SearchResult {
+            source: item
+                .profile
+                .and_then(|profile| profile.name)
+                .or_else(|| host(&item.url)),
+            title: item.title,
+            link: item.url,
+            snippet: item.description,
+            published: item.page_age.or(item.age),
+            score: None,
+        })
+        .collect())
+}
+
+#[async_trait]
+impl Search for Searcher {
+    async fn search(&self, query: &str) -> Result<SearchResults, SearchError> {
+        let mut url = Url::parse(&self.url)?;
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("count", &self.max_count.min(MAX_COUNT).to_string());
+
+        let request = self
+            .client
+            .get(url)
+            .header("Accept", "application/json")
+            .header("X-Subscription-Token", &self.api_key);
+        let body = send_request(request).await?;
+        parse_results(&body, self.max_count)
+    }
+}
+
+#[async_trait]
+impl StructureTool for Searcher {
+    type Input = String;
+    type Output = SearchResults;
+
+    fn name(&self) -> &str {
+        "BraveSearch"
+    }
+
+    fn description(&self) -> &str {
+        r#"BraveSearch is a tool designed to perform search queries on the Brave search engine.
+It takes a search query string as input and returns relevant search results with their source and age.
+This tool is ideal for scenarios where real-time information from the internet is required.
+"#
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        self.search(&input)
+            .await
+            .map_err(|err| ToolError::NormalError(Box::new(err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_results() {
+        let body = r#"{
+            "type": "search",
+            "web": {
+                "type": "search",
+                "results": [
+                    {"title": "A", "url": "https://www.example.com/a", "description": "First", "page_age": "2025-03-01T00:00:00", "profile": {"name": "Example"}},
+                    {"title": "B", "url": "https://example.org/b", "description": "Second", "age": "2 days ago"}
+                ]
+            }
+        }"#;
+        let results = parse_results(body, 10).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].source.as_deref(), Some("Example"));
+        assert_eq!(results[0].published.as_deref(), Some("2025-03-01T00:00:00"));
+        assert_eq!(results[1].source.as_deref(), Some("example.org"));
+        assert_eq!(results[1].published.as_deref(), Some("2 days ago"));
+        assert_eq!(parse_results(body, 1).unwrap().len(), 1);
+
+        // The news or videos of a query without web results.
+        assert!(
+            parse_results(r#"{"type": "search", "news": {"results": []}}"#, 10)
+                .unwrap()
+                .is_empty()
+        );
+        let error = r#"{"type": "ErrorResponse", "error": {"id": "1", "status": 422, "code": "VALIDATION", "detail": "Unable to validate request parameter(s)."}}"#;
+        assert!(matches!(
+            parse_results(error, 10),
+            Err(SearchError::SearchError(message)) if message == "Unable to validate request parameter(s)."
+        ));
+    }
+}
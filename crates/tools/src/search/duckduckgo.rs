@@ -1,8 +1,8 @@
-use super::{Search, SearchError, SearchResult, SearchResults};
+use super::{host, Search, SearchError, SearchResult, SearchResults};
 use alith_core::tool::{StructureTool, ToolError};
 use async_trait::async_trait;
 use reqwest::Client;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 use std::collections::HashMap;
 use url::Url;
 
@@ -35,53 +35,56 @@ impl Search for Searcher {
 
         url.query_pairs_mut().extend_pairs(query_params.iter());
 
-        let response = self.client.get(url).send().await?;
+        let response = self.client.get(url).send().await?.error_for_status()?;
         let body = response.text().await?;
-        let document = Html::parse_document(&body);
-
-        let result_selector = Selector::parse(".web-result").unwrap();
-        let result_title_selector = Selector::parse(".result__a").unwrap();
-        let result_url_selector = Selector::parse(".result__url").unwrap();
-        let result_snippet_selector = Selector::parse(".result__snippet").unwrap();
-
-        let results = document
-            .select(&result_selector)
-            .map(|result| {
-                let title = result
-                    .select(&result_title_selector)
-                    .next()
-                    .unwrap()
-                    .text()
-                    .collect::<Vec<_>>()
-                    .join("");
-                let link = result
-                    .select(&result_url_selector)
-                    .next()
-                    .unwrap()
-                    .text()
-                    .collect::<Vec<_>>()
-                    .join("")
-                    .trim()
-                    .to_string();
-                let snippet = result
-                    .select(&result_snippet_selector)
-                    .next()
-                    .unwrap()
-                    .text()
-                    .collect::<Vec<_>>()
-                    .join("");
-
-                SearchResult {
-                    title,
-                    link,
-                    snippet,
-                }
+        parse_results(&body, self.max_count)
+    }
+}
+
+/// Parses the results of the HTML page. The results missing a title or a link are skipped,
+/// so a change of the markup yields fewer results rather than a failure.
+pub fn parse_results(body: &str, max_count: usize) -> Result<SearchResults, SearchError> {
+    let document = Html::parse_document(body);
+
+    let result_selector = selector(".web-result")?;
+    let result_title_selector = selector(".result__a")?;
+    let result_url_selector = selector(".result__url")?;
+    let result_snippet_selector = selector(".result__snippet")?;
+
+    let results = document
+        .select(&result_selector)
+        .filter_map(|result| {
+            let title = text(result, &result_title_selector)?;
+            let link = text(result, &result_url_selector)?;
+            let snippet = text(result, &result_snippet_selector).unwrap_or_default();
+            Some(SearchResult {
+                source: host(&link),
+                title,
+                link,
+                snippet,
+                ..Default::default()
             })
-            .take(self.max_count)
-            .collect::<Vec<_>>();
+        })
+        .take(max_count)
+        .collect::<Vec<_>>();
 
-        Ok(results)
-    }
+    Ok(results)
+}
+
+fn selector(selector: &str) -> Result<Selector, SearchError> {
+    Selector::parse(selector).map_err(|err| SearchError::ParseError(err.to_string()))
+}
+
+/// The trimmed text of the first element matching the selector, if not empty.
+fn text(element: ElementRef, selector: &Selector) -> Option<String> {
+    let text = element
+        .select(selector)
+        .next()?
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string();
+    (!text.is_empty()).then_some(text)
 }
 
 #[async_trait]
@@ -107,3 +110,31 @@ such as finding the latest news, retrieving detailed information on a specific t
             .map_err(|err| ToolError::NormalError(Box::new(err)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_results() {
+        let body = r#"
+            <div class="result web-result">
+                <a class="result__a" href="https://example.com/a">Example A</a>
+                <a class="result__url">www.example.com/a</a>
+                <a class="result__snippet">The first result</a>
+            </div>
+            <div class="result web-result">
+                <a class="result__a">Without a link</a>
+            </div>
+        "#;
+        let results = parse_results(body, 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Example A");
+        assert_eq!(results[0].link, "www.example.com/a");
+        assert_eq!(results[0].source.as_deref(), Some("example.com"));
+
+        assert!(parse_results("<html>changed markup</html>", 10)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -2,14 +2,35 @@ use std::sync::Arc;
 
 use alith_core::tool::{StructureTool, Tool, ToolError};
 use async_trait::async_trait;
+use reqwest::RequestBuilder;
 use serde::{Deserialize, Serialize};
+use url::Url;
 
+pub mod bing;
+pub mod brave;
 pub mod duckduckgo;
+pub mod searxng;
+pub mod tavily;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub enum SearchProvider {
     #[default]
     DuckDuckGo,
+    /// A SearxNG instance, e.g. `http://localhost:8080`, with the JSON format enabled.
+    SearxNG {
+        url: String,
+    },
+    Brave {
+        api_key: String,
+    },
+    Tavily {
+        api_key: String,
+    },
+    /// The Bing Web Search API, retired by Microsoft on August 11, 2025. See
+    /// [`bing::Searcher`] to use a compatible endpoint.
+    Bing {
+        api_key: String,
+    },
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -17,6 +38,8 @@ pub enum SearchProvider {
 pub enum SearchError {
     #[error("Failed to search: {0}")]
     SearchError(String),
+    #[error("Failed to parse the results: {0}")]
+    ParseError(String),
     #[error("An unknown error occurred: {0}")]
     Unknown(String),
     #[error("IO error: {0}")]
@@ -30,11 +53,20 @@ pub enum SearchError {
     JsonError(#[from] serde_json::Error),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchResult {
-    title: String,
-    link: String,
-    snippet: String,
+    pub title: String,
+    pub link: String,
+    pub snippet: String,
+    /// When the page was published, as given by the provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+    /// The site publishing the page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The relevance of the result, if the provider scores it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
 }
 
 pub type SearchResults = Vec<SearchResult>;
@@ -60,16 +92,26 @@ impl Default for SearchTool {
 }
 
 impl SearchTool {
+    pub fn new(provider: SearchProvider) -> Self {
+        Self {
+            searcher: Self::searcher(&provider),
+            provider,
+        }
+    }
+
     #[inline]
     pub fn provider(&self) -> &SearchProvider {
         &self.provider
     }
 
-    #[inline]
     pub fn searcher(provider: &SearchProvider) -> Arc<dyn Search> {
-        Arc::new(match provider {
-            SearchProvider::DuckDuckGo => duckduckgo::Searcher::default(),
-        })
+        match provider {
+            SearchProvider::DuckDuckGo => Arc::new(duckduckgo::Searcher::default()),
+            SearchProvider::SearxNG { url } => Arc::new(searxng::Searcher::new(url)),
+            SearchProvider::Brave { api_key } => Arc::new(brave::Searcher::new(api_key)),
+            SearchProvider::Tavily { api_key } => Arc::new(tavily::Searcher::new(api_key)),
+            SearchProvider::Bing { api_key } => Arc::new(bing::Searcher::new(api_key)),
+        }
     }
 }
 
@@ -106,3 +148,27 @@ impl StructureTool for SearchTool {
             .map_err(|err| ToolError::NormalError(Box::new(err)))
     }
 }
+
+/// Sends the request and returns the body of the response, an error status becoming an
+/// error with the body as message.
+pub(crate) async fn send_request(request: RequestBuilder) -> Result<String, SearchError> {
+    let response = request.send().await?;
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(SearchError::SearchError(format!("{status}: {body}")));
+    }
+    Ok(body)
+}
+
+/// The host of a link, without `www.`, the scheme being optional.
+pub(crate) fn host(link: &str) -> Option<String> {
+    let url = if link.contains("://") {
+        Url::parse(link)
+    } else {
+        Url::parse(&format!("https://{link}"))
+    };
+    let url = url.ok()?;
+    let host = url.host_str()?;
+    Some(host.strip_prefix("www.").unwrap_or(host).to_string())
+}
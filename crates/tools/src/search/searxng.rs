@@ -0,0 +1,140 @@
+use super::{host, send_request, Search, SearchError, SearchResult, SearchResults};
+use alith_core::tool::{StructureTool, ToolError};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+
+pub const DEFAULT_URL: &str = "http://localhost:8080";
+pub const DEFAULT_MAX_COUNT: usize = 10;
+
+/// Searches with a SearxNG instance, which must allow the `json` format in its settings.
+pub struct Searcher {
+    url: String,
+    client: Client,
+    max_count: usize,
+}
+
+impl Default for Searcher {
+    fn default() -> Self {
+        Self::new(DEFAULT_URL)
+    }
+}
+
+impl Searcher {
+    pub fn new(url: impl ToString) -> Self {
+        Self {
+            url: url.to_string(),
+            client: Client::new(),
+            max_count: DEFAULT_MAX_COUNT,
+        }
+    }
+
+    pub fn max_count(mut self, max_count: usize) -> Self {
+        self.max_count = max_count;
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    results: Vec<Item>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+    #[serde(default)]
+    title: String,
+    url: String,
+    #[serde(default)]
+    content: String,
+    published_date: Option<String>,
+    score: Option<f64>,
+}
+
+/// Parses the results of the JSON response.
+pub fn parse_results(body: &str, max_count: usize) -> Result<SearchResults, SearchError> {
+    let response: Response = serde_json::from_str(body)?;
+    Ok(response
+        .results
+        .into_iter()
+        .take(max_count)
+        .map(|item| SearchResult {
+            source: host(&item.url),
+            title: item.title,
+            link: item.url,
+            snippet: item.content,
+            published: item.published_date,
+            score: item.score,
+        })
+        .collect())
+}
+
+#[async_trait]
+impl Search for Searcher {
+    async fn search(&self, query: &str) -> Result<SearchResults, SearchError> {
+        let mut url = Url::parse(&self.url)?;
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        let mut url = url.join("search")?;
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("format", "json");
+
+        let body = send_request(self.client.get(url)).await?;
+        parse_results(&body, self.max_count)
+    }
+}
+
+#[async_trait]
+impl StructureTool for Searcher {
+    type Input = String;
+    type Output = SearchResults;
+
+    fn name(&self) -> &str {
+        "SearxNGSearch"
+    }
+
+    fn description(&self) -> &str {
+        r#"SearxNGSearch is a tool designed to perform search queries with a SearxNG metasearch engine.
+It takes a search query string as input and returns relevant search results with their publication date and score.
+This tool is ideal for scenarios where real-time information from the internet is required.
+"#
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        self.search(&input)
+            .await
+            .map_err(|err| ToolError::NormalError(Box::new(err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_results() {
+        let body = r#"{
+            "query": "alith",
+            "results": [
+                {"url": "https://www.example.com/a", "title": "A", "content": "First", "publishedDate": "2025-03-01T00:00:00", "engine": "bing", "score": 2.5},
+                {"url": "https://example.org/b", "title": "B", "content": "Second", "publishedDate": null, "engine": "brave", "score": 1.0}
+            ]
+        }"#;
+        let results = parse_results(body, 1).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "A");
+        assert_eq!(results[0].source.as_deref(), Some("example.com"));
+        assert_eq!(results[0].published.as_deref(), Some("2025-03-01T00:00:00"));
+        assert_eq!(results[0].score, Some(2.5));
+
+        assert!(matches!(
+            parse_results("<html>", 10),
+            Err(SearchError::JsonError(_))
+        ));
+    }
+}
@@ -0,0 +1,160 @@
+use super::{host, send_request, Search, SearchError, SearchResult, SearchResults};
+use alith_core::tool::{StructureTool, ToolError};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use url::Url;
+
+pub const DEFAULT_URL: &str = "https://api.tavily.com/search";
+pub const DEFAULT_MAX_COUNT: usize = 10;
+/// The maximum number of results of a request.
+const MAX_COUNT: usize = 20;
+
+/// Searches with the Tavily API.
+pub struct Searcher {
+    url: String,
+    api_key: String,
+    client: Client,
+    max_count: usize,
+}
+
+impl Searcher {
+    pub fn new(api_key: impl ToString) -> Self {
+        Self {
+            url: DEFAULT_URL.to_string(),
+            api_key: api_key.to_string(),
+            client: Client::new(),
+            max_count: DEFAULT_MAX_COUNT,
+        }
+    }
+
+    pub fn url(mut self, url: impl ToString) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    pub fn max_count(mut self, max_count: usize) -> Self {
+        self.max_count = max_count;
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    results: Vec<Item>,
+    /// The error, as a message or an object with an `error` message.
+    detail: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct Item {
+    #[serde(default)]
+    title: String,
+    url: String,
+    #[serde(default)]
+    content: String,
+    score: Option<f64>,
+    published_date: Option<String>,
+}
+
+/// Parses the results of the JSON response.
+pub fn parse_results(body: &str, max_count: usize) -> Result<SearchResults, SearchError> {
+    let response: Response = serde_json::from_str(body)?;
+    if let Some(detail) = response.detail {
+        let message = detail["error"].as_str().or(detail.as_str());
+        return Err(SearchError::SearchError(
+            message.map_or_else(|| detail.to_string(), str::to_string),
+        ));
+    }
+    Ok(response
+        .results
+        .into_iter()
+        .take(max_count)
+        .map(|item| SearchResult {
+            source: host(&item.url),
+            title: item.title,
+            link: item.url,
+            snippet: item.content,
+            published: item.published_date,
+            score: item.score,
+        })
+        .collect())
+}
+
+#[async_trait]
+impl Search for Searcher {
+    async fn search(&self, query: &str) -> Result<SearchResults, SearchError> {
+        let request = self
+            .client
+            .post(Url::parse(&self.url)?)
+            .bearer_auth(&self.api_key)
+            .json(&json!({
+                "query": query,
+                "max_results": self.max_count.min(MAX_COUNT),
+            }));
+        let body = send_request(request).await?;
+        parse_results(&body, self.max_count)
+    }
+}
+
+#[async_trait]
+impl StructureTool for Searcher {
+    type Input = String;
+    type Output = SearchResults;
+
+    fn name(&self) -> &str {
+        "TavilySearch"
+    }
+
+    fn description(&self) -> &str {
+        r#"TavilySearch is a tool designed to perform search queries with the Tavily search engine built for AI agents.
+It takes a search query string as input and returns relevant search results with their relevance score.
+This tool is ideal for scenarios where real-time information from the internet is required.
+"#
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        self.search(&input)
+            .await
+            .map_err(|err| ToolError::NormalError(Box::new(err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_results() {
+        let body = r#"{
+            "query": "alith",
+            "answer": null,
+            "results": [
+                {"title": "A", "url": "https://www.example.com/a", "content": "First", "score": 0.9, "published_date": "Sat, 01 Mar 2025 00:00:00 GMT"},
+                {"title": "B", "url": "https://example.org/b", "content": "Second", "score": 0.5}
+            ],
+            "response_time": 1.2
+        }"#;
+        let results = parse_results(body, 10).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].source.as_deref(), Some("example.com"));
+        assert_eq!(results[0].score, Some(0.9));
+        assert_eq!(
+            results[0].published.as_deref(),
+            Some("Sat, 01 Mar 2025 00:00:00 GMT")
+        );
+        assert_eq!(results[1].published, None);
+        assert_eq!(parse_results(body, 1).unwrap().len(), 1);
+
+        assert!(parse_results(r#"{"query": "alith"}"#, 10)
+            .unwrap()
+            .is_empty());
+        let error = r#"{"detail": {"error": "Unauthorized: missing or invalid API key."}}"#;
+        assert!(matches!(
+            parse_results(error, 10),
+            Err(SearchError::SearchError(message)) if message == "Unauthorized: missing or invalid API key."
+        ));
+    }
+}
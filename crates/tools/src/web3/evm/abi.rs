@@ -0,0 +1,98 @@
+//! The encoding of contract calls from human-readable signatures, and the decoding of their
+//! results to JSON.
+
+use super::EvmError;
+use alloy::dyn_abi::{DynSolValue, JsonAbiExt, Specifier};
+use alloy::json_abi::Function;
+use alloy::primitives::hex;
+use serde_json::{json, Value};
+
+/// Parses a function signature such as `balanceOf(address)(uint256)` or
+/// `function balanceOf(address owner) view returns (uint256)`.
+pub fn parse_function(signature: &str) -> Result<Function, EvmError> {
+    Function::parse(signature).map_err(|err| EvmError::InvalidInput(err.to_string()))
+}
+
+/// Encodes the call data of the function, the arguments being coerced from their string
+/// form according to the parameter types.
+pub fn encode_call(function: &Function, args: &[String]) -> Result<Vec<u8>, EvmError> {
+    if function.inputs.len() != args.len() {
+        return Err(EvmError::InvalidInput(format!(
+            "{} expects {} arguments, got {}",
+            function.signature(),
+            function.inputs.len(),
+            args.len()
+        )));
+    }
+    let values = function
+        .inputs
+        .iter()
+        .zip(args)
+        .map(|(param, arg)| Ok(param.resolve()?.coerce_str(arg)?))
+        .collect::<Result<Vec<_>, EvmError>>()?;
+    Ok(function.abi_encode_input(&values)?)
+}
+
+/// Decodes the data returned by the function: a single output is returned as is, several
+/// outputs as an array.
+pub fn decode_output(function: &Function, data: &[u8]) -> Result<Value, EvmError> {
+    let mut values = function.abi_decode_output(data)?;
+    Ok(if values.len() == 1 {
+        to_json(&values.remove(0))
+    } else {
+        Value::Array(values.iter().map(to_json).collect())
+    })
+}
+
+/// Converts an ABI value to JSON. The integers are written as decimal strings since they
+/// don't fit in a JSON number.
+pub fn to_json(value: &DynSolValue) -> Value {
+    if let Some(b) = value.as_bool() {
+        json!(b)
+    } else if let Some((i, _)) = value.as_int() {
+        json!(i.to_string())
+    } else if let Some((u, _)) = value.as_uint() {
+        json!(u.to_string())
+    } else if let Some(address) = value.as_address() {
+        json!(address.to_checksum(None))
+    } else if let Some((bytes, size)) = value.as_fixed_bytes() {
+        json!(hex::encode_prefixed(&bytes[..size]))
+    } else if let Some(bytes) = value.as_bytes() {
+        json!(hex::encode_prefixed(bytes))
+    } else if let Some(function) = value.as_function() {
+        json!(hex::encode_prefixed(function.as_slice()))
+    } else if let Some(s) = value.as_str() {
+        json!(s)
+    } else if let Some(values) = value
+        .as_array()
+        .or_else(|| value.as_fixed_array())
+        .or_else(|| value.as_tuple())
+    {
+        Value::Array(values.iter().map(to_json).collect())
+    } else {
+        Value::Null
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_and_decode_call() {
+        let function = parse_function("balanceOf(address)(uint256)").unwrap();
+        let data = encode_call(
+            &function,
+            &["0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".to_string()],
+        )
+        .unwrap();
+        assert_eq!(hex::encode(&data[..4]), "70a08231");
+        assert_eq!(data.len(), 36);
+
+        let mut output = [0u8; 32];
+        output[31] = 42;
+        assert_eq!(decode_output(&function, &output).unwrap(), json!("42"));
+
+        assert!(encode_call(&function, &[]).is_err());
+    }
+}
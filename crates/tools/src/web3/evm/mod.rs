@@ -0,0 +1,811 @@
+//! Tools for EVM chains: balances, contract calls, gas estimation, transactions signed with a
+//! local key and their receipts. They can be tried against a local node with
+//! [`EvmConfig::anvil`].
+
+use super::policy::{PolicyEngine, Spend, TransactionIntent, NATIVE_ASSET};
+use alith_core::tool::{StructureTool, Tool, ToolError};
+use alloy::consensus::TxEnvelope;
+use alloy::eips::eip2718::Encodable2718;
+use alloy::network::{EthereumWallet, ReceiptResponse, TransactionBuilder};
+use alloy::primitives::utils::{format_units, parse_ether, parse_units, UnitsError};
+use alloy::primitives::{hex, keccak256, Address, Bytes, TxHash, U256};
+use alloy::providers::{Provider, ProviderBuilder, SendableTx};
+use alloy::rpc::types::TransactionRequest;
+use alloy::signers::local::{LocalSignerError, PrivateKeySigner};
+use alloy::sol;
+use alloy::sol_types::SolCall;
+use alloy::transports::TransportError;
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use url::Url;
+
+pub mod abi;
+
+/// The RPC URL of a local anvil or hardhat node.
+pub const ANVIL_RPC_URL: &str = "http://127.0.0.1:8545";
+/// The chain ID of a local anvil or hardhat node.
+pub const ANVIL_CHAIN_ID: u64 = 31337;
+/// The decimals of the native token.
+pub const NATIVE_DECIMALS: u8 = 18;
+
+sol! {
+    #[sol(rpc)]
+    interface IERC20 {
+        function balanceOf(address owner) external view returns (uint256);
+        function decimals() external view returns (uint8);
+        function symbol() external view returns (string);
+        function transfer(address to, uint256 amount) external returns (bool);
+        function transferFrom(address from, address to, uint256 amount) external returns (bool);
+        function approve(address spender, uint256 amount) external returns (bool);
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("EVM error")]
+pub enum EvmError {
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    #[error("No signer is configured")]
+    MissingSigner,
+    #[error("The node is on chain {actual}, expected {expected}")]
+    ChainIdMismatch { expected: u64, actual: u64 },
+    #[error("RPC error: {0}")]
+    RpcError(#[from] TransportError),
+    #[error("Contract error: {0}")]
+    ContractError(#[from] alloy::contract::Error),
+    #[error("ABI error: {0}")]
+    AbiError(#[from] alloy::dyn_abi::Error),
+    #[error("Units error: {0}")]
+    UnitsError(#[from] UnitsError),
+    #[error("Signer error: {0}")]
+    SignerError(#[from] LocalSignerError),
+    #[error("Url error: {0}")]
+    UrlError(#[from] url::ParseError),
+}
+
+impl From<EvmError> for ToolError {
+    fn from(err: EvmError) -> Self {
+        ToolError::NormalError(Box::new(err))
+    }
+}
+
+/// The chain the tools talk to, the key signing the transactions and the policy they must
+/// comply with.
+#[derive(Debug, Clone)]
+pub struct EvmConfig {
+    pub rpc_url: Url,
+    pub chain_id: u64,
+    signer: Option<PrivateKeySigner>,
+    policy: Option<Arc<PolicyEngine>>,
+}
+
+impl EvmConfig {
+    pub fn new(rpc_url: impl AsRef<str>, chain_id: u64) -> Result<Self, EvmError> {
+        Ok(Self {
+            rpc_url: Url::parse(rpc_url.as_ref())?,
+            chain_id,
+            signer: None,
+            policy: None,
+        })
+    }
+
+    /// A local anvil or hardhat node.
+    pub fn anvil() -> Self {
+        Self {
+            rpc_url: Url::parse(ANVIL_RPC_URL).expect("valid anvil url"),
+            chain_id: ANVIL_CHAIN_ID,
+            signer: None,
+            policy: None,
+        }
+    }
+
+    pub fn signer(mut self, signer: PrivateKeySigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Signs with the key of an encrypted JSON keystore.
+    pub fn keystore(
+        self,
+        path: impl AsRef<Path>,
+        password: impl AsRef<[u8]>,
+    ) -> Result<Self, EvmError> {
+        Ok(self.signer(PrivateKeySigner::decrypt_keystore(path, password)?))
+    }
+
+    /// Signs with a hex encoded private key, e.g. one of the anvil dev accounts.
+    pub fn private_key(self, key: &str) -> Result<Self, EvmError> {
+        Ok(self.signer(PrivateKeySigner::from_str(key)?))
+    }
+
+    /// Checks the transactions against the policy before signing them.
+    pub fn policy(mut self, policy: Arc<PolicyEngine>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// The name of the chain in the policy, e.g. `evm:1`.
+    #[inline]
+    pub fn chain(&self) -> String {
+        format!("evm:{}", self.chain_id)
+    }
+
+    /// The address of the signer.
+    #[inline]
+    pub fn address(&self) -> Option<Address> {
+        self.signer.as_ref().map(PrivateKeySigner::address)
+    }
+
+    /// All the EVM tools sharing this config.
+    pub fn tools(&self) -> Vec<Box<dyn Tool>> {
+        vec![
+            Box::new(EvmBalanceTool::new(self.clone())),
+            Box::new(EvmReadContractTool::new(self.clone())),
+            Box::new(EvmEstimateGasTool::new(self.clone())),
+            Box::new(EvmSignTransactionTool::new(self.clone())),
+            Box::new(EvmSendTransactionTool::new(self.clone())),
+            Box::new(EvmTransferTool::new(self.clone())),
+            Box::new(EvmReceiptTool::new(self.clone())),
+        ]
+    }
+
+    pub fn provider(&self) -> impl Provider {
+        ProviderBuilder::new().connect_http(self.rpc_url.clone())
+    }
+
+    /// A provider filling and signing the transactions with the configured signer, after
+    /// checking the node is on the configured chain.
+    async fn wallet_provider(&self) -> Result<impl Provider, EvmError> {
+        let signer = self.signer.clone().ok_or(EvmError::MissingSigner)?;
+        let provider = ProviderBuilder::new()
+            .wallet(EthereumWallet::from(signer))
+            .connect_http(self.rpc_url.clone());
+        let actual = provider.get_chain_id().await?;
+        if actual != self.chain_id {
+            return Err(EvmError::ChainIdMismatch {
+                expected: self.chain_id,
+                actual,
+            });
+        }
+        Ok(provider)
+    }
+
+    /// Signs the transaction with the configured signer. When there is a policy, the
+    /// transaction is checked against it, and simulated if required, before being signed.
+    pub async fn sign(
+        &self,
+        tx: TransactionRequest,
+        signature: Option<String>,
+    ) -> Result<TxEnvelope, ToolError> {
+        let provider = self.wallet_provider().await?;
+        let tx = tx
+            .with_from(self.address().ok_or(EvmError::MissingSigner)?)
+            .with_chain_id(self.chain_id);
+        let intent = self.intent(&tx, signature);
+        if let Some(policy) = &self.policy {
+            policy.check(&intent)?;
+            if policy.requires_simulation(&intent.chain) {
+                if let Err(err) = provider.call(tx.clone()).await {
+                    let reason = format!("the simulation of the transaction failed: {err}");
+                    return Err(policy.deny(&intent, &reason).into());
+                }
+            }
+        }
+        let SendableTx::Envelope(envelope) = provider.fill(tx).await.map_err(EvmError::from)?
+        else {
+            return Err(ToolError::Unknown(
+                "the transaction was not signed".to_string(),
+            ));
+        };
+        if let Some(policy) = &self.policy {
+            policy.approve(&intent, &envelope.tx_hash().to_string())?;
+        }
+        Ok(envelope)
+    }
+
+    /// Describes the transaction for the policy: it spends its native value, and an ERC-20
+    /// transfer, `transferFrom` or approval also the token amount. The recipient or spender
+    /// of a token call is checked as the destination, and the other calls with data are
+    /// opaque.
+    fn intent(&self, tx: &TransactionRequest, signature: Option<String>) -> TransactionIntent {
+        let to = tx.to().map(|to| to.to_checksum(None)).unwrap_or_default();
+        let data: &[u8] = tx.input().map(|data| data.as_ref()).unwrap_or_default();
+        let mut methods: Vec<String> = signature.into_iter().collect();
+        if data.len() >= 4 {
+            methods.push(hex::encode_prefixed(&data[..4]));
+        }
+        let value: u128 = tx.value().unwrap_or_default().saturating_to();
+        let mut intent = TransactionIntent {
+            chain: self.chain(),
+            to: to.clone(),
+            contract: None,
+            spends: vec![Spend::new(NATIVE_ASSET, value)],
+            methods,
+            opaque_call: false,
+        };
+        let token_call = if let Ok(call) = IERC20::transferCall::abi_decode(data) {
+            Some((IERC20::transferCall::SIGNATURE, call.to, call.amount))
+        } else if let Ok(call) = IERC20::transferFromCall::abi_decode(data) {
+            Some((IERC20::transferFromCall::SIGNATURE, call.to, call.amount))
+        } else if let Ok(call) = IERC20::approveCall::abi_decode(data) {
+            Some((IERC20::approveCall::SIGNATURE, call.spender, call.amount))
+        } else {
+            None
+        };
+        match token_call {
+            Some((signature, recipient, amount)) => {
+                if !intent.methods.iter().any(|m| m == signature) {
+                    intent.methods.push(signature.to_string());
+                }
+                intent.to = recipient.to_checksum(None);
+                intent.contract = Some(to.clone());
+                intent.spends.push(Spend::new(to, amount.saturating_to()));
+            }
+            None => intent.opaque_call = !data.is_empty(),
+        }
+        intent
+    }
+
+    /// Builds the transaction described by the input, from the signer if there is one.
+    fn transaction(&self, input: &TransactionInput) -> Result<TransactionRequest, EvmError> {
+        let mut tx = TransactionRequest::default()
+            .with_to(parse_address(&input.to)?)
+            .with_chain_id(self.chain_id);
+        if let Some(from) = self.address() {
+            tx = tx.with_from(from);
+        }
+        if let Some(value) = &input.value {
+            tx = tx.with_value(parse_ether(value)?);
+        }
+        match (&input.data, &input.signature) {
+            (Some(_), Some(_)) => {
+                return Err(EvmError::InvalidInput(
+                    "either data or signature can be given, not both".to_string(),
+                ))
+            }
+            (Some(data), None) => {
+                let data =
+                    hex::decode(data).map_err(|err| EvmError::InvalidInput(err.to_string()))?;
+                tx = tx.with_input(Bytes::from(data));
+            }
+            (None, Some(signature)) => {
+                let function = abi::parse_function(signature)?;
+                tx = tx.with_input(Bytes::from(abi::encode_call(&function, &input.args)?));
+            }
+            (None, None) => {}
+        }
+        Ok(tx)
+    }
+}
+
+fn parse_address(address: &str) -> Result<Address, EvmError> {
+    Address::from_str(address.trim()).map_err(|_| EvmError::InvalidAddress(address.to_string()))
+}
+
+fn parse_hash(hash: &str) -> Result<TxHash, EvmError> {
+    TxHash::from_str(hash.trim())
+        .map_err(|_| EvmError::InvalidInput(format!("invalid transaction hash {hash}")))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BalanceInput {
+    /// The address holding the balance.
+    pub address: String,
+    /// The address of an ERC-20 token, the native token when omitted.
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceOutput {
+    pub address: String,
+    pub token: Option<String>,
+    pub symbol: Option<String>,
+    /// The balance in the smallest unit.
+    pub balance: String,
+    pub decimals: u8,
+    /// The balance in whole tokens.
+    pub formatted: String,
+}
+
+/// Queries the native or ERC-20 balance of an address.
+pub struct EvmBalanceTool {
+    config: EvmConfig,
+}
+
+impl EvmBalanceTool {
+    pub fn new(config: EvmConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl StructureTool for EvmBalanceTool {
+    type Input = BalanceInput;
+    type Output = BalanceOutput;
+
+    fn name(&self) -> &str {
+        "evm_get_balance"
+    }
+
+    fn description(&self) -> &str {
+        "Gets the native token balance of an address, or its ERC-20 balance when a token address is given."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let provider = self.config.provider();
+        let address = parse_address(&input.address)?;
+        let (balance, decimals, symbol) = match &input.token {
+            Some(token) => {
+                let token = IERC20::new(parse_address(token)?, &provider);
+                let balance = token
+                    .balanceOf(address)
+                    .call()
+                    .await
+                    .map_err(EvmError::from)?;
+                let decimals = token.decimals().call().await.map_err(EvmError::from)?;
+                let symbol = token.symbol().call().await.map_err(EvmError::from)?;
+                (balance, decimals, Some(symbol))
+            }
+            None => (
+                provider
+                    .get_balance(address)
+                    .await
+                    .map_err(EvmError::from)?,
+                NATIVE_DECIMALS,
+                None,
+            ),
+        };
+        Ok(BalanceOutput {
+            address: input.address,
+            token: input.token,
+            symbol,
+            balance: balance.to_string(),
+            decimals,
+            formatted: format_units(balance, decimals).map_err(EvmError::from)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReadContractInput {
+    /// The address of the contract.
+    pub address: String,
+    /// The function signature with its outputs, e.g. `balanceOf(address)(uint256)` or
+    /// `function getReserves() view returns (uint112, uint112, uint32)`.
+    pub signature: String,
+    /// The arguments of the function, e.g. `0x...` for addresses or `[1,2]` for arrays.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// Reads the state of a contract by calling one of its view functions.
+pub struct EvmReadContractTool {
+    config: EvmConfig,
+}
+
+impl EvmReadContractTool {
+    pub fn new(config: EvmConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl StructureTool for EvmReadContractTool {
+    type Input = ReadContractInput;
+    type Output = Value;
+
+    fn name(&self) -> &str {
+        "evm_read_contract"
+    }
+
+    fn description(&self) -> &str {
+        "Calls a view function of a contract and returns its decoded outputs, the integers being written as decimal strings."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let function = abi::parse_function(&input.signature)?;
+        let tx = TransactionRequest::default()
+            .with_to(parse_address(&input.address)?)
+            .with_input(Bytes::from(abi::encode_call(&function, &input.args)?));
+        let output = self
+            .config
+            .provider()
+            .call(tx)
+            .await
+            .map_err(EvmError::from)?;
+        Ok(abi::decode_output(&function, &output)?)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TransactionInput {
+    /// The recipient or the contract called.
+    pub to: String,
+    /// The amount of native token sent, in whole tokens, e.g. `0.1`.
+    #[serde(default)]
+    pub value: Option<String>,
+    /// The hex encoded call data.
+    #[serde(default)]
+    pub data: Option<String>,
+    /// The signature of the function called, instead of the call data, e.g.
+    /// `approve(address,uint256)`.
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// The arguments of the function called.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasEstimate {
+    pub gas: u64,
+    /// The gas price in wei.
+    pub gas_price: String,
+    /// The estimated fee in whole native tokens.
+    pub fee: String,
+}
+
+/// Estimates the gas used by a transaction.
+pub struct EvmEstimateGasTool {
+    config: EvmConfig,
+}
+
+impl EvmEstimateGasTool {
+    pub fn new(config: EvmConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl StructureTool for EvmEstimateGasTool {
+    type Input = TransactionInput;
+    type Output = GasEstimate;
+
+    fn name(&self) -> &str {
+        "evm_estimate_gas"
+    }
+
+    fn description(&self) -> &str {
+        "Estimates the gas and the fee of a transaction sending native tokens or calling a contract."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let provider = self.config.provider();
+        let tx = self.config.transaction(&input)?;
+        let gas = provider.estimate_gas(tx).await.map_err(EvmError::from)?;
+        let gas_price = provider.get_gas_price().await.map_err(EvmError::from)?;
+        let fee = U256::from(gas) * U256::from(gas_price);
+        Ok(GasEstimate {
+            gas,
+            gas_price: gas_price.to_string(),
+            fee: format_units(fee, NATIVE_DECIMALS).map_err(EvmError::from)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTransaction {
+    pub hash: String,
+    /// The hex encoded signed transaction, to be sent with `evm_send_transaction`.
+    pub raw_transaction: String,
+}
+
+/// Builds a transaction, filling its nonce, gas and fees from the node, and signs it
+/// without sending it.
+pub struct EvmSignTransactionTool {
+    config: EvmConfig,
+}
+
+impl EvmSignTransactionTool {
+    pub fn new(config: EvmConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl StructureTool for EvmSignTransactionTool {
+    type Input = TransactionInput;
+    type Output = SignedTransaction;
+
+    fn name(&self) -> &str {
+        "evm_sign_transaction"
+    }
+
+    fn description(&self) -> &str {
+        "Builds and signs a transaction sending native tokens or calling a contract, without sending it."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let tx = self.config.transaction(&input)?;
+        let signature = input
+            .signature
+            .as_deref()
+            .map(abi::parse_function)
+            .transpose()?
+            .map(|function| function.signature());
+        let envelope = self.config.sign(tx, signature).await?;
+        Ok(SignedTransaction {
+            hash: envelope.tx_hash().to_string(),
+            raw_transaction: hex::encode_prefixed(envelope.encoded_2718()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SendTransactionInput {
+    /// The hex encoded signed transaction.
+    pub raw_transaction: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentTransaction {
+    pub hash: String,
+}
+
+/// Sends a signed transaction. When there is a policy, only the transactions signed under it
+/// are sent.
+pub struct EvmSendTransactionTool {
+    config: EvmConfig,
+}
+
+impl EvmSendTransactionTool {
+    pub fn new(config: EvmConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl StructureTool for EvmSendTransactionTool {
+    type Input = SendTransactionInput;
+    type Output = SentTransaction;
+
+    fn name(&self) -> &str {
+        "evm_send_transaction"
+    }
+
+    fn description(&self) -> &str {
+        "Sends a signed transaction and returns its hash."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let raw = hex::decode(&input.raw_transaction)
+            .map_err(|err| EvmError::InvalidInput(err.to_string()))?;
+        if let Some(policy) = &self.config.policy {
+            policy.check_send(&self.config.chain(), &keccak256(&raw).to_string())?;
+        }
+        let pending = self
+            .config
+            .provider()
+            .send_raw_transaction(&raw)
+            .await
+            .map_err(EvmError::from)?;
+        Ok(SentTransaction {
+            hash: pending.tx_hash().to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TransferInput {
+    /// The recipient.
+    pub to: String,
+    /// The amount in whole tokens, e.g. `1.5`.
+    pub amount: String,
+    /// The address of an ERC-20 token, the native token when omitted.
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// Signs and sends a transfer of native or ERC-20 tokens.
+pub struct EvmTransferTool {
+    config: EvmConfig,
+}
+
+impl EvmTransferTool {
+    pub fn new(config: EvmConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl StructureTool for EvmTransferTool {
+    type Input = TransferInput;
+    type Output = SentTransaction;
+
+    fn name(&self) -> &str {
+        "evm_transfer"
+    }
+
+    fn description(&self) -> &str {
+        "Transfers native tokens, or ERC-20 tokens when a token address is given, and returns the transaction hash."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let provider = self.config.provider();
+        let to = parse_address(&input.to)?;
+        let tx = TransactionRequest::default();
+        let tx = match &input.token {
+            Some(token) => {
+                let token = parse_address(token)?;
+                let decimals = IERC20::new(token, &provider)
+                    .decimals()
+                    .call()
+                    .await
+                    .map_err(EvmError::from)?;
+                let amount = parse_units(&input.amount, decimals)
+                    .map_err(EvmError::from)?
+                    .get_absolute();
+                tx.with_to(token)
+                    .with_input(IERC20::transferCall { to, amount }.abi_encode())
+            }
+            None => tx
+                .with_to(to)
+                .with_value(parse_ether(&input.amount).map_err(EvmError::from)?),
+        };
+        let envelope = self.config.sign(tx, None).await?;
+        let pending = provider
+            .send_raw_transaction(&envelope.encoded_2718())
+            .await
+            .map_err(EvmError::from)?;
+        Ok(SentTransaction {
+            hash: pending.tx_hash().to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReceiptInput {
+    /// The hash of the transaction.
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Receipt {
+    pub hash: String,
+    /// Whether the transaction succeeded.
+    pub status: bool,
+    pub block_number: Option<u64>,
+    pub from: String,
+    pub to: Option<String>,
+    pub contract_address: Option<String>,
+    pub gas_used: u64,
+    /// The gas price paid in wei.
+    pub effective_gas_price: String,
+}
+
+/// Fetches the receipt of a transaction, `null` while it is pending.
+pub struct EvmReceiptTool {
+    config: EvmConfig,
+}
+
+impl EvmReceiptTool {
+    pub fn new(config: EvmConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl StructureTool for EvmReceiptTool {
+    type Input = ReceiptInput;
+    type Output = Option<Receipt>;
+
+    fn name(&self) -> &str {
+        "evm_get_receipt"
+    }
+
+    fn description(&self) -> &str {
+        "Gets the receipt of a transaction: its status, block and gas used. Returns null while the transaction is pending."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let receipt = self
+            .config
+            .provider()
+            .get_transaction_receipt(parse_hash(&input.hash)?)
+            .await
+            .map_err(EvmError::from)?;
+        Ok(receipt.map(|receipt| Receipt {
+            hash: receipt.transaction_hash().to_string(),
+            status: receipt.status(),
+            block_number: receipt.block_number(),
+            from: receipt.from().to_checksum(None),
+            to: receipt.to().map(|to| to.to_checksum(None)),
+            contract_address: receipt.contract_address().map(|a| a.to_checksum(None)),
+            gas_used: receipt.gas_used(),
+            effective_gas_price: receipt.effective_gas_price().to_string(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The first dev account of anvil.
+    const ANVIL_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    #[test]
+    fn test_intent() {
+        const TOKEN: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+        const RECIPIENT: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+        let config = EvmConfig::anvil();
+        let recipient = parse_address(RECIPIENT).unwrap();
+        let call = |data: Vec<u8>| {
+            TransactionRequest::default()
+                .with_to(parse_address(TOKEN).unwrap())
+                .with_value(U256::from(5))
+                .with_input(Bytes::from(data))
+        };
+
+        let transfer = IERC20::transferCall {
+            to: recipient,
+            amount: U256::from(100),
+        };
+        let intent = config.intent(&call(transfer.abi_encode()), None);
+        assert_eq!(intent.to, RECIPIENT);
+        assert_eq!(intent.contract.as_deref(), Some(TOKEN));
+        assert_eq!(
+            intent.spends,
+            [Spend::new(NATIVE_ASSET, 5), Spend::new(TOKEN, 100)]
+        );
+        assert_eq!(intent.methods, ["0xa9059cbb", "transfer(address,uint256)"]);
+        assert!(!intent.opaque_call);
+
+        let approve = IERC20::approveCall {
+            spender: recipient,
+            amount: U256::MAX,
+        };
+        let intent = config.intent(&call(approve.abi_encode()), None);
+        assert_eq!(intent.to, RECIPIENT);
+        assert_eq!(
+            intent.spends,
+            [Spend::new(NATIVE_ASSET, 5), Spend::new(TOKEN, u128::MAX)]
+        );
+        assert_eq!(intent.methods, ["0x095ea7b3", "approve(address,uint256)"]);
+
+        let intent = config.intent(&call(vec![0xde, 0xad, 0xbe, 0xef]), None);
+        assert_eq!(intent.to, TOKEN);
+        assert_eq!(intent.contract, None);
+        assert_eq!(intent.spends, [Spend::new(NATIVE_ASSET, 5)]);
+        assert!(intent.opaque_call);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local anvil node"]
+    async fn test_transfer_on_anvil() {
+        let config = EvmConfig::anvil().private_key(ANVIL_KEY).unwrap();
+        let to = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string();
+
+        let sent = EvmTransferTool::new(config.clone())
+            .run_with_args(TransferInput {
+                to: to.clone(),
+                amount: "1".to_string(),
+                token: None,
+            })
+            .await
+            .unwrap();
+        let receipt = EvmReceiptTool::new(config.clone())
+            .run_with_args(ReceiptInput { hash: sent.hash })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(receipt.status);
+
+        let balance = EvmBalanceTool::new(config)
+            .run_with_args(BalanceInput {
+                address: to,
+                token: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(balance.decimals, NATIVE_DECIMALS);
+    }
+}
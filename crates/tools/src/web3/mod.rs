@@ -1 +1,3 @@
-
+pub mod evm;
+pub mod policy;
+pub mod solana;
@@ -0,0 +1,621 @@
+//! The guardrails checked before the web3 tools sign a transaction: spend limits, address and
+//! method lists, daily caps tracked in a local ledger and mandatory simulation. The calls that
+//! cannot be decoded are only made to allowed contracts. Every decision is written to the audit
+//! log.
+//!
+//! ```json
+//! {
+//!     "chains": {
+//!         "evm:1": {
+//!             "limits": { "native": { "maxPerTransaction": 100000000000000000, "dailyCap": 1000000000000000000 } },
+//!             "deniedAddresses": ["0x0000000000000000000000000000000000000000"],
+//!             "allowedMethods": ["transfer(address,uint256)", "0x095ea7b3"]
+//!         },
+//!         "solana": { "requireSimulation": true }
+//!     }
+//! }
+//! ```
+
+use alith_core::tool::ToolError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The asset name of the native token of a chain.
+pub const NATIVE_ASSET: &str = "native";
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, thiserror::Error)]
+#[error("Policy error")]
+pub enum PolicyError {
+    #[error("{0}")]
+    Violation(String),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("The policy state is poisoned")]
+    Poisoned,
+}
+
+impl From<PolicyError> for ToolError {
+    fn from(err: PolicyError) -> Self {
+        match err {
+            PolicyError::Violation(reason) => ToolError::PolicyViolation(reason),
+            err => ToolError::NormalError(Box::new(err)),
+        }
+    }
+}
+
+/// The spend limits of an asset, in its smallest unit, e.g. wei or lamports.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SpendLimit {
+    pub max_per_transaction: Option<u128>,
+    /// The maximum spent per UTC day.
+    pub daily_cap: Option<u128>,
+}
+
+/// The policy of a chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChainPolicy {
+    /// The limits by asset: [`NATIVE_ASSET`] or the address of a token. The assets without
+    /// limits can be spent freely.
+    pub limits: HashMap<String, SpendLimit>,
+    /// The only recipients and contracts allowed, any when none.
+    pub allowed_addresses: Option<HashSet<String>>,
+    pub denied_addresses: HashSet<String>,
+    /// The only contract methods allowed, by signature or selector, any when none.
+    pub allowed_methods: Option<HashSet<String>>,
+    /// Whether the transactions are simulated before being signed or sent.
+    pub require_simulation: bool,
+}
+
+impl Default for ChainPolicy {
+    fn default() -> Self {
+        Self {
+            limits: HashMap::new(),
+            allowed_addresses: None,
+            denied_addresses: HashSet::new(),
+            allowed_methods: None,
+            require_simulation: true,
+        }
+    }
+}
+
+/// The policies by chain, e.g. `evm:1` or `solana`. The chains without a policy are denied.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Web3Policy {
+    pub chains: HashMap<String, ChainPolicy>,
+}
+
+impl Web3Policy {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, PolicyError> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn chain(mut self, chain: impl ToString, policy: ChainPolicy) -> Self {
+        self.chains.insert(chain.to_string(), policy);
+        self
+    }
+}
+
+/// An amount of an asset spent by a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spend {
+    /// [`NATIVE_ASSET`] or the address of a token.
+    pub asset: String,
+    /// The amount in the smallest unit.
+    pub amount: u128,
+}
+
+impl Spend {
+    pub fn new(asset: impl ToString, amount: u128) -> Self {
+        Self {
+            asset: asset.to_string(),
+            amount,
+        }
+    }
+}
+
+/// A transaction to be signed, as seen by the policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionIntent {
+    pub chain: String,
+    /// The recipient of the funds or of the allowance, or the contract called.
+    pub to: String,
+    /// The token contract or mint, for token transfers and approvals.
+    pub contract: Option<String>,
+    /// The assets spent, e.g. the native value sent along with a token transfer. An approval
+    /// spends the amount it allows.
+    pub spends: Vec<Spend>,
+    /// The names the called method is known by, e.g. its signature and selector, empty for
+    /// plain transfers.
+    pub methods: Vec<String>,
+    /// Whether the call data could not be decoded, the contract called must then be allowed
+    /// explicitly.
+    #[serde(default)]
+    pub opaque_call: bool,
+}
+
+/// The amounts spent during the current day.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Ledger {
+    /// The days since the Unix epoch.
+    day: u64,
+    /// The amounts spent by `chain/asset`.
+    spent: HashMap<String, u128>,
+}
+
+#[derive(Debug, Default)]
+struct PolicyState {
+    ledger: Ledger,
+    /// The transactions signed under the policy, by hash or signature.
+    approved: HashSet<String>,
+}
+
+#[derive(Serialize)]
+struct AuditEntry<'a> {
+    timestamp: u64,
+    decision: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    intent: Option<&'a TransactionIntent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+}
+
+/// Enforces a [`Web3Policy`], shared by the tools of all the chains.
+#[derive(Debug)]
+pub struct PolicyEngine {
+    policy: Web3Policy,
+    ledger_path: Option<PathBuf>,
+    audit_path: Option<PathBuf>,
+    state: Mutex<PolicyState>,
+}
+
+impl PolicyEngine {
+    pub fn new(policy: Web3Policy) -> Self {
+        Self {
+            policy,
+            ledger_path: None,
+            audit_path: None,
+            state: Mutex::new(PolicyState::default()),
+        }
+    }
+
+    /// Keeps the daily spending in a JSON file, so the caps hold across restarts.
+    pub fn ledger(mut self, path: impl Into<PathBuf>) -> Result<Self, PolicyError> {
+        let path = path.into();
+        if path.exists() {
+            self.state
+                .get_mut()
+                .map_err(|_| PolicyError::Poisoned)?
+                .ledger = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+        }
+        self.ledger_path = Some(path);
+        Ok(self)
+    }
+
+    /// Appends every decision to a JSON lines file.
+    pub fn audit_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_path = Some(path.into());
+        self
+    }
+
+    #[inline]
+    pub fn policy(&self) -> &Web3Policy {
+        &self.policy
+    }
+
+    /// Whether the transactions of the chain must be simulated.
+    pub fn requires_simulation(&self, chain: &str) -> bool {
+        self.policy
+            .chains
+            .get(chain)
+            .is_none_or(|policy| policy.require_simulation)
+    }
+
+    /// Checks the transaction against the policy, without recording it.
+    pub fn check(&self, intent: &TransactionIntent) -> Result<(), PolicyError> {
+        let state = self.state()?;
+        match self.violation(&state.ledger, intent) {
+            Some(reason) => Err(self.deny(intent, &reason)),
+            None => Ok(()),
+        }
+    }
+
+    /// Records the signed transaction, identified by its hash or signature, and its spending.
+    /// It is checked again since other transactions may have been approved meanwhile.
+    pub fn approve(&self, intent: &TransactionIntent, id: &str) -> Result<(), PolicyError> {
+        let mut state = self.state()?;
+        if let Some(reason) = self.violation(&state.ledger, intent) {
+            return Err(self.deny(intent, &reason));
+        }
+        let today = today();
+        let mut ledger = if state.ledger.day == today {
+            state.ledger.clone()
+        } else {
+            Ledger {
+                day: today,
+                spent: HashMap::new(),
+            }
+        };
+        for spend in &intent.spends {
+            let spent = ledger
+                .spent
+                .entry(ledger_key(&intent.chain, &spend.asset))
+                .or_default();
+            *spent = spent.saturating_add(spend.amount);
+        }
+        if let Some(path) = &self.ledger_path {
+            write_ledger(path, &ledger)?;
+        }
+        state.ledger = ledger;
+        state.approved.insert(id.to_string());
+        self.audit(AuditEntry {
+            timestamp: now(),
+            decision: "approved",
+            intent: Some(intent),
+            id: Some(id),
+            reason: None,
+        });
+        Ok(())
+    }
+
+    /// Denies the transaction, e.g. after a failed simulation, and returns the violation.
+    pub fn deny(&self, intent: &TransactionIntent, reason: &str) -> PolicyError {
+        self.audit(AuditEntry {
+            timestamp: now(),
+            decision: "denied",
+            intent: Some(intent),
+            id: None,
+            reason: Some(reason),
+        });
+        PolicyError::Violation(reason.to_string())
+    }
+
+    /// Checks the transaction to be sent, identified by its hash or signature, was signed
+    /// under the policy.
+    pub fn check_send(&self, chain: &str, id: &str) -> Result<(), PolicyError> {
+        let approved = self.state()?.approved.contains(id);
+        if !approved {
+            let reason = format!("the transaction {id} on {chain} was not signed under the policy");
+            return Err(self.deny_send(id, &reason));
+        }
+        self.audit(AuditEntry {
+            timestamp: now(),
+            decision: "sent",
+            intent: None,
+            id: Some(id),
+            reason: None,
+        });
+        Ok(())
+    }
+
+    /// Denies sending the transaction, e.g. after a failed simulation, and returns the
+    /// violation.
+    pub fn deny_send(&self, id: &str, reason: &str) -> PolicyError {
+        self.audit(AuditEntry {
+            timestamp: now(),
+            decision: "denied",
+            intent: None,
+            id: Some(id),
+            reason: Some(reason),
+        });
+        PolicyError::Violation(reason.to_string())
+    }
+
+    fn state(&self) -> Result<MutexGuard<'_, PolicyState>, PolicyError> {
+        self.state.lock().map_err(|_| PolicyError::Poisoned)
+    }
+
+    fn violation(&self, ledger: &Ledger, intent: &TransactionIntent) -> Option<String> {
+        let Some(policy) = self.policy.chains.get(&intent.chain) else {
+            return Some(format!("no policy allows transactions on {}", intent.chain));
+        };
+        for address in std::iter::once(&intent.to).chain(intent.contract.as_ref()) {
+            if contains_address(&policy.denied_addresses, address) {
+                return Some(format!("the address {address} is denied"));
+            }
+            if let Some(allowed) = &policy.allowed_addresses {
+                if !contains_address(allowed, address) {
+                    return Some(format!("the address {address} is not allowed"));
+                }
+            }
+        }
+        if let Some(allowed) = &policy.allowed_methods {
+            let is_allowed = intent
+                .methods
+                .iter()
+                .any(|method| allowed.iter().any(|a| a.eq_ignore_ascii_case(method)));
+            if !intent.methods.is_empty() && !is_allowed {
+                return Some(format!(
+                    "the method {} is not allowed",
+                    intent.methods.join(" / ")
+                ));
+            }
+        }
+        if intent.opaque_call
+            && !policy
+                .allowed_addresses
+                .as_ref()
+                .is_some_and(|allowed| contains_address(allowed, &intent.to))
+        {
+            return Some(format!(
+                "the call to {} cannot be decoded and the contract is not allowed",
+                intent.to
+            ));
+        }
+        intent
+            .spends
+            .iter()
+            .find_map(|spend| limit_violation(policy, ledger, &intent.chain, spend))
+    }
+
+    fn audit(&self, entry: AuditEntry) {
+        match entry.decision {
+            "denied" => tracing::warn!(
+                "web3 policy denied {:?}: {}",
+                entry.intent,
+                entry.reason.unwrap_or_default()
+            ),
+            decision => tracing::info!("web3 policy {decision} {:?}", entry.id),
+        }
+        let Some(path) = &self.audit_path else {
+            return;
+        };
+        let result = serde_json::to_string(&entry)
+            .map_err(std::io::Error::from)
+            .and_then(|line| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| writeln!(file, "{line}"))
+            });
+        if let Err(err) = result {
+            tracing::error!("failed to write the web3 audit log: {err}");
+        }
+    }
+}
+
+/// EVM addresses are compared case insensitively, the others, e.g. base58 ones, exactly.
+fn same_address(a: &str, b: &str) -> bool {
+    if a.starts_with("0x") {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+fn contains_address(addresses: &HashSet<String>, address: &str) -> bool {
+    addresses.iter().any(|a| same_address(a, address))
+}
+
+/// Checks the spend against the limits of its asset, the assets without limits being spent
+/// freely.
+fn limit_violation(
+    policy: &ChainPolicy,
+    ledger: &Ledger,
+    chain: &str,
+    spend: &Spend,
+) -> Option<String> {
+    let limit = policy
+        .limits
+        .iter()
+        .find(|(asset, _)| same_address(asset, &spend.asset))
+        .map(|(_, limit)| limit)?;
+    if let Some(max) = limit.max_per_transaction {
+        if spend.amount > max {
+            return Some(format!(
+                "the amount {} of {} exceeds the limit of {max} per transaction",
+                spend.amount, spend.asset
+            ));
+        }
+    }
+    if let Some(cap) = limit.daily_cap {
+        let spent = if ledger.day == today() {
+            ledger
+                .spent
+                .get(&ledger_key(chain, &spend.asset))
+                .copied()
+                .unwrap_or_default()
+        } else {
+            0
+        };
+        if spent.saturating_add(spend.amount) > cap {
+            return Some(format!(
+                "the amount {} of {} exceeds the daily cap of {cap}, {spent} were already spent today",
+                spend.amount, spend.asset
+            ));
+        }
+    }
+    None
+}
+
+/// Writes the ledger to a temporary file renamed over the previous one, so a crash cannot
+/// leave it truncated.
+fn write_ledger(path: &Path, ledger: &Ledger) -> Result<(), PolicyError> {
+    let temp_path = temp_ledger_path(path);
+    std::fs::write(&temp_path, serde_json::to_string_pretty(ledger)?)?;
+    std::fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+fn temp_ledger_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+fn ledger_key(chain: &str, asset: &str) -> String {
+    if asset.starts_with("0x") {
+        format!("{chain}/{}", asset.to_lowercase())
+    } else {
+        format!("{chain}/{asset}")
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn today() -> u64 {
+    now() / SECONDS_PER_DAY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intent(to: &str, amount: u128, methods: &[&str]) -> TransactionIntent {
+        TransactionIntent {
+            chain: "evm:31337".to_string(),
+            to: to.to_string(),
+            contract: None,
+            spends: vec![Spend::new(NATIVE_ASSET, amount)],
+            methods: methods.iter().map(|m| m.to_string()).collect(),
+            opaque_call: false,
+        }
+    }
+
+    fn policy() -> Web3Policy {
+        Web3Policy::default().chain(
+            "evm:31337",
+            ChainPolicy {
+                limits: HashMap::from([(
+                    NATIVE_ASSET.to_string(),
+                    SpendLimit {
+                        max_per_transaction: Some(100),
+                        daily_cap: Some(150),
+                    },
+                )]),
+                denied_addresses: HashSet::from(["0xDEAD".to_string()]),
+                allowed_methods: Some(HashSet::from(["0xa9059cbb".to_string()])),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("alith-policy-{name}-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_policy_engine() {
+        let engine = PolicyEngine::new(policy());
+
+        assert!(engine.check(&intent("0xdead", 1, &[])).is_err());
+        assert!(engine.check(&intent("0xbeef", 101, &[])).is_err());
+        assert!(engine.check(&intent("0xbeef", 1, &["0x095ea7b3"])).is_err());
+        assert!(engine.check(&intent("0xbeef", 1, &["0xA9059CBB"])).is_ok());
+
+        engine.approve(&intent("0xbeef", 100, &[]), "0x1").unwrap();
+        assert!(matches!(
+            engine.approve(&intent("0xbeef", 60, &[]), "0x2"),
+            Err(PolicyError::Violation(_))
+        ));
+        assert!(engine.check_send("evm:31337", "0x1").is_ok());
+        assert!(engine.check_send("evm:31337", "0x2").is_err());
+
+        let mut other_chain = intent("0xbeef", 1, &[]);
+        other_chain.chain = "evm:1".to_string();
+        assert!(engine.check(&other_chain).is_err());
+    }
+
+    #[test]
+    fn test_spends_and_opaque_calls() {
+        let engine = PolicyEngine::new(policy());
+        let mut transfer = intent("0xbeef", 101, &["0xa9059cbb"]);
+        transfer.contract = Some("0xC0FFEE".to_string());
+        transfer.spends.push(Spend::new("0xc0ffee", 1_000_000));
+        // The native value exceeds its limit, whatever the token spent.
+        assert!(engine.check(&transfer).is_err());
+        transfer.spends[0].amount = 1;
+        assert!(engine.check(&transfer).is_ok());
+
+        let mut call = intent("0xc0ffee", 0, &["0xa9059cbb"]);
+        call.opaque_call = true;
+        assert!(engine.check(&call).is_err());
+        let mut policy = policy();
+        policy
+            .chains
+            .get_mut("evm:31337")
+            .unwrap()
+            .allowed_addresses = Some(HashSet::from(["0xC0FFEE".to_string()]));
+        assert!(PolicyEngine::new(policy).check(&call).is_ok());
+    }
+
+    #[test]
+    fn test_ledger_reload() {
+        let path = temp_path("ledger");
+        let engine = PolicyEngine::new(policy()).ledger(&path).unwrap();
+        engine.approve(&intent("0xbeef", 100, &[]), "0x1").unwrap();
+        drop(engine);
+
+        let engine = PolicyEngine::new(policy()).ledger(&path).unwrap();
+        assert!(engine.check(&intent("0xbeef", 60, &[])).is_err());
+        assert!(engine.check(&intent("0xbeef", 50, &[])).is_ok());
+        assert!(!temp_ledger_path(&path).exists());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_daily_cap_rollover() {
+        let path = temp_path("rollover");
+        let yesterday = Ledger {
+            day: today() - 1,
+            spent: HashMap::from([("evm:31337/native".to_string(), 150)]),
+        };
+        write_ledger(&path, &yesterday).unwrap();
+
+        let engine = PolicyEngine::new(policy()).ledger(&path).unwrap();
+        engine.approve(&intent("0xbeef", 100, &[]), "0x1").unwrap();
+        let ledger: Ledger =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(ledger.day, today());
+        assert_eq!(
+            ledger.spent,
+            HashMap::from([("evm:31337/native".to_string(), 100)])
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_audit_log() {
+        let path = temp_path("audit");
+        let engine = PolicyEngine::new(policy()).audit_log(&path);
+        engine.approve(&intent("0xbeef", 100, &[]), "0x1").unwrap();
+        assert!(engine.check(&intent("0xdead", 1, &[])).is_err());
+        engine.check_send("evm:31337", "0x1").unwrap();
+
+        let entries: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let decisions: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry["decision"].as_str().unwrap())
+            .collect();
+        assert_eq!(decisions, ["approved", "denied", "sent"]);
+        assert_eq!(entries[0]["id"], "0x1");
+        assert_eq!(entries[1]["intent"]["to"], "0xdead");
+        assert_eq!(entries[1]["reason"], "the address 0xdead is denied");
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,898 @@
+//! Tools for Solana: SOL and SPL token balances, account lookups, transfers signed with a local
+//! keypair, simulation and status polling. They can be tried against `solana-test-validator`
+//! with [`SolanaConfig::local`].
+
+use super::policy::{PolicyEngine, Spend, TransactionIntent, NATIVE_ASSET};
+use alith_core::tool::{StructureTool, Tool, ToolError};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::ClientError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use solana_transaction_status::TransactionConfirmationStatus;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use spl_token_2022::extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The name of the chain in the policy.
+pub const SOLANA_CHAIN: &str = "solana";
+/// The RPC URL of `solana-test-validator`.
+pub const LOCAL_RPC_URL: &str = "http://127.0.0.1:8899";
+/// The decimals of SOL.
+pub const SOL_DECIMALS: u8 = 9;
+/// The wait between two status checks of a transaction.
+pub const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// The longest wait for a transaction to be confirmed.
+pub const MAX_STATUS_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, thiserror::Error)]
+#[error("Solana error")]
+pub enum SolanaError {
+    #[error("Invalid public key: {0}")]
+    InvalidPubkey(String),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    #[error("No keypair is configured")]
+    MissingKeypair,
+    #[error("Failed to read the keypair: {0}")]
+    KeypairError(String),
+    #[error("Client error: {0}")]
+    ClientError(#[from] ClientError),
+    #[error("Program error: {0}")]
+    ProgramError(#[from] ProgramError),
+    #[error("Serialization error: {0}")]
+    SerializationError(#[from] bincode::Error),
+    #[error("Base64 error: {0}")]
+    Base64Error(#[from] base64::DecodeError),
+}
+
+impl From<SolanaError> for ToolError {
+    fn from(err: SolanaError) -> Self {
+        ToolError::NormalError(Box::new(err))
+    }
+}
+
+/// The cluster the tools talk to, the keypair signing the transactions and the policy they
+/// must comply with.
+#[derive(Debug, Clone)]
+pub struct SolanaConfig {
+    pub rpc_url: String,
+    pub commitment: CommitmentConfig,
+    keypair: Option<Arc<Keypair>>,
+    policy: Option<Arc<PolicyEngine>>,
+}
+
+impl SolanaConfig {
+    pub fn new(rpc_url: impl ToString) -> Self {
+        Self {
+            rpc_url: rpc_url.to_string(),
+            commitment: CommitmentConfig::confirmed(),
+            keypair: None,
+            policy: None,
+        }
+    }
+
+    /// A local `solana-test-validator`.
+    pub fn local() -> Self {
+        Self::new(LOCAL_RPC_URL)
+    }
+
+    pub fn commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn keypair(mut self, keypair: Keypair) -> Self {
+        self.keypair = Some(Arc::new(keypair));
+        self
+    }
+
+    /// Signs with the keypair of a JSON file, as written by `solana-keygen`.
+    pub fn keypair_file(self, path: impl AsRef<Path>) -> Result<Self, SolanaError> {
+        let keypair =
+            read_keypair_file(path).map_err(|err| SolanaError::KeypairError(err.to_string()))?;
+        Ok(self.keypair(keypair))
+    }
+
+    /// Checks the transactions against the policy before signing them.
+    pub fn policy(mut self, policy: Arc<PolicyEngine>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// The public key of the keypair.
+    #[inline]
+    pub fn pubkey(&self) -> Option<Pubkey> {
+        self.keypair.as_ref().map(|keypair| keypair.pubkey())
+    }
+
+    /// All the Solana tools sharing this config.
+    pub fn tools(&self) -> Vec<Box<dyn Tool>> {
+        vec![
+            Box::new(SolanaBalanceTool::new(self.clone())),
+            Box::new(SolanaAccountTool::new(self.clone())),
+            Box::new(SolanaSignTransferTool::new(self.clone())),
+            Box::new(SolanaSimulateTransactionTool::new(self.clone())),
+            Box::new(SolanaSendTransactionTool::new(self.clone())),
+            Box::new(SolanaSignatureStatusTool::new(self.clone())),
+        ]
+    }
+
+    pub fn client(&self) -> RpcClient {
+        RpcClient::new_with_commitment(self.rpc_url.clone(), self.commitment)
+    }
+
+    /// Builds and signs the transfer of SOL, or of an SPL token when a mint is given. When
+    /// there is a policy, the transfer is checked against it, and simulated if required.
+    pub async fn transfer(&self, input: &TransferInput) -> Result<Transaction, ToolError> {
+        let (transaction, intent) = self.build_transfer(input).await?;
+        let Some(policy) = &self.policy else {
+            return Ok(transaction);
+        };
+        policy.check(&intent)?;
+        if policy.requires_simulation(SOLANA_CHAIN) {
+            if let Some(reason) = self.simulation_error(&transaction).await? {
+                let reason = format!("the simulation of the transaction failed: {reason}");
+                return Err(policy.deny(&intent, &reason).into());
+            }
+        }
+        policy.approve(&intent, &transaction.signatures[0].to_string())?;
+        Ok(transaction)
+    }
+
+    /// Simulates the transaction, returning its error if it would fail.
+    async fn simulation_error(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Option<String>, SolanaError> {
+        let result = self.client().simulate_transaction(transaction).await?.value;
+        Ok(result.err.map(|err| err.to_string()))
+    }
+
+    /// Builds and signs the transfer, the associated token account of the recipient being
+    /// created if needed, and describes it for the policy.
+    async fn build_transfer(
+        &self,
+        input: &TransferInput,
+    ) -> Result<(Transaction, TransactionIntent), SolanaError> {
+        let keypair = self.keypair.as_ref().ok_or(SolanaError::MissingKeypair)?;
+        let from = keypair.pubkey();
+        let to = parse_pubkey(&input.to)?;
+        let client = self.client();
+        let (instructions, intent) = match &input.mint {
+            Some(mint) => {
+                let mint = parse_pubkey(mint)?;
+                let mint_account = mint_account(&client, &mint).await?;
+                let token_program = mint_account.owner;
+                let decimals = client.get_token_supply(&mint).await?.decimals;
+                let amount = parse_amount(&input.amount, decimals)?;
+                let recipient_account =
+                    get_associated_token_address_with_program_id(&to, &mint, &token_program);
+                // The rent of the token account of the recipient, paid when it is created.
+                let rent = match client
+                    .get_account_with_commitment(&recipient_account, client.commitment())
+                    .await?
+                    .value
+                {
+                    Some(_) => 0,
+                    None => {
+                        let len = token_account_len(&mint_account)?;
+                        client.get_minimum_balance_for_rent_exemption(len).await?
+                    }
+                };
+                let instructions = vec![
+                    create_associated_token_account_idempotent(&from, &to, &mint, &token_program),
+                    // The instruction of Token-2022 is also valid for the SPL Token program.
+                    spl_token_2022::instruction::transfer_checked(
+                        &token_program,
+                        &get_associated_token_address_with_program_id(&from, &mint, &token_program),
+                        &mint,
+                        &recipient_account,
+                        &from,
+                        &[],
+                        amount,
+                        decimals,
+                    )?,
+                ];
+                (
+                    instructions,
+                    transfer_intent(&to, Some(&mint), amount, rent),
+                )
+            }
+            None => {
+                let amount = parse_amount(&input.amount, SOL_DECIMALS)?;
+                (
+                    vec![system_instruction::transfer(&from, &to, amount)],
+                    transfer_intent(&to, None, amount, 0),
+                )
+            }
+        };
+        let blockhash = client.get_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&from),
+            &[keypair.as_ref()],
+            blockhash,
+        );
+        Ok((transaction, intent))
+    }
+}
+
+/// Describes a transfer for the policy, with the rent of the token account created for the
+/// recipient, if any, spent in SOL.
+fn transfer_intent(
+    to: &Pubkey,
+    mint: Option<&Pubkey>,
+    amount: u64,
+    rent: u64,
+) -> TransactionIntent {
+    let mut spends = vec![Spend::new(
+        mint.map_or_else(|| NATIVE_ASSET.to_string(), Pubkey::to_string),
+        amount.into(),
+    )];
+    if rent > 0 {
+        spends.push(Spend::new(NATIVE_ASSET, rent.into()));
+    }
+    TransactionIntent {
+        chain: SOLANA_CHAIN.to_string(),
+        to: to.to_string(),
+        contract: mint.map(Pubkey::to_string),
+        spends,
+        methods: vec![],
+        opaque_call: false,
+    }
+}
+
+/// The account of the mint, owned either by the SPL Token program or Token-2022.
+async fn mint_account(client: &RpcClient, mint: &Pubkey) -> Result<Account, SolanaError> {
+    let account = client.get_account(mint).await?;
+    if account.owner == spl_token::id() || account.owner == spl_token_2022::id() {
+        Ok(account)
+    } else {
+        Err(SolanaError::InvalidInput(format!(
+            "{mint} is not the mint of a token program"
+        )))
+    }
+}
+
+/// The size of a token account of the mint, with the extensions the associated token account
+/// program initializes.
+fn token_account_len(mint_account: &Account) -> Result<usize, SolanaError> {
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account.data)?;
+    let mut extensions =
+        ExtensionType::get_required_init_account_extensions(&mint.get_extension_types()?);
+    if mint_account.owner == spl_token_2022::id() {
+        extensions.push(ExtensionType::ImmutableOwner);
+    }
+    Ok(ExtensionType::try_calculate_account_len::<
+        spl_token_2022::state::Account,
+    >(&extensions)?)
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<Pubkey, SolanaError> {
+    Pubkey::from_str(pubkey.trim()).map_err(|_| SolanaError::InvalidPubkey(pubkey.to_string()))
+}
+
+fn parse_signature(signature: &str) -> Result<Signature, SolanaError> {
+    Signature::from_str(signature.trim())
+        .map_err(|_| SolanaError::InvalidInput(format!("invalid signature {signature}")))
+}
+
+/// Converts an amount in whole tokens, e.g. `1.5`, to the smallest unit.
+pub fn parse_amount(amount: &str, decimals: u8) -> Result<u64, SolanaError> {
+    let invalid = || SolanaError::InvalidInput(format!("invalid amount {amount}"));
+    let amount = amount.trim();
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if fraction.len() > decimals as usize
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+        || (whole.is_empty() && fraction.is_empty())
+    {
+        return Err(invalid());
+    }
+    let digits = format!("{whole}{fraction:0<width$}", width = decimals as usize);
+    digits.parse::<u64>().map_err(|_| invalid())
+}
+
+/// Converts an amount in the smallest unit to whole tokens.
+pub fn format_amount(amount: u64, decimals: u8) -> String {
+    let digits = format!("{amount:0>width$}", width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{whole}.{fraction}")
+    }
+}
+
+/// Encodes a signed transaction in base64, the wire format of the RPC.
+pub fn encode_transaction(transaction: &Transaction) -> Result<String, SolanaError> {
+    Ok(STANDARD.encode(bincode::serialize(transaction)?))
+}
+
+/// Decodes a base64 encoded signed transaction.
+pub fn decode_transaction(transaction: &str) -> Result<Transaction, SolanaError> {
+    let transaction: Transaction = bincode::deserialize(&STANDARD.decode(transaction.trim())?)?;
+    if transaction.signatures.is_empty() {
+        return Err(SolanaError::InvalidInput(
+            "the transaction is not signed".to_string(),
+        ));
+    }
+    Ok(transaction)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BalanceInput {
+    /// The address of the wallet.
+    pub address: String,
+    /// The mint of an SPL token, SOL when omitted.
+    #[serde(default)]
+    pub mint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceOutput {
+    pub address: String,
+    pub mint: Option<String>,
+    /// The balance in the smallest unit, e.g. lamports.
+    pub amount: u64,
+    pub decimals: u8,
+    /// The balance in whole tokens.
+    pub formatted: String,
+}
+
+/// Queries the SOL balance of a wallet, or its balance of an SPL token.
+pub struct SolanaBalanceTool {
+    config: SolanaConfig,
+}
+
+impl SolanaBalanceTool {
+    pub fn new(config: SolanaConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl StructureTool for SolanaBalanceTool {
+    type Input = BalanceInput;
+    type Output = BalanceOutput;
+
+    fn name(&self) -> &str {
+        "solana_get_balance"
+    }
+
+    fn description(&self) -> &str {
+        "Gets the SOL balance of a wallet, or its SPL token balance when a mint is given."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let client = self.config.client();
+        let address = parse_pubkey(&input.address)?;
+        let (amount, decimals) = match &input.mint {
+            Some(mint) => {
+                let mint = parse_pubkey(mint)?;
+                let token_program = mint_account(&client, &mint).await?.owner;
+                let account =
+                    get_associated_token_address_with_program_id(&address, &mint, &token_program);
+                let exists = client
+                    .get_account_with_commitment(&account, client.commitment())
+                    .await
+                    .map_err(SolanaError::from)?
+                    .value
+                    .is_some();
+                if exists {
+                    let balance = client
+                        .get_token_account_balance(&account)
+                        .await
+                        .map_err(SolanaError::from)?;
+                    (
+                        balance
+                            .amount
+                            .parse()
+                            .map_err(|_| ToolError::InvalidOutput)?,
+                        balance.decimals,
+                    )
+                } else {
+                    // A wallet without a token account holds none of the token.
+                    (
+                        0,
+                        client
+                            .get_token_supply(&mint)
+                            .await
+                            .map_err(SolanaError::from)?
+                            .decimals,
+                    )
+                }
+            }
+            None => (
+                client
+                    .get_balance(&address)
+                    .await
+                    .map_err(SolanaError::from)?,
+                SOL_DECIMALS,
+            ),
+        };
+        Ok(BalanceOutput {
+            address: input.address,
+            mint: input.mint,
+            amount,
+            decimals,
+            formatted: format_amount(amount, decimals),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AccountInput {
+    /// The address of the account.
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountInfo {
+    pub address: String,
+    pub lamports: u64,
+    /// The program owning the account.
+    pub owner: String,
+    pub executable: bool,
+    pub rent_epoch: u64,
+    pub data_len: usize,
+    /// The base64 encoded data of the account.
+    pub data: String,
+}
+
+/// Looks up an account.
+pub struct SolanaAccountTool {
+    config: SolanaConfig,
+}
+
+impl SolanaAccountTool {
+    pub fn new(config: SolanaConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl StructureTool for SolanaAccountTool {
+    type Input = AccountInput;
+    type Output = Option<AccountInfo>;
+
+    fn name(&self) -> &str {
+        "solana_get_account"
+    }
+
+    fn description(&self) -> &str {
+        "Gets the lamports, owner program and data of an account. Returns null when the account doesn't exist."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let account = self
+            .config
+            .client()
+            .get_account_with_commitment(&parse_pubkey(&input.address)?, self.config.commitment)
+            .await
+            .map_err(SolanaError::from)?
+            .value;
+        Ok(account.map(|account| AccountInfo {
+            address: input.address,
+            lamports: account.lamports,
+            owner: account.owner.to_string(),
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+            data_len: account.data.len(),
+            data: STANDARD.encode(&account.data),
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TransferInput {
+    /// The address of the recipient wallet.
+    pub to: String,
+    /// The amount in whole tokens, e.g. `1.5`.
+    pub amount: String,
+    /// The mint of an SPL token, SOL when omitted.
+    #[serde(default)]
+    pub mint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTransaction {
+    pub signature: String,
+    /// The base64 encoded signed transaction, to be simulated or sent.
+    pub transaction: String,
+}
+
+/// Builds and signs a transfer of SOL or of an SPL token, without sending it.
+pub struct SolanaSignTransferTool {
+    config: SolanaConfig,
+}
+
+impl SolanaSignTransferTool {
+    pub fn new(config: SolanaConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl StructureTool for SolanaSignTransferTool {
+    type Input = TransferInput;
+    type Output = SignedTransaction;
+
+    fn name(&self) -> &str {
+        "solana_sign_transfer"
+    }
+
+    fn description(&self) -> &str {
+        "Builds and signs a transfer of SOL, or of an SPL token when a mint is given, without sending it."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let transaction = self.config.transfer(&input).await?;
+        Ok(SignedTransaction {
+            signature: transaction.signatures[0].to_string(),
+            transaction: encode_transaction(&transaction)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TransactionInput {
+    /// The base64 encoded signed transaction.
+    pub transaction: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationOutput {
+    /// The error of the transaction, none when it would succeed.
+    pub err: Option<String>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+}
+
+/// Simulates a signed transaction.
+pub struct SolanaSimulateTransactionTool {
+    config: SolanaConfig,
+}
+
+impl SolanaSimulateTransactionTool {
+    pub fn new(config: SolanaConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl StructureTool for SolanaSimulateTransactionTool {
+    type Input = TransactionInput;
+    type Output = SimulationOutput;
+
+    fn name(&self) -> &str {
+        "solana_simulate_transaction"
+    }
+
+    fn description(&self) -> &str {
+        "Simulates a signed transaction and returns its error if it would fail, its logs and the compute units consumed."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let transaction = decode_transaction(&input.transaction)?;
+        let result = self
+            .config
+            .client()
+            .simulate_transaction(&transaction)
+            .await
+            .map_err(SolanaError::from)?
+            .value;
+        Ok(SimulationOutput {
+            err: result.err.map(|err| err.to_string()),
+            logs: result.logs.unwrap_or_default(),
+            units_consumed: result.units_consumed,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentTransaction {
+    pub signature: String,
+}
+
+/// Sends a signed transaction. When there is a policy, only the transactions signed under it
+/// are sent, after being simulated again if required.
+pub struct SolanaSendTransactionTool {
+    config: SolanaConfig,
+}
+
+impl SolanaSendTransactionTool {
+    pub fn new(config: SolanaConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl StructureTool for SolanaSendTransactionTool {
+    type Input = TransactionInput;
+    type Output = SentTransaction;
+
+    fn name(&self) -> &str {
+        "solana_send_transaction"
+    }
+
+    fn description(&self) -> &str {
+        "Sends a signed transaction and returns its signature."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let transaction = decode_transaction(&input.transaction)?;
+        if let Some(policy) = &self.config.policy {
+            let signature = transaction.signatures[0].to_string();
+            if policy.requires_simulation(SOLANA_CHAIN) {
+                if let Some(reason) = self.config.simulation_error(&transaction).await? {
+                    let reason = format!("the simulation of the transaction failed: {reason}");
+                    return Err(policy.deny_send(&signature, &reason).into());
+                }
+            }
+            policy.check_send(SOLANA_CHAIN, &signature)?;
+        }
+        let signature = self
+            .config
+            .client()
+            .send_transaction(&transaction)
+            .await
+            .map_err(SolanaError::from)?;
+        Ok(SentTransaction {
+            signature: signature.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SignatureStatusInput {
+    /// The signature of the transaction.
+    pub signature: String,
+    /// How long to wait for the transaction to be confirmed, in seconds, at most 120. The
+    /// status is checked once when omitted.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureStatus {
+    pub signature: String,
+    /// One of `not_found`, `processed`, `confirmed` or `finalized`.
+    pub status: String,
+    pub slot: Option<u64>,
+    /// The error of the transaction, none when it succeeded.
+    pub err: Option<String>,
+}
+
+/// Gets the status of a transaction, polling until it is confirmed.
+pub struct SolanaSignatureStatusTool {
+    config: SolanaConfig,
+}
+
+impl SolanaSignatureStatusTool {
+    pub fn new(config: SolanaConfig) -> Self {
+        Self { config }
+    }
+
+    async fn status(
+        &self,
+        client: &RpcClient,
+        signature: &Signature,
+    ) -> Result<SignatureStatus, SolanaError> {
+        let status = client
+            .get_signature_statuses(&[*signature])
+            .await?
+            .value
+            .into_iter()
+            .next()
+            .flatten();
+        Ok(match status {
+            Some(status) => SignatureStatus {
+                signature: signature.to_string(),
+                status: match status.confirmation_status {
+                    Some(TransactionConfirmationStatus::Finalized) => "finalized",
+                    Some(TransactionConfirmationStatus::Confirmed) => "confirmed",
+                    _ => "processed",
+                }
+                .to_string(),
+                slot: Some(status.slot),
+                err: status.err.map(|err| err.to_string()),
+            },
+            None => SignatureStatus {
+                signature: signature.to_string(),
+                status: "not_found".to_string(),
+                slot: None,
+                err: None,
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl StructureTool for SolanaSignatureStatusTool {
+    type Input = SignatureStatusInput;
+    type Output = SignatureStatus;
+
+    fn name(&self) -> &str {
+        "solana_get_signature_status"
+    }
+
+    fn description(&self) -> &str {
+        "Gets the status of a transaction by its signature, waiting up to the timeout for it to be confirmed."
+    }
+
+    async fn run_with_args(&self, input: Self::Input) -> Result<Self::Output, ToolError> {
+        let client = self.config.client();
+        let signature = parse_signature(&input.signature)?;
+        let deadline = status_deadline(Instant::now(), input.timeout);
+        loop {
+            let status = self.status(&client, &signature).await?;
+            let done =
+                matches!(status.status.as_str(), "confirmed" | "finalized") || status.err.is_some();
+            if done || Instant::now() >= deadline {
+                return Ok(status);
+            }
+            tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// When to stop waiting for a transaction, the timeout being clamped to
+/// [`MAX_STATUS_TIMEOUT`].
+fn status_deadline(now: Instant, timeout: Option<u64>) -> Instant {
+    let timeout = Duration::from_secs(timeout.unwrap_or_default()).min(MAX_STATUS_TIMEOUT);
+    now.checked_add(timeout).unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amounts() {
+        assert_eq!(parse_amount("1.5", SOL_DECIMALS).unwrap(), 1_500_000_000);
+        assert_eq!(parse_amount("0.000000001", SOL_DECIMALS).unwrap(), 1);
+        assert_eq!(parse_amount("2", 6).unwrap(), 2_000_000);
+        assert!(parse_amount("0.0000000001", SOL_DECIMALS).is_err());
+        assert!(parse_amount("-1", SOL_DECIMALS).is_err());
+        assert!(parse_amount(".", SOL_DECIMALS).is_err());
+
+        assert_eq!(format_amount(1_500_000_000, SOL_DECIMALS), "1.5");
+        assert_eq!(format_amount(1, SOL_DECIMALS), "0.000000001");
+        assert_eq!(format_amount(2_000_000, 6), "2");
+    }
+
+    #[test]
+    fn test_transfer_intent_policy() {
+        use super::super::policy::{ChainPolicy, SpendLimit, Web3Policy};
+        use std::collections::{HashMap, HashSet};
+
+        const MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let to = Keypair::new().pubkey();
+        let mint = parse_pubkey(&format!(" {MINT}\n")).unwrap();
+        let intent = transfer_intent(&to, Some(&mint), 2_000_000, 2_039_280);
+        assert_eq!(intent.contract.as_deref(), Some(MINT));
+        assert_eq!(
+            intent.spends,
+            [
+                Spend::new(MINT, 2_000_000),
+                Spend::new(NATIVE_ASSET, 2_039_280)
+            ]
+        );
+
+        let policy = |chain: ChainPolicy| {
+            PolicyEngine::new(Web3Policy::default().chain(SOLANA_CHAIN, chain))
+        };
+        let limits = |asset: &str, max: u128| ChainPolicy {
+            limits: HashMap::from([(
+                asset.to_string(),
+                SpendLimit {
+                    max_per_transaction: Some(max),
+                    daily_cap: None,
+                },
+            )]),
+            ..Default::default()
+        };
+        assert!(policy(limits(MINT, 1_000_000)).check(&intent).is_err());
+        assert!(policy(limits(NATIVE_ASSET, 1_000_000))
+            .check(&intent)
+            .is_err());
+        assert!(policy(limits(MINT, 2_000_000)).check(&intent).is_ok());
+        let denied = ChainPolicy {
+            denied_addresses: HashSet::from([MINT.to_string()]),
+            ..Default::default()
+        };
+        assert!(policy(denied).check(&intent).is_err());
+    }
+
+    #[test]
+    fn test_status_deadline() {
+        let now = Instant::now();
+        assert_eq!(status_deadline(now, None), now);
+        assert_eq!(
+            status_deadline(now, Some(30)),
+            now + Duration::from_secs(30)
+        );
+        assert_eq!(
+            status_deadline(now, Some(u64::MAX)),
+            now + MAX_STATUS_TIMEOUT
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local solana-test-validator"]
+    async fn test_transfer_on_test_validator() {
+        let config = SolanaConfig::local().keypair(Keypair::new());
+        let payer = config.pubkey().unwrap();
+        let airdrop = config
+            .client()
+            .request_airdrop(&payer, 2_000_000_000)
+            .await
+            .unwrap();
+        let status = SolanaSignatureStatusTool::new(config.clone())
+            .run_with_args(SignatureStatusInput {
+                signature: airdrop.to_string(),
+                timeout: Some(30),
+            })
+            .await
+            .unwrap();
+        assert_eq!(status.status, "confirmed");
+
+        let to = Keypair::new().pubkey().to_string();
+        let signed = SolanaSignTransferTool::new(config.clone())
+            .run_with_args(TransferInput {
+                to: to.clone(),
+                amount: "0.5".to_string(),
+                mint: None,
+            })
+            .await
+            .unwrap();
+        let simulation = SolanaSimulateTransactionTool::new(config.clone())
+            .run_with_args(TransactionInput {
+                transaction: signed.transaction.clone(),
+            })
+            .await
+            .unwrap();
+        assert!(simulation.err.is_none());
+
+        let sent = SolanaSendTransactionTool::new(config.clone())
+            .run_with_args(TransactionInput {
+                transaction: signed.transaction,
+            })
+            .await
+            .unwrap();
+        assert_eq!(sent.signature, signed.signature);
+        SolanaSignatureStatusTool::new(config.clone())
+            .run_with_args(SignatureStatusInput {
+                signature: sent.signature,
+                timeout: Some(30),
+            })
+            .await
+            .unwrap();
+
+        let balance = SolanaBalanceTool::new(config)
+            .run_with_args(BalanceInput {
+                address: to,
+                mint: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(balance.formatted, "0.5");
+    }
+}